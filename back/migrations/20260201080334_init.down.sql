@@ -0,0 +1,16 @@
+drop table agent_policy;
+drop table agent;
+drop table osago_policy;
+drop table medassistance_policy_member;
+drop table medassistance_policy;
+drop table green_card_policy;
+drop table car;
+drop table policy;
+drop table person;
+
+drop type osago_zone;
+drop type car_insurance_period_unit;
+drop type policy_status;
+drop type policy_type;
+drop type person_status;
+drop type sex;
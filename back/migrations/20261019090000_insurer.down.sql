@@ -0,0 +1,3 @@
+alter table policy drop column insurer_id;
+
+drop table insurer;
@@ -0,0 +1,15 @@
+create table insurer (
+    id serial primary key,
+    name varchar(255) not null unique
+);
+
+alter table policy add column insurer_id int references insurer(id);
+
+-- Policies created before insurers were tracked get a placeholder insurer
+insert into insurer (name)
+select 'Невідомий страховик'
+where exists (select 1 from policy);
+
+update policy set insurer_id = (select min(id) from insurer);
+
+alter table policy alter column insurer_id set not null;
@@ -0,0 +1,4 @@
+-- Only the records go, the stored files stay where they are
+drop table attachment;
+
+drop type attachment_owner;
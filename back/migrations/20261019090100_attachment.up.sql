@@ -0,0 +1,16 @@
+create type attachment_owner as enum ('policy', 'person', 'car');
+
+create table attachment (
+    id serial primary key,
+    owner_type attachment_owner not null,
+    owner_id int not null,
+    file_name varchar(255) not null,
+    content_type varchar(255) not null,
+    size_bytes bigint not null,
+    sha256 char(64) not null,
+    storage_key varchar(255) not null unique,
+    uploaded_by varchar(255) null,
+    uploaded_at timestamptz not null default now()
+);
+
+create index attachment_owner_idx on attachment (owner_type, owner_id);
@@ -0,0 +1,4 @@
+-- pg_trgm stays, other schemas on the server may use it
+drop index person_birth_date_idx;
+
+alter table person drop column merged_into_id;
@@ -0,0 +1,6 @@
+create extension if not exists pg_trgm;
+
+-- Set on records archived by a merge, pointing at the surviving person
+alter table person add column merged_into_id int null references person(id);
+
+create index person_birth_date_idx on person (birth_date);
@@ -0,0 +1,5 @@
+drop index car_plate_idx;
+
+-- Not created while the data held duplicates
+drop index if exists car_chassis_key;
+drop index if exists person_tax_number_key;
@@ -0,0 +1,32 @@
+-- Data migrated from Access may still hold duplicates. The indexes are only
+-- created once those are merged, and the application checks for matches
+-- before inserting either way.
+do $$
+begin
+    if not exists (
+        select 1 from person
+        where tax_number <> '' and status <> 'archived'
+        group by tax_number
+        having count(*) > 1
+    ) then
+        create unique index person_tax_number_key on person (tax_number)
+            where tax_number <> '' and status <> 'archived';
+    else
+        raise notice 'person.tax_number has duplicates, unique index not created';
+    end if;
+
+    if not exists (
+        select 1 from car
+        where chassis <> ''
+        group by upper(chassis)
+        having count(*) > 1
+    ) then
+        create unique index car_chassis_key on car (upper(chassis))
+            where chassis <> '';
+    else
+        raise notice 'car.chassis has duplicates, unique index not created';
+    end if;
+end
+$$;
+
+create index car_plate_idx on car (upper(regexp_replace(plate, '[\s-]', '', 'g')));
@@ -0,0 +1,10 @@
+drop index person_email_trgm_idx;
+drop index person_phone2_trgm_idx;
+drop index person_phone_trgm_idx;
+drop index person_tax_number_trgm_idx;
+drop index person_name_lat_trgm_idx;
+drop index person_name_trgm_idx;
+
+drop function person_name_lat_doc(text, text, text, text, text, text);
+drop function person_name_doc(text, text, text);
+drop function translit_uk(text);
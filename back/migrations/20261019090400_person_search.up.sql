@@ -0,0 +1,66 @@
+-- Ukrainian to Latin transliteration per the 2010 Cabinet of Ministers
+-- resolution No. 55
+create function translit_uk(input text) returns text
+language sql immutable strict parallel safe
+as $$
+    select translate(
+        replace(replace(replace(replace(replace(replace(replace(replace(replace(replace(replace(
+            -- є, ї, й, ю, я are spelled differently at the start of a word
+            regexp_replace(regexp_replace(regexp_replace(regexp_replace(regexp_replace(
+                lower(input),
+                '(^|[^а-яіїєґ''’ʼ])є', '\1ye', 'g'),
+                '(^|[^а-яіїєґ''’ʼ])ї', '\1yi', 'g'),
+                '(^|[^а-яіїєґ''’ʼ])й', '\1y', 'g'),
+                '(^|[^а-яіїєґ''’ʼ])ю', '\1yu', 'g'),
+                '(^|[^а-яіїєґ''’ʼ])я', '\1ya', 'g'),
+            'зг', 'zgh'),
+            'ж', 'zh'),
+            'х', 'kh'),
+            'ц', 'ts'),
+            'ч', 'ch'),
+            'щ', 'shch'),
+            'ш', 'sh'),
+            'є', 'ie'),
+            'ю', 'iu'),
+            'я', 'ia'),
+            'ї', 'i'),
+        -- Soft sign and apostrophes have no Latin counterpart and are dropped
+        'абвгґдезиійклмнопрстуфь''’ʼ',
+        'abvhgdezyiiklmnoprstuf'
+    )
+$$;
+
+create function person_name_doc(last_name text, first_name text, patronymic_name text) returns text
+language sql immutable parallel safe
+as $$
+    select lower(last_name || ' ' || first_name || coalesce(' ' || patronymic_name, ''))
+$$;
+
+-- Stored Latin names where present, transliterated Cyrillic otherwise
+create function person_name_lat_doc(
+    last_name text,
+    first_name text,
+    patronymic_name text,
+    last_name_lat text,
+    first_name_lat text,
+    patronymic_name_lat text
+) returns text
+language sql immutable parallel safe
+as $$
+    select lower(
+        coalesce(last_name_lat, translit_uk(last_name))
+        || ' ' || coalesce(first_name_lat, translit_uk(first_name))
+        || coalesce(' ' || coalesce(patronymic_name_lat, translit_uk(patronymic_name)), '')
+    )
+$$;
+
+create index person_name_trgm_idx on person
+    using gin (person_name_doc(last_name, first_name, patronymic_name) gin_trgm_ops);
+create index person_name_lat_trgm_idx on person
+    using gin (person_name_lat_doc(last_name, first_name, patronymic_name, last_name_lat, first_name_lat, patronymic_name_lat) gin_trgm_ops);
+create index person_tax_number_trgm_idx on person using gin (tax_number gin_trgm_ops);
+create index person_phone_trgm_idx on person
+    using gin (regexp_replace(phone, '[^0-9]', '', 'g') gin_trgm_ops);
+create index person_phone2_trgm_idx on person
+    using gin (regexp_replace(coalesce(phone2, ''), '[^0-9]', '', 'g') gin_trgm_ops);
+create index person_email_trgm_idx on person using gin (lower(email) gin_trgm_ops);
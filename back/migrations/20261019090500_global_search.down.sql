@@ -0,0 +1,6 @@
+drop index policy_number_key_trgm_idx;
+drop index car_make_model_trgm_idx;
+drop index car_chassis_key_trgm_idx;
+drop index car_plate_key_trgm_idx;
+
+drop function ident_key(text);
@@ -0,0 +1,16 @@
+-- Plates, chassis and policy numbers get typed with Cyrillic and Latin
+-- look-alike letters interchangeably, so both sides are folded to Latin
+create function ident_key(input text) returns text
+language sql immutable strict parallel safe
+as $$
+    select translate(
+        upper(regexp_replace(input, '[\s-]', '', 'g')),
+        'АВЕІКМНОРСТХ',
+        'ABEIKMHOPCTX'
+    )
+$$;
+
+create index car_plate_key_trgm_idx on car using gin (ident_key(plate) gin_trgm_ops);
+create index car_chassis_key_trgm_idx on car using gin (ident_key(chassis) gin_trgm_ops);
+create index car_make_model_trgm_idx on car using gin (lower(make || ' ' || model) gin_trgm_ops);
+create index policy_number_key_trgm_idx on policy using gin (ident_key(series || number) gin_trgm_ops);
@@ -0,0 +1,6 @@
+drop table notification_log;
+
+drop type notification_channel;
+drop type notification_kind;
+
+alter table agent drop column email;
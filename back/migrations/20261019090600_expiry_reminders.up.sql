@@ -0,0 +1,21 @@
+alter table agent add column email varchar(255) null;
+
+create type notification_kind as enum ('expiry_reminder', 'expiry_digest');
+create type notification_channel as enum ('email');
+
+-- One row per reminder sent. A reminder is identified by its policy, recipient
+-- and the end date it warned about, so a prolonged policy gets reminded again.
+create table notification_log (
+    id serial primary key,
+    kind notification_kind not null,
+    channel notification_channel not null,
+    policy_id int not null references policy(id),
+    recipient varchar(255) not null,
+    end_date date not null,
+    days_before int not null,
+    sent_at timestamptz not null default now()
+);
+
+create unique index notification_log_dedupe_key
+    on notification_log (kind, channel, policy_id, recipient, end_date, days_before);
+create index notification_log_policy_idx on notification_log (policy_id);
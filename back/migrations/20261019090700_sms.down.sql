@@ -0,0 +1,11 @@
+-- Postgres cannot drop enum values, so 'payment_reminder' and 'sms' stay in
+-- notification_kind and notification_channel
+drop table sms_message;
+
+drop type sms_status;
+
+drop table policy_payment;
+
+alter table person drop column sms_opt_out;
+
+alter table notification_log rename column due_date to end_date;
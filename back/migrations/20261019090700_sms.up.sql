@@ -0,0 +1,39 @@
+alter type notification_kind add value 'payment_reminder';
+alter type notification_channel add value 'sms';
+
+-- The date a reminder is about: the policy end date or a payment due date
+alter table notification_log rename column end_date to due_date;
+
+alter table person add column sms_opt_out boolean not null default false;
+
+create table policy_payment (
+    id serial primary key,
+    policy_id int not null references policy(id),
+    due_date date not null,
+    amount int not null,
+    paid_on date null
+);
+
+create index policy_payment_policy_idx on policy_payment (policy_id);
+create index policy_payment_unpaid_idx on policy_payment (due_date) where paid_on is null;
+
+create type sms_status as enum ('sent', 'delivered', 'failed');
+
+-- Every SMS handed to the provider, including failed attempts
+create table sms_message (
+    id serial primary key,
+    kind notification_kind not null,
+    person_id int not null references person(id),
+    policy_id int null references policy(id),
+    phone varchar(20) not null,
+    text text not null,
+    provider varchar(50) not null,
+    provider_message_id varchar(255) null,
+    status sms_status not null,
+    error text null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now()
+);
+
+create index sms_message_person_idx on sms_message (person_id);
+create index sms_message_pending_idx on sms_message (created_at) where status = 'sent';
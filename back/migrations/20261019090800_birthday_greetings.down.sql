@@ -0,0 +1,8 @@
+-- Postgres cannot drop enum values, so 'birthday_greeting' stays in notification_kind
+drop table birthday_greeting;
+
+drop type birthday_greeting_status;
+
+drop index person_birthday_idx;
+
+alter table person drop column birthday_opt_out;
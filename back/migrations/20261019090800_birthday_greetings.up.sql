@@ -0,0 +1,21 @@
+alter type notification_kind add value if not exists 'birthday_greeting';
+
+alter table person add column birthday_opt_out boolean not null default false;
+
+create index person_birthday_idx on person ((extract(month from birth_date)), (extract(day from birth_date)));
+
+create type birthday_greeting_status as enum ('sent', 'cancelled');
+
+-- A greeting that went out, or one an agent cancelled before it did
+create table birthday_greeting (
+    id serial primary key,
+    person_id int not null references person(id),
+    greeting_date date not null,
+    status birthday_greeting_status not null,
+    channel notification_channel null,
+    recipient varchar(255) null,
+    text text null,
+    cancelled_by varchar(255) null,
+    created_at timestamptz not null default now(),
+    unique (person_id, greeting_date)
+);
@@ -0,0 +1,11 @@
+drop table webhook_attempt;
+
+drop table webhook_delivery;
+
+drop type webhook_delivery_status;
+
+drop table webhook_event;
+
+drop table webhook_subscription;
+
+drop type webhook_event_type;
@@ -0,0 +1,45 @@
+create type webhook_event_type as enum ('policy.created', 'policy.updated', 'policy.status_changed', 'person.created');
+
+create table webhook_subscription (
+    id serial primary key,
+    url text not null,
+    secret varchar(255) not null,
+    events webhook_event_type[] not null,
+    active boolean not null default true,
+    description varchar(255) null,
+    created_at timestamptz not null default now()
+);
+
+-- Outbox: written in the same transaction as the change the event describes
+create table webhook_event (
+    id serial primary key,
+    event_type webhook_event_type not null,
+    payload jsonb not null,
+    created_at timestamptz not null default now()
+);
+
+create type webhook_delivery_status as enum ('pending', 'delivered', 'failed');
+
+create table webhook_delivery (
+    id serial primary key,
+    event_id int not null references webhook_event(id),
+    subscription_id int not null references webhook_subscription(id) on delete cascade,
+    status webhook_delivery_status not null default 'pending',
+    attempts int not null default 0,
+    next_attempt_at timestamptz not null default now(),
+    delivered_at timestamptz null,
+    unique (event_id, subscription_id)
+);
+
+create index webhook_delivery_due_idx on webhook_delivery (next_attempt_at) where status = 'pending';
+
+create table webhook_attempt (
+    id serial primary key,
+    delivery_id int not null references webhook_delivery(id) on delete cascade,
+    attempted_at timestamptz not null default now(),
+    response_status int null,
+    error text null,
+    duration_ms int not null
+);
+
+create index webhook_attempt_delivery_idx on webhook_attempt (delivery_id);
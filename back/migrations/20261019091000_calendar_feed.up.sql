@@ -0,0 +1,7 @@
+-- Secret for subscribing to /calendar.ics, one per user. Only its SHA-256 is kept.
+create table calendar_token (
+    user_email varchar(255) primary key,
+    token_hash varchar(64) not null unique,
+    created_at timestamptz not null default now(),
+    last_used_at timestamptz null
+);
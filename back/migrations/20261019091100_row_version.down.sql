@@ -0,0 +1,3 @@
+alter table policy drop column version;
+alter table car drop column version;
+alter table person drop column version;
@@ -0,0 +1,4 @@
+-- Bumped by every update, so an edit based on an older read can be refused
+alter table person add column version int not null default 1;
+alter table car add column version int not null default 1;
+alter table policy add column version int not null default 1;
@@ -0,0 +1,16 @@
+-- First response to a POST sent with an Idempotency-Key, replayed to retries
+create table idempotency_key (
+    user_email varchar(255) not null,
+    key varchar(255) not null,
+    -- SHA-256 of the method, path and body, to tell retries from reused keys
+    request_hash varchar(64) not null,
+    -- Null while the first request is being handled
+    status smallint null,
+    content_type varchar(255) null,
+    body bytea null,
+    created_at timestamptz not null default now(),
+    expires_at timestamptz not null,
+    primary key (user_email, key)
+);
+
+create index idempotency_key_expires_at_idx on idempotency_key (expires_at);
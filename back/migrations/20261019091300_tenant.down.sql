@@ -0,0 +1,78 @@
+-- The insure_tenant role stays, other databases on the server may use it
+drop policy tenant_isolation on webhook_attempt;
+drop policy tenant_isolation on webhook_delivery;
+drop policy tenant_isolation on birthday_greeting;
+drop policy tenant_isolation on sms_message;
+drop policy tenant_isolation on notification_log;
+drop policy tenant_isolation on policy_payment;
+drop policy tenant_isolation on agent_policy;
+drop policy tenant_isolation on medassistance_policy_member;
+drop policy tenant_isolation on medassistance_policy;
+drop policy tenant_isolation on osago_policy;
+drop policy tenant_isolation on green_card_policy;
+drop policy tenant_isolation on policy;
+drop policy tenant_isolation on webhook_event;
+drop policy tenant_isolation on webhook_subscription;
+drop policy tenant_isolation on attachment;
+drop policy tenant_isolation on insurer;
+drop policy tenant_isolation on agent;
+drop policy tenant_isolation on car;
+drop policy tenant_isolation on person;
+
+alter table webhook_attempt disable row level security;
+alter table webhook_delivery disable row level security;
+alter table birthday_greeting disable row level security;
+alter table sms_message disable row level security;
+alter table notification_log disable row level security;
+alter table policy_payment disable row level security;
+alter table agent_policy disable row level security;
+alter table medassistance_policy_member disable row level security;
+alter table medassistance_policy disable row level security;
+alter table osago_policy disable row level security;
+alter table green_card_policy disable row level security;
+alter table policy disable row level security;
+alter table webhook_event disable row level security;
+alter table webhook_subscription disable row level security;
+alter table attachment disable row level security;
+alter table insurer disable row level security;
+alter table agent disable row level security;
+alter table car disable row level security;
+alter table person disable row level security;
+
+revoke all on all tables in schema public from insure_tenant;
+revoke all on all sequences in schema public from insure_tenant;
+
+do $$
+begin
+    if exists (select 1 from pg_indexes where indexname = 'person_tax_number_key') then
+        drop index person_tax_number_key;
+        create unique index person_tax_number_key on person (tax_number)
+            where tax_number <> '' and status <> 'archived';
+    end if;
+
+    if exists (select 1 from pg_indexes where indexname = 'car_chassis_key') then
+        drop index car_chassis_key;
+        create unique index car_chassis_key on car (upper(chassis))
+            where chassis <> '';
+    end if;
+end
+$$;
+
+alter table insurer drop constraint insurer_name_key;
+alter table insurer add constraint insurer_name_key unique (name);
+
+alter table calendar_token drop column tenant_id;
+alter table webhook_event drop column tenant_id;
+alter table webhook_subscription drop column tenant_id;
+alter table attachment drop column tenant_id;
+alter table insurer drop column tenant_id;
+alter table agent drop column tenant_id;
+alter table policy drop column tenant_id;
+alter table car drop column tenant_id;
+alter table person drop column tenant_id;
+
+drop function current_tenant();
+
+drop table tenant_user;
+
+drop table tenant;
@@ -0,0 +1,234 @@
+-- Agencies sharing this instance, with their data kept apart by row-level
+-- security. Request handlers run as `insure_tenant`, which only sees rows of
+-- the tenant named by the `app.tenant_id` setting; background jobs run as the
+-- owner and see every tenant.
+create table tenant (
+    id serial primary key,
+    name varchar(255) not null unique,
+    created_at timestamptz not null default now()
+);
+
+create table tenant_user (
+    tenant_id int not null references tenant (id) on delete cascade,
+    user_email varchar(255) not null,
+    primary key (tenant_id, user_email)
+);
+
+create index tenant_user_email_idx on tenant_user (user_email);
+
+-- Everything so far belongs to the agency that had the instance to itself
+insert into tenant (id, name) values (1, 'Default');
+select setval('tenant_id_seq', 1);
+
+insert into tenant_user (tenant_id, user_email) values
+    (1, 'dlike.version10@gmail.com'),
+    (1, 'yerig68@gmail.com'),
+    (1, 'ieremenko68@gmail.com');
+
+-- Tenant of the current request, null outside of one
+create function current_tenant() returns int
+language sql stable
+as $$ select nullif(current_setting('app.tenant_id', true), '')::int $$;
+
+-- Rows owned by a tenant directly. New ones get the tenant of the request.
+alter table person add column tenant_id int not null default 1 references tenant (id);
+alter table car add column tenant_id int not null default 1 references tenant (id);
+alter table policy add column tenant_id int not null default 1 references tenant (id);
+alter table agent add column tenant_id int not null default 1 references tenant (id);
+alter table insurer add column tenant_id int not null default 1 references tenant (id);
+alter table attachment add column tenant_id int not null default 1 references tenant (id);
+alter table webhook_subscription add column tenant_id int not null default 1 references tenant (id);
+alter table webhook_event add column tenant_id int not null default 1 references tenant (id);
+-- The feed shows the tenant that was active when its token was issued
+alter table calendar_token add column tenant_id int not null default 1 references tenant (id);
+
+alter table person alter column tenant_id set default current_tenant();
+alter table car alter column tenant_id set default current_tenant();
+alter table policy alter column tenant_id set default current_tenant();
+alter table agent alter column tenant_id set default current_tenant();
+alter table insurer alter column tenant_id set default current_tenant();
+alter table attachment alter column tenant_id set default current_tenant();
+alter table webhook_subscription alter column tenant_id set default current_tenant();
+alter table webhook_event alter column tenant_id set default current_tenant();
+alter table calendar_token alter column tenant_id set default current_tenant();
+
+create index person_tenant_idx on person (tenant_id);
+create index car_tenant_idx on car (tenant_id);
+create index policy_tenant_idx on policy (tenant_id);
+
+-- Tax numbers, chassis and insurer names only need to be unique per tenant
+alter table insurer drop constraint insurer_name_key;
+alter table insurer add constraint insurer_name_key unique (tenant_id, name);
+
+do $$
+begin
+    if exists (select 1 from pg_indexes where indexname = 'person_tax_number_key') then
+        drop index person_tax_number_key;
+        create unique index person_tax_number_key on person (tenant_id, tax_number)
+            where tax_number <> '' and status <> 'archived';
+    end if;
+
+    if exists (select 1 from pg_indexes where indexname = 'car_chassis_key') then
+        drop index car_chassis_key;
+        create unique index car_chassis_key on car (tenant_id, upper(chassis))
+            where chassis <> '';
+    end if;
+end
+$$;
+
+-- Roles are shared by every database on the server, so it may exist already.
+-- Creating it needs CREATEROLE; the owner has to be a member to switch to it.
+do $$
+begin
+    create role insure_tenant nologin;
+exception
+    when duplicate_object or unique_violation then null;
+end
+$$;
+
+grant insure_tenant to current_user;
+
+-- Granted table by table: a table added later stays out of reach until it is
+-- granted here along with a policy, rather than readable across tenants.
+grant select, insert, update, delete on
+    person,
+    car,
+    policy,
+    green_card_policy,
+    medassistance_policy,
+    medassistance_policy_member,
+    osago_policy,
+    agent,
+    agent_policy,
+    insurer,
+    policy_payment,
+    attachment,
+    notification_log,
+    sms_message,
+    birthday_greeting,
+    webhook_subscription,
+    webhook_event,
+    webhook_delivery,
+    webhook_attempt,
+    -- Scoped to the user rather than the tenant
+    calendar_token,
+    idempotency_key
+to insure_tenant;
+
+grant usage on all sequences in schema public to insure_tenant;
+
+alter table person enable row level security;
+create policy tenant_isolation on person to insure_tenant
+    using (tenant_id = current_tenant());
+
+alter table car enable row level security;
+create policy tenant_isolation on car to insure_tenant
+    using (tenant_id = current_tenant());
+
+alter table agent enable row level security;
+create policy tenant_isolation on agent to insure_tenant
+    using (tenant_id = current_tenant());
+
+alter table insurer enable row level security;
+create policy tenant_isolation on insurer to insure_tenant
+    using (tenant_id = current_tenant());
+
+alter table attachment enable row level security;
+create policy tenant_isolation on attachment to insure_tenant
+    using (tenant_id = current_tenant());
+
+alter table webhook_subscription enable row level security;
+create policy tenant_isolation on webhook_subscription to insure_tenant
+    using (tenant_id = current_tenant());
+
+alter table webhook_event enable row level security;
+create policy tenant_isolation on webhook_event to insure_tenant
+    using (tenant_id = current_tenant());
+
+-- Foreign keys are checked regardless of row-level security, so the checks
+-- below keep a tenant from linking to another tenant's rows by id.
+alter table policy enable row level security;
+create policy tenant_isolation on policy to insure_tenant
+    using (tenant_id = current_tenant())
+    with check (
+        tenant_id = current_tenant()
+        and exists (select 1 from person where person.id = policy.holder_id)
+        and exists (select 1 from insurer where insurer.id = policy.insurer_id)
+    );
+
+-- Rows that belong to a tenant through their parent, which the subqueries
+-- only see when it belongs to the current one
+alter table green_card_policy enable row level security;
+create policy tenant_isolation on green_card_policy to insure_tenant
+    using (exists (select 1 from policy where policy.id = green_card_policy.id))
+    with check (
+        exists (select 1 from policy where policy.id = green_card_policy.id)
+        and exists (select 1 from car where car.id = green_card_policy.car_id)
+    );
+
+alter table osago_policy enable row level security;
+create policy tenant_isolation on osago_policy to insure_tenant
+    using (exists (select 1 from policy where policy.id = osago_policy.id))
+    with check (
+        exists (select 1 from policy where policy.id = osago_policy.id)
+        and exists (select 1 from car where car.id = osago_policy.car_id)
+    );
+
+alter table medassistance_policy enable row level security;
+create policy tenant_isolation on medassistance_policy to insure_tenant
+    using (exists (select 1 from policy where policy.id = medassistance_policy.id));
+
+alter table medassistance_policy_member enable row level security;
+create policy tenant_isolation on medassistance_policy_member to insure_tenant
+    using (
+        exists (
+            select 1 from policy
+            where policy.id = medassistance_policy_member.medassistance_policy_id
+        )
+    )
+    with check (
+        exists (
+            select 1 from policy
+            where policy.id = medassistance_policy_member.medassistance_policy_id
+        )
+        and exists (select 1 from person where person.id = medassistance_policy_member.member_id)
+    );
+
+alter table agent_policy enable row level security;
+create policy tenant_isolation on agent_policy to insure_tenant
+    using (exists (select 1 from policy where policy.id = agent_policy.policy_id))
+    with check (
+        exists (select 1 from policy where policy.id = agent_policy.policy_id)
+        and exists (select 1 from agent where agent.id = agent_policy.agent_id)
+    );
+
+alter table policy_payment enable row level security;
+create policy tenant_isolation on policy_payment to insure_tenant
+    using (exists (select 1 from policy where policy.id = policy_payment.policy_id));
+
+alter table notification_log enable row level security;
+create policy tenant_isolation on notification_log to insure_tenant
+    using (exists (select 1 from policy where policy.id = notification_log.policy_id));
+
+alter table sms_message enable row level security;
+create policy tenant_isolation on sms_message to insure_tenant
+    using (exists (select 1 from person where person.id = sms_message.person_id));
+
+alter table birthday_greeting enable row level security;
+create policy tenant_isolation on birthday_greeting to insure_tenant
+    using (exists (select 1 from person where person.id = birthday_greeting.person_id));
+
+alter table webhook_delivery enable row level security;
+create policy tenant_isolation on webhook_delivery to insure_tenant
+    using (
+        exists (
+            select 1 from webhook_subscription
+            where webhook_subscription.id = webhook_delivery.subscription_id
+        )
+    );
+
+alter table webhook_attempt enable row level security;
+create policy tenant_isolation on webhook_attempt to insure_tenant
+    using (
+        exists (select 1 from webhook_delivery where webhook_delivery.id = webhook_attempt.delivery_id)
+    );
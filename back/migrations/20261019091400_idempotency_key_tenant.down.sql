@@ -0,0 +1,9 @@
+alter table idempotency_key drop constraint idempotency_key_pkey;
+
+-- Keep one response per key
+delete from idempotency_key a
+using idempotency_key b
+where a.user_email = b.user_email and a.key = b.key and a.tenant_id > b.tenant_id;
+
+alter table idempotency_key drop column tenant_id;
+alter table idempotency_key add primary key (user_email, key);
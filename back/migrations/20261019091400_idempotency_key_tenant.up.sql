@@ -0,0 +1,8 @@
+-- A key is used once per tenant, so a retry sent to another tenant of the
+-- same user is a request of its own there.
+alter table idempotency_key
+    add column tenant_id int not null default 1 references tenant on delete cascade;
+alter table idempotency_key alter column tenant_id drop default;
+
+alter table idempotency_key drop constraint idempotency_key_pkey;
+alter table idempotency_key add primary key (user_email, tenant_id, key);
@@ -0,0 +1,2 @@
+-- The indexes stay, they only enforce what the application checks anyway
+drop function create_unique_person_car_indexes();
@@ -0,0 +1,32 @@
+-- 20261019090300_unique_person_car skips the unique indexes on tax numbers
+-- and chassis while migrated data still has duplicates. This creates each one
+-- that is missing once its duplicates are merged; `back migrate
+-- unique-indexes` runs it again later.
+create function create_unique_person_car_indexes()
+returns void
+language plpgsql
+as $$
+begin
+    if to_regclass('person_tax_number_key') is null and not exists (
+        select 1 from person
+        where tax_number <> '' and status <> 'archived'
+        group by tenant_id, tax_number
+        having count(*) > 1
+    ) then
+        create unique index person_tax_number_key on person (tenant_id, tax_number)
+            where tax_number <> '' and status <> 'archived';
+    end if;
+
+    if to_regclass('car_chassis_key') is null and not exists (
+        select 1 from car
+        where chassis <> ''
+        group by tenant_id, upper(chassis)
+        having count(*) > 1
+    ) then
+        create unique index car_chassis_key on car (tenant_id, upper(chassis))
+            where chassis <> '';
+    end if;
+end
+$$;
+
+select create_unique_person_car_indexes();
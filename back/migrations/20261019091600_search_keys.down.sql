@@ -0,0 +1,6 @@
+alter table policy drop column number_key;
+alter table car drop column chassis_key, drop column plate_key;
+
+create index car_plate_key_trgm_idx on car using gin (ident_key(plate) gin_trgm_ops);
+create index car_chassis_key_trgm_idx on car using gin (ident_key(chassis) gin_trgm_ops);
+create index policy_number_key_trgm_idx on policy using gin (ident_key(series || number) gin_trgm_ops);
@@ -0,0 +1,16 @@
+-- Folding every plate, chassis and policy number with ident_key is the slow
+-- part of a broad search, e.g. a VIN prefix shared by thousands of cars. The
+-- planner counts the function as cheap and scans the table instead of the
+-- index, so the folded values are stored.
+alter table car
+    add column plate_key text generated always as (ident_key(plate)) stored,
+    add column chassis_key text generated always as (ident_key(chassis)) stored;
+alter table policy
+    add column number_key text generated always as (ident_key(series || number)) stored;
+
+drop index car_plate_key_trgm_idx;
+drop index car_chassis_key_trgm_idx;
+drop index policy_number_key_trgm_idx;
+create index car_plate_key_trgm_idx on car using gin (plate_key gin_trgm_ops);
+create index car_chassis_key_trgm_idx on car using gin (chassis_key gin_trgm_ops);
+create index policy_number_key_trgm_idx on policy using gin (number_key gin_trgm_ops);
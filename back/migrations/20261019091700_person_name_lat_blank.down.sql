@@ -0,0 +1,18 @@
+create or replace function person_name_lat_doc(
+    last_name text,
+    first_name text,
+    patronymic_name text,
+    last_name_lat text,
+    first_name_lat text,
+    patronymic_name_lat text
+) returns text
+language sql immutable parallel safe
+as $$
+    select lower(
+        coalesce(last_name_lat, translit_uk(last_name))
+        || ' ' || coalesce(first_name_lat, translit_uk(first_name))
+        || coalesce(' ' || coalesce(patronymic_name_lat, translit_uk(patronymic_name)), '')
+    )
+$$;
+
+reindex index person_name_lat_trgm_idx;
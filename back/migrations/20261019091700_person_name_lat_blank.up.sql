@@ -0,0 +1,21 @@
+-- Blank Latin names, as saved by forms and imports, hid the transliteration
+-- of the Cyrillic name from search
+create or replace function person_name_lat_doc(
+    last_name text,
+    first_name text,
+    patronymic_name text,
+    last_name_lat text,
+    first_name_lat text,
+    patronymic_name_lat text
+) returns text
+language sql immutable parallel safe
+as $$
+    select lower(
+        coalesce(nullif(trim(last_name_lat), ''), translit_uk(last_name))
+        || ' ' || coalesce(nullif(trim(first_name_lat), ''), translit_uk(first_name))
+        || coalesce(' ' || coalesce(nullif(trim(patronymic_name_lat), ''), translit_uk(patronymic_name)), '')
+    )
+$$;
+
+-- The index holds values of the old definition
+reindex index person_name_lat_trgm_idx;
@@ -0,0 +1,9 @@
+-- Keep the newest token of each user
+delete from calendar_token t
+using calendar_token newer
+where
+    newer.user_email = t.user_email
+    and (newer.created_at, newer.tenant_id) > (t.created_at, t.tenant_id);
+
+alter table calendar_token drop constraint calendar_token_pkey;
+alter table calendar_token add primary key (user_email);
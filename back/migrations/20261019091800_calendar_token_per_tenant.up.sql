@@ -0,0 +1,3 @@
+-- One feed token per user and tenant, so issuing one in a tenant keeps the others
+alter table calendar_token drop constraint calendar_token_pkey;
+alter table calendar_token add primary key (user_email, tenant_id);
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::config::GoogleConfig;
+
 #[derive(Debug, Serialize)]
 pub struct TokenRequest {
     pub code: String,
@@ -36,11 +38,10 @@ pub struct GoogleOAuthClient {
 }
 
 impl GoogleOAuthClient {
-    pub fn from_env() -> Self {
+    pub fn new(config: &GoogleConfig) -> Self {
         Self {
-            client_id: std::env::var("GOOGLE_CLIENT_ID").expect("GOOGLE_CLIENT_ID must be set"),
-            client_secret: std::env::var("GOOGLE_CLIENT_SECRET")
-                .expect("GOOGLE_CLIENT_SECRET must be set"),
+            client_id: config.client_id.clone(),
+            client_secret: config.client_secret.clone(),
             http_client: reqwest::Client::new(),
         }
     }
@@ -1,18 +1,28 @@
-use axum::{extract::Request, http::StatusCode, middleware::Next, response::Response};
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::{http::StatusCode, middleware::Next, response::Response};
 use tower_cookies::Cookies;
 use tracing::Span;
 
 use super::cookie::{AuthSession, get_auth_session, set_auth_cookie};
 use super::google::GoogleOAuthClient;
-
-const ALLOWED_USERS: &[&str] = &[
-    "dlike.version10@gmail.com",
-    "yerig68@gmail.com",
-    "ieremenko68@gmail.com",
-];
-
-pub fn is_allowed_user(email: &str) -> bool {
-    ALLOWED_USERS.contains(&email)
+use crate::config::Config;
+use crate::endpoints::calendar::FEED_PATH;
+use crate::endpoints::monitoring::PUBLIC_PATHS;
+use crate::openapi::{DOCS_PATH, SPEC_PATH};
+
+/// Routes that skip the session check: the auth flow itself, the calendar
+/// feed, which checks its own token, health checks and metrics, and the API
+/// documentation.
+pub fn is_public(path: &str) -> bool {
+    path.starts_with("/auth/")
+        || path == FEED_PATH
+        || PUBLIC_PATHS.contains(&path)
+        || path == SPEC_PATH
+        || path
+            .strip_prefix(DOCS_PATH)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
 }
 
 #[derive(Clone)]
@@ -29,20 +39,20 @@ pub async fn store_http_span_middleware(mut request: Request, next: Next) -> Res
 }
 
 pub async fn auth_middleware(
+    State(config): State<Arc<Config>>,
     cookies: Cookies,
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
     let path = request.uri().path();
 
-    // Skip auth for auth routes
-    if path.starts_with("/auth/") {
+    if is_public(path) {
         return Ok(next.run(request).await);
     }
 
     let session = get_auth_session(&cookies).ok_or(StatusCode::UNAUTHORIZED)?;
 
-    let google_client = GoogleOAuthClient::from_env();
+    let google_client = GoogleOAuthClient::new(&config.google);
 
     // Check if access token is expired and needs refresh
     let session = if session.is_access_token_expired() {
@@ -60,10 +70,7 @@ pub async fn auth_middleware(
         );
 
         // Update cookie with new tokens
-        let secure = std::env::var("SECURE_COOKIES")
-            .map(|v| v == "true")
-            .unwrap_or(false);
-        set_auth_cookie(&cookies, &new_session, secure);
+        set_auth_cookie(&cookies, &new_session, config.secure_cookies);
 
         new_session
     } else {
@@ -86,24 +93,3 @@ pub async fn auth_middleware(
 
     Ok(next.run(request).await)
 }
-
-pub async fn allowed_users_middleware(
-    request: Request,
-    next: Next,
-) -> Result<Response, StatusCode> {
-    let path = request.uri().path();
-
-    if path.starts_with("/auth/") {
-        return Ok(next.run(request).await);
-    }
-
-    let user = request.extensions().get::<AuthUser>();
-
-    if let Some(user) = user
-        && is_allowed_user(&user.email)
-    {
-        Ok(next.run(request).await)
-    } else {
-        Err(StatusCode::UNAUTHORIZED)
-    }
-}
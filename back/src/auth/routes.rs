@@ -1,73 +1,94 @@
+use std::sync::Arc;
+
 use axum::{
-    extract::Json,
+    extract::{Json, State},
     http::StatusCode,
     response::IntoResponse,
-    routing::{get, post},
-    Router,
 };
 use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 use tower_cookies::Cookies;
+use utoipa::ToSchema;
+use utoipa_axum::{router::OpenApiRouter, routes};
 
 use super::cookie::{
     clear_auth_cookie, clear_oauth_state_cookie, get_auth_session, get_oauth_state,
     set_auth_cookie, set_oauth_state_cookie, AuthSession,
 };
 use super::google::GoogleOAuthClient;
-use super::middleware::is_allowed_user;
+use crate::config::Config;
+use crate::state::AppState;
+use crate::tenant;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CallbackRequest {
     code: String,
     redirect_uri: String,
     state: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CallbackResponse {
     email: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct InitResponse {
     state: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MeResponse {
     email: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorResponse {
     error: String,
 }
 
-pub fn router<S>() -> Router<S>
-where
-    S: Clone + Send + Sync + 'static,
-{
-    Router::new()
-        .route("/auth/init", get(init))
-        .route("/auth/callback", post(callback))
-        .route("/auth/me", get(me))
-        .route("/auth/logout", post(logout))
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(init))
+        .routes(routes!(callback))
+        .routes(routes!(me))
+        .routes(routes!(logout))
 }
 
-async fn init(cookies: Cookies) -> Json<InitResponse> {
+/// Starts a Google sign-in: returns the OAuth `state` to send along and
+/// keeps it in a cookie for the callback to compare.
+#[utoipa::path(
+    get,
+    path = "/auth/init",
+    tag = "auth",
+    security(()),
+    responses((status = 200, body = InitResponse))
+)]
+async fn init(State(config): State<Arc<Config>>, cookies: Cookies) -> Json<InitResponse> {
     let bytes: [u8; 32] = rand::rng().random();
     let state = URL_SAFE_NO_PAD.encode(bytes);
 
-    let secure = std::env::var("SECURE_COOKIES")
-        .map(|v| v == "true")
-        .unwrap_or(false);
-    set_oauth_state_cookie(&cookies, &state, secure);
+    set_oauth_state_cookie(&cookies, &state, config.secure_cookies);
 
     Json(InitResponse { state })
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/callback",
+    tag = "auth",
+    security(()),
+    responses(
+        (status = 200, body = CallbackResponse, description = "Signed in, the session cookie is set"),
+        (status = 400, body = ErrorResponse, description = "OAuth state mismatch or Google rejected the code"),
+        (status = 403, body = ErrorResponse, description = "Account does not belong to any tenant")
+    )
+)]
 async fn callback(
+    State(config): State<Arc<Config>>,
+    State(pool): State<PgPool>,
     cookies: Cookies,
     Json(payload): Json<CallbackRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
@@ -92,7 +113,7 @@ async fn callback(
 
     clear_oauth_state_cookie(&cookies);
 
-    let google_client = GoogleOAuthClient::from_env();
+    let google_client = GoogleOAuthClient::new(&config.google);
 
     // Exchange code for tokens
     let token_response = google_client
@@ -122,8 +143,19 @@ async fn callback(
             )
         })?;
 
-    // Enforce allowlist at login
-    if !is_allowed_user(&user_info.email) {
+    // Only members of a tenant may sign in
+    let tenants = tenant::user_tenants(&pool, &user_info.email)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to look up tenants: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: "Failed to check access".to_string(),
+                }),
+            )
+        })?;
+    if tenants.is_empty() {
         return Err((
             StatusCode::FORBIDDEN,
             Json(ErrorResponse {
@@ -149,19 +181,28 @@ async fn callback(
     );
 
     // Set cookie
-    let secure = std::env::var("SECURE_COOKIES")
-        .map(|v| v == "true")
-        .unwrap_or(false);
-    set_auth_cookie(&cookies, &session, secure);
+    set_auth_cookie(&cookies, &session, config.secure_cookies);
 
     Ok(Json(CallbackResponse {
         email: user_info.email,
     }))
 }
 
-async fn me(cookies: Cookies) -> Result<Json<MeResponse>, StatusCode> {
+#[utoipa::path(
+    get,
+    path = "/auth/me",
+    tag = "auth",
+    responses(
+        (status = 200, body = MeResponse),
+        (status = 401, description = "No session, or Google no longer accepts it")
+    )
+)]
+async fn me(
+    State(config): State<Arc<Config>>,
+    cookies: Cookies,
+) -> Result<Json<MeResponse>, StatusCode> {
     let session = get_auth_session(&cookies).ok_or(StatusCode::UNAUTHORIZED)?;
-    let google_client = GoogleOAuthClient::from_env();
+    let google_client = GoogleOAuthClient::new(&config.google);
     let user_info = google_client
         .get_user_info(&session.access_token)
         .await
@@ -172,6 +213,13 @@ async fn me(cookies: Cookies) -> Result<Json<MeResponse>, StatusCode> {
     }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    security(()),
+    responses((status = 200, description = "Session cookie cleared"))
+)]
 async fn logout(cookies: Cookies) -> impl IntoResponse {
     clear_auth_cookie(&cookies);
     StatusCode::OK
@@ -2,43 +2,82 @@ use axum::Json;
 use axum::extract::{Query, State};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::error::AppResult;
+use crate::shared::page::{Page, PageQuery};
 
-#[derive(Serialize)]
+const SORT_COLUMNS: &[&str] = &["full_name", "policy_count"];
+
+#[derive(Serialize, ToSchema)]
 pub struct AgentWithPolicies {
     pub id: i32,
     pub full_name: String,
+    pub email: Option<String>,
     pub policy_count: i64,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct AgentQuery {
     pub search: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/agents",
+    tag = "agents",
+    params(AgentQuery, PageQuery),
+    responses((status = 200, body = Page<AgentWithPolicies>))
+)]
 pub async fn get_agents(
     State(pool): State<PgPool>,
     Query(query): Query<AgentQuery>,
-) -> AppResult<Json<Vec<AgentWithPolicies>>> {
+    Query(page): Query<PageQuery>,
+) -> AppResult<Json<Page<AgentWithPolicies>>> {
     let search = query.search.unwrap_or_default().to_lowercase();
     let search_pattern = format!("%{}%", search);
 
-    let agents = sqlx::query_as!(
+    let sort = page.sort(SORT_COLUMNS)?;
+    let limit = page.limit(50);
+    let offset = page.offset();
+
+    let items = sqlx::query_as!(
         AgentWithPolicies,
         r#"
-        SELECT a.id, a.full_name, count(ap.policy_id) as "policy_count!: i64"
+        SELECT a.id, a.full_name, a.email, count(ap.policy_id) as "policy_count!: i64"
         FROM agent a
         LEFT JOIN agent_policy ap ON ap.agent_id = a.id
-        WHERE lower(a.full_name) LIKE $1
-        GROUP BY a.id, a.full_name
-        ORDER BY a.full_name
-        LIMIT 50
+        WHERE lower(a.full_name) LIKE $1 AND a.tenant_id = current_tenant()
+        GROUP BY a.id, a.full_name, a.email
+        ORDER BY
+            case when $2::text = 'policy_count' and not $3 then count(ap.policy_id) end asc,
+            case when $2::text = 'policy_count' and $3 then count(ap.policy_id) end desc,
+            case when $2::text = 'full_name' and $3 then a.full_name end desc,
+            a.full_name,
+            a.id
+        LIMIT $4
+        OFFSET $5
         "#,
-        search_pattern
+        search_pattern,
+        sort as Option<String>,
+        page.descending(),
+        limit,
+        offset,
     )
     .fetch_all(&pool)
     .await?;
 
-    Ok(Json(agents))
+    let total = sqlx::query_scalar!(
+        r#"SELECT count(*) as "count!" FROM agent WHERE lower(full_name) LIKE $1 AND tenant_id = current_tenant()"#,
+        search_pattern
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(Page {
+        items,
+        total,
+        limit,
+        offset,
+    }))
 }
@@ -1,9 +1,13 @@
 pub mod get;
+pub mod update;
 
-use axum::{Router, routing::get};
-use sqlx::PgPool;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
-pub fn router() -> Router<PgPool> {
-    Router::new()
-        .route("/agents", get(get::get_agents))
+use crate::state::AppState;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get::get_agents))
+        .routes(routes!(update::update_agent))
 }
@@ -0,0 +1,59 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::error::{AppError, AppResult};
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateAgent {
+    full_name: String,
+    /// Where expiry digests are sent; agents without one get none
+    email: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Agent {
+    pub id: i32,
+    pub full_name: String,
+    pub email: Option<String>,
+}
+
+#[utoipa::path(
+    put,
+    path = "/agents/{id}",
+    tag = "agents",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 200, body = Agent),
+        (status = 404, description = "No such agent")
+    )
+)]
+pub async fn update_agent(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+    Json(body): Json<UpdateAgent>,
+) -> AppResult<Json<Agent>> {
+    let email = body
+        .email
+        .map(|email| email.trim().to_owned())
+        .filter(|email| !email.is_empty());
+
+    let agent = sqlx::query_as!(
+        Agent,
+        r#"
+        update agent
+        set full_name = $2, email = $3
+        where id = $1 and tenant_id = current_tenant()
+        returning id, full_name, email
+        "#,
+        id,
+        body.full_name,
+        email
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    agent.map(Json).ok_or(AppError::not_found())
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use axum::Extension;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+use crate::storage::FileStorage;
+
+#[utoipa::path(
+    delete,
+    path = "/attachments/{id}",
+    tag = "attachments",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 204, description = "Deleted with its file"),
+        (status = 404, description = "No such attachment")
+    )
+)]
+pub async fn delete_attachment(
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Arc<FileStorage>>,
+    Path(id): Path<i32>,
+) -> AppResult<StatusCode> {
+    let mut tx = pool.begin().await?;
+
+    let storage_key = sqlx::query_scalar!(
+        "delete from attachment where id = $1 and tenant_id = current_tenant() returning storage_key",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::not_found())?;
+
+    // Remove the file before committing, so a storage failure keeps the row
+    storage.delete(&storage_key).await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use axum::Extension;
+use axum::extract::{Path, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+use crate::storage::FileStorage;
+
+struct AttachmentFile {
+    file_name: String,
+    content_type: String,
+    storage_key: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/attachments/{id}",
+    tag = "attachments",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 200, description = "The file, with the content type it was stored with", content_type = "application/octet-stream"),
+        (status = 404, description = "No such attachment")
+    )
+)]
+pub async fn download_attachment(
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Arc<FileStorage>>,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let file = sqlx::query_as!(
+        AttachmentFile,
+        r#"
+        select file_name, content_type, storage_key
+        from attachment
+        where id = $1 and tenant_id = current_tenant()
+        "#,
+        id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::not_found())?;
+
+    let content = storage.get(&file.storage_key).await?;
+
+    // RFC 5987 encoding keeps Cyrillic file names intact
+    let disposition = format!(
+        "attachment; filename*=UTF-8''{}",
+        utf8_percent_encode(&file.file_name, NON_ALPHANUMERIC)
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, file.content_type),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        content,
+    ))
+}
@@ -0,0 +1,23 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+use crate::shared::attachment::{
+    self,
+    model::{Attachment, AttachmentOwner},
+};
+
+pub async fn list_attachments(
+    State(pool): State<PgPool>,
+    Path(owner_id): Path<i32>,
+    owner: AttachmentOwner,
+) -> AppResult<Json<Vec<Attachment>>> {
+    if !attachment::owner_exists(&pool, owner, owner_id).await? {
+        return Err(AppError::not_found());
+    }
+
+    let attachments = attachment::for_owner(&pool, owner, owner_id).await?;
+
+    Ok(Json(attachments))
+}
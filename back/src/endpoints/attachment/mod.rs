@@ -0,0 +1,96 @@
+pub mod delete;
+pub mod download;
+pub mod list;
+#[cfg(test)]
+mod tests;
+pub mod upload;
+
+use axum::extract::{DefaultBodyLimit, Multipart, Path, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use sqlx::PgPool;
+use std::sync::Arc;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::auth::middleware::AuthUser;
+use crate::error::AppResult;
+use crate::shared::attachment::model::{Attachment, AttachmentOwner};
+use crate::state::AppState;
+use crate::storage::{AttachmentLimits, FileStorage};
+
+pub fn router() -> OpenApiRouter<AppState> {
+    let owners = OpenApiRouter::new()
+        .routes(routes!(list_policy_attachments, upload_policy_attachment))
+        .routes(routes!(list_person_attachments, upload_person_attachment))
+        .routes(routes!(list_car_attachments, upload_car_attachment))
+        // Size is enforced while reading the upload, against `AttachmentLimits`
+        .layer(DefaultBodyLimit::disable());
+
+    OpenApiRouter::new().merge(owners).routes(routes!(
+        download::download_attachment,
+        delete::delete_attachment
+    ))
+}
+
+/// List and upload handlers for the attachments of one kind of owner.
+macro_rules! owner_handlers {
+    ($owner:expr, $path:literal, $list:ident, $upload:ident) => {
+        #[utoipa::path(
+            get,
+            path = $path,
+            tag = "attachments",
+            params(("id" = i32, Path)),
+            responses(
+                (status = 200, body = Vec<Attachment>),
+                (status = 404, description = "No such owner")
+            )
+        )]
+        async fn $list(state: State<PgPool>, path: Path<i32>) -> AppResult<Json<Vec<Attachment>>> {
+            list::list_attachments(state, path, $owner).await
+        }
+
+        #[utoipa::path(
+            post,
+            path = $path,
+            tag = "attachments",
+            params(("id" = i32, Path)),
+            request_body(content = upload::UploadForm, content_type = "multipart/form-data"),
+            responses(
+                (status = 201, body = Attachment),
+                (status = 404, description = "No such owner"),
+                (status = 413, description = "File is larger than ATTACHMENT_MAX_BYTES"),
+                (status = 415, description = "File type is not allowed")
+            )
+        )]
+        async fn $upload(
+            state: State<PgPool>,
+            storage: Extension<Arc<FileStorage>>,
+            limits: Extension<Arc<AttachmentLimits>>,
+            user: Extension<AuthUser>,
+            path: Path<i32>,
+            multipart: Multipart,
+        ) -> AppResult<(StatusCode, Json<Attachment>)> {
+            upload::upload_attachment(state, storage, limits, user, path, $owner, multipart).await
+        }
+    };
+}
+
+owner_handlers!(
+    AttachmentOwner::Policy,
+    "/policies/{id}/attachments",
+    list_policy_attachments,
+    upload_policy_attachment
+);
+owner_handlers!(
+    AttachmentOwner::Person,
+    "/people/{id}/attachments",
+    list_person_attachments,
+    upload_person_attachment
+);
+owner_handlers!(
+    AttachmentOwner::Car,
+    "/cars/{id}/attachments",
+    list_car_attachments,
+    upload_car_attachment
+);
@@ -0,0 +1,106 @@
+use axum::http::{Method, StatusCode, header};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use crate::storage::AttachmentLimits;
+use crate::testing::{TEST_USER, TestApp};
+use crate::testing::fixtures::PersonBuilder;
+
+const PDF: &[u8] = b"%PDF-1.7\n1 0 obj\n<<>>\nendobj\n%%EOF\n";
+
+async fn person_uri(app: &TestApp) -> String {
+    let person = PersonBuilder::new().create(app).await;
+    format!("/people/{}/attachments", person["id"])
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn upload_and_download(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let uri = person_uri(&app).await;
+
+    // The type comes from the content, not from what the client claims
+    let attachment = app
+        .upload(&uri, "Паспорт.pdf", "image/png", PDF)
+        .await
+        .expect(StatusCode::CREATED);
+    assert_eq!(attachment["file_name"], "Паспорт.pdf");
+    assert_eq!(attachment["content_type"], "application/pdf");
+    assert_eq!(attachment["size_bytes"], PDF.len());
+    assert_eq!(attachment["sha256"], hex::encode(Sha256::digest(PDF)));
+    assert_eq!(attachment["uploaded_by"], TEST_USER);
+
+    let listed = app.get(&uri).await.expect(StatusCode::OK);
+    assert_eq!(listed, json!([attachment]));
+
+    let file_uri = format!("/attachments/{}", attachment["id"]);
+    let download = app.get(&file_uri).await;
+    assert_eq!(download.status, StatusCode::OK);
+    assert_eq!(download.text.as_bytes(), PDF);
+    assert_eq!(download.headers[header::CONTENT_TYPE], "application/pdf");
+    assert_eq!(
+        download.headers[header::CONTENT_DISPOSITION],
+        "attachment; filename*=UTF-8''%D0%9F%D0%B0%D1%81%D0%BF%D0%BE%D1%80%D1%82%2Epdf"
+    );
+
+    let response = app.send(Method::DELETE, &file_uri, &[], None).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    app.get(&file_uri).await.expect(StatusCode::NOT_FOUND);
+    assert_eq!(app.get(&uri).await.expect(StatusCode::OK), json!([]));
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn rejected_uploads(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let uri = person_uri(&app).await;
+
+    let response = app
+        .upload(
+            &uri,
+            "notes.pdf",
+            "application/pdf",
+            b"plain text, not a PDF",
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let response = app.upload(&uri, "empty.pdf", "application/pdf", b"").await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let max_bytes = AttachmentLimits::default().max_bytes;
+    let mut content = PDF.to_vec();
+    content.resize(max_bytes + 1, b' ');
+    let response = app
+        .upload(&uri, "scan.pdf", "application/pdf", &content)
+        .await;
+    assert_eq!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        response.body["message"],
+        format!("File exceeds {} bytes", max_bytes)
+    );
+
+    // Up to the limit is fine
+    content.truncate(max_bytes);
+    app.upload(&uri, "scan.pdf", "application/pdf", &content)
+        .await
+        .expect(StatusCode::CREATED);
+
+    let listed = app.get(&uri).await.expect(StatusCode::OK);
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn missing_owner(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+
+    for uri in [
+        "/people/999999/attachments",
+        "/policies/999999/attachments",
+        "/cars/999999/attachments",
+    ] {
+        app.get(uri).await.expect(StatusCode::NOT_FOUND);
+        app.upload(uri, "scan.pdf", "application/pdf", PDF)
+            .await
+            .expect(StatusCode::NOT_FOUND);
+    }
+}
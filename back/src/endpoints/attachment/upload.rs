@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use axum::extract::{Multipart, Path, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::auth::middleware::AuthUser;
+use crate::error::{AppError, AppResult};
+use crate::shared::attachment::{
+    self,
+    model::{Attachment, AttachmentOwner},
+};
+use crate::storage::{AttachmentLimits, FileStorage};
+
+/// The multipart form of an upload, described for the OpenAPI document only.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct UploadForm {
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+pub async fn upload_attachment(
+    State(pool): State<PgPool>,
+    Extension(storage): Extension<Arc<FileStorage>>,
+    Extension(limits): Extension<Arc<AttachmentLimits>>,
+    Extension(user): Extension<AuthUser>,
+    Path(owner_id): Path<i32>,
+    owner: AttachmentOwner,
+    mut multipart: Multipart,
+) -> AppResult<(StatusCode, Json<Attachment>)> {
+    if !attachment::owner_exists(&pool, owner, owner_id).await? {
+        return Err(AppError::not_found());
+    }
+
+    let mut field = loop {
+        let field = multipart
+            .next_field()
+            .await
+            .map_err(|e| AppError::status(StatusCode::BAD_REQUEST, e.body_text()))?
+            .ok_or(AppError::status(
+                StatusCode::BAD_REQUEST,
+                "Missing file field",
+            ))?;
+        if field.name() == Some("file") {
+            break field;
+        }
+    };
+
+    let file_name = field
+        .file_name()
+        .filter(|s| !s.is_empty())
+        .unwrap_or("file")
+        .to_owned();
+
+    let mut content = Vec::new();
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .map_err(|e| AppError::status(StatusCode::BAD_REQUEST, e.body_text()))?
+    {
+        if content.len() + chunk.len() > limits.max_bytes {
+            return Err(AppError::status(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("File exceeds {} bytes", limits.max_bytes),
+            ));
+        }
+        content.extend_from_slice(&chunk);
+    }
+
+    if content.is_empty() {
+        return Err(AppError::status(StatusCode::BAD_REQUEST, "File is empty"));
+    }
+
+    // Trust the file signature, not the client-provided content type
+    let content_type = infer::get(&content)
+        .map(|t| t.mime_type())
+        .filter(|t| limits.allows(t))
+        .ok_or(AppError::status(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Unsupported file type",
+        ))?
+        .to_owned();
+
+    let sha256 = hex::encode(Sha256::digest(&content));
+    let random: [u8; 16] = rand::rng().random();
+    let storage_key = format!("{}/{}/{}", owner.as_str(), owner_id, hex::encode(random));
+    let size_bytes = content.len() as i64;
+
+    storage
+        .put(&storage_key, content.into(), &content_type)
+        .await?;
+
+    let attachment = sqlx::query_as!(
+        Attachment,
+        r#"
+        insert into attachment (owner_type, owner_id, file_name, content_type, size_bytes, sha256, storage_key, uploaded_by)
+        values ($1, $2, $3, $4, $5, $6, $7, $8)
+        returning id, file_name, content_type, size_bytes, sha256, uploaded_by, uploaded_at
+        "#,
+        owner as AttachmentOwner,
+        owner_id,
+        file_name,
+        content_type,
+        size_bytes,
+        sha256,
+        storage_key,
+        user.email
+    )
+    .fetch_one(&pool)
+    .await;
+
+    match attachment {
+        Ok(attachment) => Ok((StatusCode::CREATED, Json(attachment))),
+        Err(err) => {
+            // Don't leave an unreferenced file behind
+            if let Err(e) = storage.delete(&storage_key).await {
+                tracing::warn!(
+                    "Failed to remove orphaned attachment {}: {:?}",
+                    storage_key,
+                    e
+                );
+            }
+            Err(err.into())
+        }
+    }
+}
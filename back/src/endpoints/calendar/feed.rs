@@ -0,0 +1,301 @@
+use axum::extract::{Query, State};
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use chrono::Datelike;
+use serde::Deserialize;
+use sqlx::PgPool;
+use utoipa::IntoParams;
+
+use super::token::token_hash;
+use crate::error::{AppError, AppResult};
+use crate::shared::ical::{Calendar, Event, Recurrence};
+use crate::shared::policy::labels::type_label;
+use crate::shared::policy::model::PolicyType;
+use crate::tenant;
+
+/// Past policy ends and payments stay in the feed this long.
+const HISTORY_DAYS: i32 = 30;
+
+#[derive(Clone, Copy, PartialEq)]
+enum EventKind {
+    Expiry,
+    Birthday,
+    Payment,
+}
+
+impl EventKind {
+    const ALL: [EventKind; 3] = [EventKind::Expiry, EventKind::Birthday, EventKind::Payment];
+
+    fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "expiry" => Some(EventKind::Expiry),
+            "birthday" => Some(EventKind::Birthday),
+            "payment" => Some(EventKind::Payment),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct CalendarQuery {
+    pub token: String,
+    /// Only policies linked to the agent, and birthdays of their holders
+    pub agent_id: Option<i32>,
+    /// Comma-separated `expiry`, `birthday`, `payment`; all of them by default
+    pub kind: Option<String>,
+}
+
+impl CalendarQuery {
+    fn kinds(&self) -> AppResult<Vec<EventKind>> {
+        let Some(kind) = self.kind.as_deref().filter(|kind| !kind.trim().is_empty()) else {
+            return Ok(EventKind::ALL.to_vec());
+        };
+
+        kind.split(',')
+            .map(str::trim)
+            .filter(|kind| !kind.is_empty())
+            .map(|kind| {
+                EventKind::parse(kind).ok_or(AppError::status(
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown event kind '{}'", kind),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Policy ends, client birthdays and payment due dates as an iCalendar feed
+/// for calendar apps to subscribe to.
+#[utoipa::path(
+    get,
+    path = "/calendar.ics",
+    tag = "calendar",
+    params(CalendarQuery),
+    security(()),
+    responses(
+        (status = 200, description = "iCalendar feed", content_type = "text/calendar"),
+        (status = 400, description = "Unknown event kind"),
+        (status = 401, description = "Invalid token")
+    )
+)]
+pub async fn get_calendar(
+    State(pool): State<PgPool>,
+    Query(query): Query<CalendarQuery>,
+) -> AppResult<impl IntoResponse> {
+    // The token's user must still belong to the tenant it was issued for
+    let tenant_id = tenant::unrestricted(
+        sqlx::query_scalar!(
+            r#"
+            update calendar_token t
+            set last_used_at = now()
+            from tenant_user tu
+            where t.token_hash = $1 and tu.user_email = t.user_email and tu.tenant_id = t.tenant_id
+            returning t.tenant_id
+            "#,
+            token_hash(&query.token)
+        )
+        .fetch_optional(&pool),
+    )
+    .await?
+    .ok_or(AppError::status(StatusCode::UNAUTHORIZED, "Invalid token"))?;
+
+    let kinds = query.kinds()?;
+    let calendar = tenant::scope(tenant_id, async {
+        let mut calendar = Calendar::new("Страхування", chrono::Utc::now());
+        if kinds.contains(&EventKind::Expiry) {
+            add_expiring_policies(&pool, &mut calendar, query.agent_id).await?;
+        }
+        if kinds.contains(&EventKind::Birthday) {
+            add_birthdays(&pool, &mut calendar, query.agent_id).await?;
+        }
+        if kinds.contains(&EventKind::Payment) {
+            add_payments(&pool, &mut calendar, query.agent_id).await?;
+        }
+        Ok::<_, sqlx::Error>(calendar)
+    })
+    .await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "inline; filename=\"calendar.ics\"",
+            ),
+        ],
+        calendar.finish(),
+    ))
+}
+
+async fn add_expiring_policies(
+    pool: &PgPool,
+    calendar: &mut Calendar,
+    agent_id: Option<i32>,
+) -> sqlx::Result<()> {
+    let policies = sqlx::query!(
+        r#"
+        select
+            p.id,
+            p.series,
+            p.number,
+            p.type as "policy_type: PolicyType",
+            p.end_date as "end_date!",
+            pe.first_name,
+            pe.last_name,
+            pe.phone,
+            i.name as insurer
+        from policy p
+        join person pe on pe.id = p.holder_id
+        join insurer i on i.id = p.insurer_id
+        where
+            p.tenant_id = current_tenant()
+            and p.status = 'active'
+            and p.end_date >= current_date - $1::int
+            and ($2::int is null or exists (
+                select 1 from agent_policy ap where ap.policy_id = p.id and ap.agent_id = $2
+            ))
+        order by p.end_date, p.id
+        "#,
+        HISTORY_DAYS,
+        agent_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for policy in policies {
+        calendar.event(&Event {
+            uid: format!("policy-end-{}@insure-agent", policy.id),
+            date: policy.end_date,
+            summary: format!(
+                "Закінчується {} {} {}: {} {}",
+                type_label(policy.policy_type),
+                policy.series,
+                policy.number,
+                policy.last_name,
+                policy.first_name
+            ),
+            description: format!("Страховик: {}\nТелефон: {}", policy.insurer, policy.phone),
+            category: "Закінчення полісів",
+            recurrence: Recurrence::None,
+        });
+    }
+
+    Ok(())
+}
+
+async fn add_birthdays(
+    pool: &PgPool,
+    calendar: &mut Calendar,
+    agent_id: Option<i32>,
+) -> sqlx::Result<()> {
+    let people = sqlx::query!(
+        r#"
+        select id, first_name, last_name, patronymic_name, phone, birth_date
+        from person pe
+        where
+            tenant_id = current_tenant()
+            and status = 'active'
+            and ($1::int is null or exists (
+                select 1
+                from policy p
+                join agent_policy ap on ap.policy_id = p.id
+                where p.holder_id = pe.id and ap.agent_id = $1
+            ))
+        order by id
+        "#,
+        agent_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for person in people {
+        let leap_day = (person.birth_date.month(), person.birth_date.day()) == (2, 29);
+        let full_name = [
+            Some(person.last_name.as_str()),
+            Some(person.first_name.as_str()),
+            person.patronymic_name.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+        calendar.event(&Event {
+            uid: format!("birthday-{}@insure-agent", person.id),
+            date: person.birth_date,
+            summary: format!("День народження: {}", full_name),
+            description: format!(
+                "{} р. н.\nТелефон: {}",
+                person.birth_date.year(),
+                person.phone
+            ),
+            category: "Дні народження",
+            recurrence: if leap_day {
+                Recurrence::YearlyLastDayOfFebruary
+            } else {
+                Recurrence::Yearly
+            },
+        });
+    }
+
+    Ok(())
+}
+
+async fn add_payments(
+    pool: &PgPool,
+    calendar: &mut Calendar,
+    agent_id: Option<i32>,
+) -> sqlx::Result<()> {
+    let payments = sqlx::query!(
+        r#"
+        select
+            pp.id,
+            pp.due_date,
+            pp.amount,
+            p.series,
+            p.number,
+            p.type as "policy_type: PolicyType",
+            pe.first_name,
+            pe.last_name,
+            pe.phone
+        from policy_payment pp
+        join policy p on p.id = pp.policy_id
+        join person pe on pe.id = p.holder_id
+        where
+            p.tenant_id = current_tenant()
+            and pp.paid_on is null
+            and pp.due_date >= current_date - $1::int
+            and p.status = 'active'
+            and ($2::int is null or exists (
+                select 1 from agent_policy ap where ap.policy_id = p.id and ap.agent_id = $2
+            ))
+        order by pp.due_date, pp.id
+        "#,
+        HISTORY_DAYS,
+        agent_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for payment in payments {
+        calendar.event(&Event {
+            uid: format!("payment-{}@insure-agent", payment.id),
+            date: payment.due_date,
+            summary: format!(
+                "Платіж {} грн: {} {}",
+                payment.amount, payment.last_name, payment.first_name
+            ),
+            description: format!(
+                "{} {} {}\nТелефон: {}",
+                type_label(payment.policy_type),
+                payment.series,
+                payment.number,
+                payment.phone
+            ),
+            category: "Платежі",
+            recurrence: Recurrence::None,
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+pub mod feed;
+#[cfg(test)]
+mod tests;
+pub mod token;
+
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+/// Served without the session cookie: calendar apps authenticate with `?token=`.
+pub const FEED_PATH: &str = "/calendar.ics";
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(feed::get_calendar))
+        .routes(routes!(token::create_token, token::delete_token))
+}
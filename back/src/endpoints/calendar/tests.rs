@@ -0,0 +1,233 @@
+use axum::http::{Method, StatusCode, header};
+use chrono::{Days, Utc};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use crate::tenant::TENANT_ID;
+use crate::testing::fixtures::{self, PolicyBuilder};
+use crate::testing::{DEFAULT_TENANT, TEST_USER, TestApp, TestResponse};
+
+async fn token(app: &TestApp) -> String {
+    let issued = app
+        .send(Method::POST, "/calendar/token", &[], None)
+        .await
+        .expect(StatusCode::CREATED);
+    issued["token"].as_str().unwrap().to_owned()
+}
+
+async fn token_in(app: &TestApp, tenant: &str) -> String {
+    let issued = app
+        .send(
+            Method::POST,
+            "/calendar/token",
+            &[(TENANT_ID, tenant)],
+            None,
+        )
+        .await
+        .expect(StatusCode::CREATED);
+    issued["token"].as_str().unwrap().to_owned()
+}
+
+/// Adds `TEST_USER` to a new tenant, returning its id for the tenant header.
+async fn join_partner(app: &TestApp) -> String {
+    let partner = sqlx::query_scalar!("insert into tenant (name) values ('Partner') returning id")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    sqlx::query!(
+        "insert into tenant_user (tenant_id, user_email) values ($1, $2)",
+        partner,
+        TEST_USER
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    partner.to_string()
+}
+
+async fn feed(app: &TestApp, token: &str, query: &str) -> TestResponse {
+    app.get(&format!("/calendar.ics?token={}{}", token, query))
+        .await
+}
+
+/// UIDs of the feed's events, in order.
+fn uids(response: TestResponse) -> Vec<String> {
+    assert_eq!(response.status, StatusCode::OK, "{}", response.text);
+    assert_eq!(
+        response.headers[header::CONTENT_TYPE],
+        "text/calendar; charset=utf-8"
+    );
+    response
+        .text
+        .lines()
+        .filter_map(|line| line.strip_prefix("UID:"))
+        .map(str::to_owned)
+        .collect()
+}
+
+/// A date `days` from today, as the API takes it.
+fn in_days(days: u64) -> Value {
+    json!(Utc::now().date_naive() + Days::new(days))
+}
+
+/// Stores the policy with an unpaid payment, returning the UIDs of its end,
+/// its holder's birthday and the payment, and the policy id.
+async fn policy_with_payment(app: &TestApp, policy: PolicyBuilder) -> ([String; 3], Value) {
+    let created = policy.with("end_date", in_days(60)).create(app).await;
+    let payment = app
+        .post(
+            &format!("/policies/{}/payments", created["id"]),
+            json!({ "due_date": in_days(30), "amount": 1200 }),
+        )
+        .await
+        .expect(StatusCode::CREATED);
+    let stored = app
+        .get(&format!("/policies/{}", created["id"]))
+        .await
+        .expect(StatusCode::OK);
+    (
+        [
+            format!("policy-end-{}@insure-agent", created["id"]),
+            format!("birthday-{}@insure-agent", stored["holder"]["id"]),
+            format!("payment-{}@insure-agent", payment["id"]),
+        ],
+        created["id"].clone(),
+    )
+}
+
+fn contains_all(uids: &[String], expected: &[String]) -> bool {
+    expected.iter().all(|uid| uids.contains(uid))
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn invalid_tokens(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let issued = token(&app).await;
+    feed(&app, &issued, "").await.expect(StatusCode::OK);
+
+    feed(&app, "not-a-token", "")
+        .await
+        .expect(StatusCode::UNAUTHORIZED);
+
+    // Issuing a new token revokes the previous one
+    let reissued = token(&app).await;
+    feed(&app, &issued, "")
+        .await
+        .expect(StatusCode::UNAUTHORIZED);
+    feed(&app, &reissued, "").await.expect(StatusCode::OK);
+
+    app.send(Method::DELETE, "/calendar/token", &[], None)
+        .await
+        .expect(StatusCode::NO_CONTENT);
+    feed(&app, &reissued, "")
+        .await
+        .expect(StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn agent_filter(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let agent = fixtures::agent(&app, "Олена Іваненко").await;
+    let (theirs, _) = policy_with_payment(
+        &app,
+        PolicyBuilder::osago(&insurer).with("agent_ids", json!([agent])),
+    )
+    .await;
+    let (others, _) = policy_with_payment(&app, PolicyBuilder::osago(&insurer)).await;
+    let token = token(&app).await;
+
+    let all = uids(feed(&app, &token, "").await);
+    assert!(contains_all(&all, &theirs));
+    assert!(contains_all(&all, &others));
+
+    let filtered = uids(feed(&app, &token, &format!("&agent_id={}", agent)).await);
+    assert!(contains_all(&filtered, &theirs));
+    assert_eq!(filtered.len(), 3, "{:?}", filtered);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn kinds_filter(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let (events, _) = policy_with_payment(&app, PolicyBuilder::osago(&insurer)).await;
+    let [expiry, birthday, payment] = events;
+    let token = token(&app).await;
+
+    assert_eq!(uids(feed(&app, &token, "&kind=birthday").await), [birthday]);
+    assert_eq!(
+        uids(feed(&app, &token, "&kind=expiry,%20payment").await),
+        [expiry, payment]
+    );
+    assert_eq!(uids(feed(&app, &token, "&kind=").await).len(), 3);
+
+    let refused = feed(&app, &token, "&kind=expiry,holiday")
+        .await
+        .expect(StatusCode::BAD_REQUEST);
+    assert_eq!(refused["message"], "Unknown event kind 'holiday'");
+
+    // Cancelled policies drop out, their holder's birthday stays
+    let (_, policy_id) = policy_with_payment(&app, PolicyBuilder::osago(&insurer)).await;
+    sqlx::query!(
+        "update policy set status = 'cancelled' where id = $1",
+        policy_id.as_i64().unwrap() as i32
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let all = uids(feed(&app, &token, "").await);
+    assert_eq!(all.len(), 4, "{:?}", all);
+    assert!(!all.contains(&format!("policy-end-{}@insure-agent", policy_id)));
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn only_the_token_tenant(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let (events, _) = policy_with_payment(&app, PolicyBuilder::osago(&insurer)).await;
+    let partner = join_partner(&app).await;
+
+    let partner_token = token_in(&app, &partner).await;
+    assert!(uids(feed(&app, &partner_token, "").await).is_empty());
+
+    let token = token(&app).await;
+    assert!(contains_all(&uids(feed(&app, &token, "").await), &events));
+
+    // Leaving the tenant stops its feed
+    sqlx::query!(
+        "delete from tenant_user where tenant_id = $1 and user_email = $2",
+        DEFAULT_TENANT,
+        TEST_USER
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    feed(&app, &token, "")
+        .await
+        .expect(StatusCode::UNAUTHORIZED);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn one_token_per_tenant(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let partner = join_partner(&app).await;
+    let own = token(&app).await;
+    let partners = token_in(&app, &partner).await;
+
+    feed(&app, &own, "").await.expect(StatusCode::OK);
+    feed(&app, &partners, "").await.expect(StatusCode::OK);
+
+    // Revoking in one tenant leaves the other tenant's feed alone
+    app.send(
+        Method::DELETE,
+        "/calendar/token",
+        &[(TENANT_ID, &partner)],
+        None,
+    )
+    .await
+    .expect(StatusCode::NO_CONTENT);
+    feed(&app, &partners, "")
+        .await
+        .expect(StatusCode::UNAUTHORIZED);
+    feed(&app, &own, "").await.expect(StatusCode::OK);
+}
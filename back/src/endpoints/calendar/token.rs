@@ -0,0 +1,81 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use rand::Rng;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use super::FEED_PATH;
+use crate::auth::middleware::AuthUser;
+use crate::error::AppResult;
+
+#[derive(Serialize, ToSchema)]
+pub struct CalendarToken {
+    pub token: String,
+    /// Feed path with the token, to be prefixed with the API origin
+    pub path: String,
+}
+
+pub fn token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Issues a new feed token for the current user and tenant. The previous one
+/// for this tenant stops working; those for the user's other tenants do not.
+#[utoipa::path(
+    post,
+    path = "/calendar/token",
+    tag = "calendar",
+    responses((status = 201, body = CalendarToken))
+)]
+pub async fn create_token(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<(StatusCode, Json<CalendarToken>)> {
+    let bytes: [u8; 32] = rand::rng().random();
+    let token = URL_SAFE_NO_PAD.encode(bytes);
+
+    sqlx::query!(
+        r#"
+        insert into calendar_token (user_email, token_hash)
+        values ($1, $2)
+        on conflict (user_email, tenant_id) do update
+        set
+            token_hash = excluded.token_hash,
+            created_at = now(),
+            last_used_at = null
+        "#,
+        user.email,
+        token_hash(&token)
+    )
+    .execute(&pool)
+    .await?;
+
+    let path = format!("{}?token={}", FEED_PATH, token);
+    Ok((StatusCode::CREATED, Json(CalendarToken { token, path })))
+}
+
+/// Revokes the current user's feed token for the current tenant.
+#[utoipa::path(
+    delete,
+    path = "/calendar/token",
+    tag = "calendar",
+    responses((status = 204, description = "Revoked"))
+)]
+pub async fn delete_token(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<AuthUser>,
+) -> AppResult<StatusCode> {
+    sqlx::query!(
+        "delete from calendar_token where user_email = $1 and tenant_id = current_tenant()",
+        user.email
+    )
+    .execute(&pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
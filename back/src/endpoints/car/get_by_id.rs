@@ -1,12 +1,27 @@
 use axum::Json;
 use axum::extract::{Path, State};
+use axum::response::IntoResponse;
 use sqlx::PgPool;
 
 use crate::error::{AppError, AppResult};
 use crate::shared::car::model::CarFull;
+use crate::shared::version;
 
-pub async fn get_car(State(pool): State<PgPool>, Path(id): Path<i32>) -> AppResult<Json<CarFull>> {
-    sqlx::query_as!(
+#[utoipa::path(
+    get,
+    path = "/cars/{id}",
+    tag = "cars",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 200, body = CarFull, headers(("ETag" = String))),
+        (status = 404, description = "No such car")
+    )
+)]
+pub async fn get_car(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let car = sqlx::query_as!(
         CarFull,
         r#"
         select
@@ -21,14 +36,16 @@ pub async fn get_car(State(pool): State<PgPool>, Path(id): Path<i32>) -> AppResu
             mileage_km,
             unladen_weight,
             laden_weight,
-            seats
+            seats,
+            version
         from car
-        where id = $1
+        where id = $1 and tenant_id = current_tenant()
         "#,
         id
     )
     .fetch_optional(&pool)
     .await?
-    .map(Json)
-    .ok_or(AppError::not_found())
+    .ok_or(AppError::not_found())?;
+
+    Ok((version::etag(car.version), Json(car)))
 }
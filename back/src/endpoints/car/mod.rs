@@ -1,11 +1,13 @@
 pub mod get_by_id;
 pub mod search;
 
-use axum::{Router, routing::get};
-use sqlx::PgPool;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
-pub fn router() -> Router<PgPool> {
-    Router::new()
-        .route("/cars/search", get(search::search_cars))
-        .route("/cars/{id}", get(get_by_id::get_car))
+use crate::state::AppState;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(search::search_cars))
+        .routes(routes!(get_by_id::get_car))
 }
@@ -2,32 +2,79 @@ use axum::Json;
 use axum::extract::{Query, State};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::error::AppResult;
+use crate::shared::page::{Page, PageQuery};
 
-#[derive(Deserialize)]
+const SORT_COLUMNS: &[&str] = &["plate", "make", "model"];
+
+#[derive(Deserialize, IntoParams)]
 pub struct SearchQuery {
     pub q: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CarSearchResult {
     pub id: i32,
     pub label: String,
 }
 
+#[utoipa::path(
+    get,
+    path = "/cars/search",
+    tag = "cars",
+    params(SearchQuery, PageQuery),
+    responses((status = 200, body = Page<CarSearchResult>))
+)]
 pub async fn search_cars(
     State(pool): State<PgPool>,
     Query(query): Query<SearchQuery>,
-) -> AppResult<Json<Vec<CarSearchResult>>> {
+    Query(page): Query<PageQuery>,
+) -> AppResult<Json<Page<CarSearchResult>>> {
     let pattern = format!("%{}%", query.q.to_lowercase());
-    let results = sqlx::query_as!(
+
+    let sort = page.sort(SORT_COLUMNS)?;
+    let limit = page.limit(30);
+    let offset = page.offset();
+
+    let items = sqlx::query_as!(
         CarSearchResult,
-        "SELECT id, plate || ' (' || make || ' ' || model || ')' AS \"label!\" FROM car WHERE LOWER(plate || ' ' || make || ' ' || model) LIKE $1",
-        pattern
+        r#"
+        SELECT id, plate || ' (' || make || ' ' || model || ')' AS "label!"
+        FROM car
+        WHERE LOWER(plate || ' ' || make || ' ' || model) LIKE $1 AND tenant_id = current_tenant()
+        ORDER BY
+            case when $2::text = 'make' and not $3 then make end asc,
+            case when $2::text = 'make' and $3 then make end desc,
+            case when $2::text = 'model' and not $3 then model end asc,
+            case when $2::text = 'model' and $3 then model end desc,
+            case when $2::text = 'plate' and $3 then plate end desc,
+            plate,
+            id
+        LIMIT $4
+        OFFSET $5
+        "#,
+        pattern,
+        sort as Option<String>,
+        page.descending(),
+        limit,
+        offset,
     )
     .fetch_all(&pool)
     .await?;
 
-    Ok(Json(results))
+    let total = sqlx::query_scalar!(
+        r#"SELECT count(*) as "count!" FROM car WHERE LOWER(plate || ' ' || make || ' ' || model) LIKE $1 AND tenant_id = current_tenant()"#,
+        pattern
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(Page {
+        items,
+        total,
+        limit,
+        offset,
+    }))
 }
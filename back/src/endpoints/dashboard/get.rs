@@ -5,21 +5,30 @@ use sqlx::PgPool;
 use super::model::{BirthdayPerson, DashboardStats, ExpiringPolicy};
 use crate::error::AppResult;
 
+#[utoipa::path(
+    get,
+    path = "/dashboard",
+    tag = "dashboard",
+    responses((status = 200, body = DashboardStats))
+)]
 pub async fn get_dashboard(State(pool): State<PgPool>) -> AppResult<Json<DashboardStats>> {
-    let people_count = sqlx::query_scalar!("select count(*) from person")
-        .fetch_one(&pool)
-        .await?
-        .unwrap_or(0);
+    let people_count =
+        sqlx::query_scalar!("select count(*) from person where tenant_id = current_tenant()")
+            .fetch_one(&pool)
+            .await?
+            .unwrap_or(0);
 
-    let policy_count = sqlx::query_scalar!("select count(*) from policy")
-        .fetch_one(&pool)
-        .await?
-        .unwrap_or(0);
+    let policy_count =
+        sqlx::query_scalar!("select count(*) from policy where tenant_id = current_tenant()")
+            .fetch_one(&pool)
+            .await?
+            .unwrap_or(0);
 
-    let car_count = sqlx::query_scalar!("select count(*) from car")
-        .fetch_one(&pool)
-        .await?
-        .unwrap_or(0);
+    let car_count =
+        sqlx::query_scalar!("select count(*) from car where tenant_id = current_tenant()")
+            .fetch_one(&pool)
+            .await?
+            .unwrap_or(0);
 
     // Find people with birthdays in the next 7 days
     // The query handles year wraparound (e.g., Dec 30 -> Jan 5)
@@ -53,7 +62,8 @@ pub async fn get_dashboard(State(pool): State<PgPool>) -> AppResult<Json<Dashboa
             end as "days_until!: i32"
         from person
         where
-            status = 'active'
+            tenant_id = current_tenant()
+            and status = 'active'
             and
             (
                 (to_char(birth_date, 'MMDD') >= to_char(current_date, 'MMDD')
@@ -85,7 +95,8 @@ pub async fn get_dashboard(State(pool): State<PgPool>) -> AppResult<Json<Dashboa
         from policy p
         join person pe on p.holder_id = pe.id
         where
-            p.end_date <= current_date + interval '7 days'
+            p.tenant_id = current_tenant()
+            and p.end_date <= current_date + interval '7 days'
             and p.status = 'active'
         order by p.end_date asc
         "#
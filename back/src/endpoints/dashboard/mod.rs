@@ -1,10 +1,11 @@
 mod get;
 mod model;
 
-use axum::Router;
-use axum::routing::get;
-use sqlx::PgPool;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
-pub fn router() -> Router<PgPool> {
-    Router::new().route("/dashboard", get(get::get_dashboard))
+use crate::state::AppState;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(get::get_dashboard))
 }
@@ -1,9 +1,10 @@
 use chrono::NaiveDate;
 use serde::Serialize;
+use utoipa::ToSchema;
 
 use crate::shared::policy::model::PolicyType;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct DashboardStats {
     pub people_count: i64,
     pub policy_count: i64,
@@ -12,7 +13,7 @@ pub struct DashboardStats {
     pub expiring_policies: Vec<ExpiringPolicy>,
 }
 
-#[derive(Serialize, sqlx::FromRow)]
+#[derive(Serialize, sqlx::FromRow, ToSchema)]
 pub struct BirthdayPerson {
     pub id: i32,
     pub first_name: String,
@@ -23,7 +24,7 @@ pub struct BirthdayPerson {
     pub days_until: i32,
 }
 
-#[derive(Serialize, sqlx::FromRow)]
+#[derive(Serialize, sqlx::FromRow, ToSchema)]
 pub struct ExpiringPolicy {
     pub id: i32,
     pub series: String,
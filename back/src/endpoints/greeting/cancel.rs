@@ -0,0 +1,110 @@
+use axum::Extension;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use crate::auth::middleware::AuthUser;
+use crate::error::{AppError, AppResult};
+use crate::notify::birthday::{GreetingStatus, is_birthday};
+
+/// Stops the greeting for a person's birthday on `date` from being sent.
+#[utoipa::path(
+    post,
+    path = "/greetings/birthdays/{person_id}/{date}/cancel",
+    tag = "greetings",
+    params(
+        ("person_id" = i32, Path),
+        ("date" = NaiveDate, Path, description = "Birthday the greeting is for")
+    ),
+    responses(
+        (status = 204, description = "Cancelled"),
+        (status = 400, description = "Not an upcoming birthday of this person"),
+        (status = 404, description = "No such person"),
+        (status = 409, description = "Already sent")
+    )
+)]
+pub async fn cancel_greeting(
+    State(pool): State<PgPool>,
+    Extension(user): Extension<AuthUser>,
+    Path((person_id, date)): Path<(i32, NaiveDate)>,
+) -> AppResult<StatusCode> {
+    let birth_date = sqlx::query_scalar!(
+        "select birth_date from person where id = $1 and tenant_id = current_tenant()",
+        person_id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::not_found())?;
+
+    let today = chrono::Local::now().date_naive();
+    if date < today || !is_birthday(birth_date, date) {
+        return Err(AppError::status(
+            StatusCode::BAD_REQUEST,
+            "Not an upcoming birthday of this person",
+        ));
+    }
+
+    let existing = sqlx::query_scalar!(
+        r#"
+        insert into birthday_greeting (person_id, greeting_date, status, cancelled_by)
+        values ($1, $2, 'cancelled', $3)
+        on conflict (person_id, greeting_date) do update set person_id = excluded.person_id
+        returning status as "status: GreetingStatus"
+        "#,
+        person_id,
+        date,
+        user.email
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    if existing == GreetingStatus::Sent {
+        return Err(AppError::status(
+            StatusCode::CONFLICT,
+            "The greeting was already sent",
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Undoes a cancellation, so the greeting goes out on the day after all.
+#[utoipa::path(
+    delete,
+    path = "/greetings/birthdays/{person_id}/{date}/cancel",
+    tag = "greetings",
+    params(
+        ("person_id" = i32, Path),
+        ("date" = NaiveDate, Path, description = "Birthday the greeting is for")
+    ),
+    responses(
+        (status = 204, description = "The greeting will be sent again"),
+        (status = 404, description = "The greeting was not cancelled")
+    )
+)]
+pub async fn restore_greeting(
+    State(pool): State<PgPool>,
+    Path((person_id, date)): Path<(i32, NaiveDate)>,
+) -> AppResult<StatusCode> {
+    let result = sqlx::query!(
+        r#"
+        delete from birthday_greeting
+        where
+            person_id = $1
+            and greeting_date = $2
+            and status = 'cancelled'
+            and person_id in (select id from person where tenant_id = current_tenant())
+        "#,
+        person_id,
+        date
+    )
+    .execute(&pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found());
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
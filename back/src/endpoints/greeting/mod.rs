@@ -0,0 +1,13 @@
+pub mod cancel;
+pub mod upcoming;
+
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(upcoming::get_upcoming_greetings))
+        .routes(routes!(cancel::cancel_greeting, cancel::restore_greeting))
+}
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use axum::extract::{Query, State};
+use axum::{Extension, Json};
+use serde::Deserialize;
+use sqlx::PgPool;
+use utoipa::IntoParams;
+
+use crate::endpoints::notification::reminders::configured;
+use crate::error::{AppError, AppResult};
+use crate::notify::Notifier;
+use crate::notify::birthday::{self, UpcomingGreeting};
+
+const MAX_DAYS: i32 = 366;
+
+#[derive(Deserialize, IntoParams)]
+pub struct UpcomingQuery {
+    pub days: Option<i32>,
+}
+
+/// Birthday greetings from today for `days` days (7 by default), including
+/// the ones already sent or cancelled.
+#[utoipa::path(
+    get,
+    path = "/greetings/birthdays",
+    tag = "greetings",
+    params(UpcomingQuery),
+    responses(
+        (status = 200, body = Vec<UpcomingGreeting>),
+        (status = 400, description = "`days` is not between 1 and 366"),
+        (status = 503, description = "Neither email nor SMS is configured")
+    )
+)]
+pub async fn get_upcoming_greetings(
+    State(pool): State<PgPool>,
+    Extension(notifier): Extension<Option<Arc<Notifier>>>,
+    Query(query): Query<UpcomingQuery>,
+) -> AppResult<Json<Vec<UpcomingGreeting>>> {
+    let notifier = configured(notifier)?;
+
+    let days = query.days.unwrap_or(7);
+    if !(1..=MAX_DAYS).contains(&days) {
+        return Err(AppError::bad_request());
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let greetings = birthday::upcoming_greetings(&pool, &notifier, today, days).await?;
+    Ok(Json(greetings))
+}
@@ -0,0 +1,48 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+
+#[utoipa::path(
+    delete,
+    path = "/insurers/{id}",
+    tag = "insurers",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "No such insurer"),
+        (status = 409, description = "Insurer is used by policies")
+    )
+)]
+pub async fn delete_insurer(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+) -> AppResult<StatusCode> {
+    let policy_count = sqlx::query_scalar!(
+        r#"select count(*) as "count!" from policy where insurer_id = $1 and tenant_id = current_tenant()"#,
+        id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    if policy_count > 0 {
+        return Err(AppError::status(
+            StatusCode::CONFLICT,
+            format!("Insurer is used by {} policies", policy_count),
+        ));
+    }
+
+    let result = sqlx::query!(
+        "delete from insurer where id = $1 and tenant_id = current_tenant()",
+        id
+    )
+    .execute(&pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found());
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
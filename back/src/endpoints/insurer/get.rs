@@ -0,0 +1,51 @@
+use axum::Json;
+use axum::extract::{Query, State};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::error::AppResult;
+
+#[derive(Serialize, ToSchema)]
+pub struct InsurerWithPolicies {
+    pub id: i32,
+    pub name: String,
+    pub policy_count: i64,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct InsurerQuery {
+    pub search: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/insurers",
+    tag = "insurers",
+    params(InsurerQuery),
+    responses((status = 200, body = Vec<InsurerWithPolicies>))
+)]
+pub async fn get_insurers(
+    State(pool): State<PgPool>,
+    Query(query): Query<InsurerQuery>,
+) -> AppResult<Json<Vec<InsurerWithPolicies>>> {
+    let search = query.search.unwrap_or_default().to_lowercase();
+    let search_pattern = format!("%{}%", search);
+
+    let insurers = sqlx::query_as!(
+        InsurerWithPolicies,
+        r#"
+        select i.id, i.name, count(p.id) as "policy_count!: i64"
+        from insurer i
+        left join policy p on p.insurer_id = i.id
+        where lower(i.name) like $1 and i.tenant_id = current_tenant()
+        group by i.id, i.name
+        order by i.name
+        "#,
+        search_pattern
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(insurers))
+}
@@ -0,0 +1,35 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+use crate::shared::insurer::model::Insurer;
+
+#[utoipa::path(
+    get,
+    path = "/insurers/{id}",
+    tag = "insurers",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 200, body = Insurer),
+        (status = 404, description = "No such insurer")
+    )
+)]
+pub async fn get_insurer(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+) -> AppResult<Json<Insurer>> {
+    sqlx::query_as!(
+        Insurer,
+        r#"
+        select id, name
+        from insurer
+        where id = $1 and tenant_id = current_tenant()
+        "#,
+        id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .map(Json)
+    .ok_or(AppError::not_found())
+}
@@ -0,0 +1,22 @@
+pub mod delete;
+pub mod get;
+pub mod get_by_id;
+pub mod post;
+#[cfg(test)]
+mod tests;
+pub mod update;
+
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get::get_insurers, post::create_insurer))
+        .routes(routes!(
+            get_by_id::get_insurer,
+            update::update_insurer,
+            delete::delete_insurer
+        ))
+}
@@ -0,0 +1,43 @@
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use serde::Deserialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::error::AppResult;
+use crate::shared::insurer::{self, model::Insurer};
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateInsurer {
+    name: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/insurers",
+    tag = "insurers",
+    responses(
+        (status = 201, body = Insurer),
+        (status = 409, description = "An insurer with the name already exists")
+    )
+)]
+pub async fn create_insurer(
+    State(pool): State<PgPool>,
+    Json(body): Json<CreateInsurer>,
+) -> AppResult<(StatusCode, Json<Insurer>)> {
+    let insurer = sqlx::query_as!(
+        Insurer,
+        r#"
+        insert into insurer (name)
+        values ($1)
+        returning id, name
+        "#,
+        body.name
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|err| insurer::name_taken(err, &body.name))?;
+
+    Ok((StatusCode::CREATED, Json(insurer)))
+}
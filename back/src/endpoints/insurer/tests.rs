@@ -0,0 +1,22 @@
+use axum::http::StatusCode;
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::testing::{TestApp, fixtures};
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn name_taken(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    fixtures::insurer(&app, "ТАС").await;
+    let other = fixtures::insurer(&app, "ARX").await;
+
+    let conflict = app
+        .post("/insurers", json!({ "name": "ТАС" }))
+        .await
+        .expect(StatusCode::CONFLICT);
+    assert_eq!(conflict["message"], "Insurer ТАС already exists");
+
+    app.put(&format!("/insurers/{}", other), json!({ "name": "ТАС" }))
+        .await
+        .expect(StatusCode::CONFLICT);
+}
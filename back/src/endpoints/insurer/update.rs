@@ -0,0 +1,47 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use serde::Deserialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::error::{AppError, AppResult};
+use crate::shared::insurer::{self, model::Insurer};
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateInsurer {
+    name: String,
+}
+
+#[utoipa::path(
+    put,
+    path = "/insurers/{id}",
+    tag = "insurers",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 200, body = Insurer),
+        (status = 404, description = "No such insurer"),
+        (status = 409, description = "An insurer with the name already exists")
+    )
+)]
+pub async fn update_insurer(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+    Json(body): Json<UpdateInsurer>,
+) -> AppResult<Json<Insurer>> {
+    let insurer = sqlx::query_as!(
+        Insurer,
+        r#"
+        update insurer
+        set name = $2
+        where id = $1 and tenant_id = current_tenant()
+        returning id, name
+        "#,
+        id,
+        body.name
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|err| insurer::name_taken(err, &body.name))?;
+
+    insurer.map(Json).ok_or(AppError::not_found())
+}
@@ -1,17 +1,38 @@
 pub mod agent;
+pub mod attachment;
+pub mod calendar;
 pub mod car;
 pub mod dashboard;
+pub mod greeting;
+pub mod insurer;
+pub mod monitoring;
+pub mod notification;
+pub mod payment;
 pub mod person;
 pub mod policy;
+pub mod search;
+pub mod tenant;
+pub mod webhook;
 
-use axum::Router;
-use sqlx::PgPool;
+use utoipa_axum::router::OpenApiRouter;
 
-pub fn router() -> Router<PgPool> {
-    Router::new()
+use crate::state::AppState;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
         .merge(agent::router())
+        .merge(attachment::router())
+        .merge(calendar::router())
         .merge(car::router())
         .merge(dashboard::router())
+        .merge(greeting::router())
+        .merge(insurer::router())
+        .merge(monitoring::router())
+        .merge(notification::router())
+        .merge(payment::router())
         .merge(person::router())
         .merge(policy::router())
+        .merge(search::router())
+        .merge(tenant::router())
+        .merge(webhook::router())
 }
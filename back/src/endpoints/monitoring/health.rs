@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::{migrate, tenant};
+
+/// Load balancers give up on a probe after a few seconds, answer before that.
+const READY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The probe is public, so the cause is only logged.
+const NOT_READY: &str = "Database is not ready";
+const TIMED_OUT: &str = "Database did not answer in time";
+
+/// Liveness: the process is up and serving requests.
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "monitoring",
+    security(()),
+    responses((status = 200, description = "Up", body = String, content_type = "text/plain"))
+)]
+pub async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// Readiness: the database answers and has the migrations this build expects.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "monitoring",
+    security(()),
+    responses(
+        (status = 200, description = "Ready", body = String, content_type = "text/plain"),
+        (status = 503, description = "Not ready, the cause is logged", body = String, content_type = "text/plain")
+    )
+)]
+pub async fn readyz(State(pool): State<PgPool>) -> Response {
+    // Public routes see no tenant's rows, the migration table included
+    let check = tenant::unrestricted(migrate::check(&pool));
+    let reason = match tokio::time::timeout(READY_TIMEOUT, check).await {
+        Ok(Ok(())) => return "ok".into_response(),
+        Ok(Err(err)) => {
+            warn!("Not ready: {:#}", err);
+            NOT_READY
+        }
+        Err(_) => {
+            warn!("Not ready: {}", TIMED_OUT);
+            TIMED_OUT
+        }
+    };
+    (StatusCode::SERVICE_UNAVAILABLE, reason).into_response()
+}
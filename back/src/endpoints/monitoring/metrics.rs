@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use sqlx::PgPool;
+
+use crate::error::AppResult;
+use crate::metrics::{Exposition, Metrics};
+use crate::tenant;
+
+/// Policies ending within this many days count as expiring this week.
+const EXPIRING_DAYS: i32 = 7;
+
+/// Metrics in the Prometheus text format.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "monitoring",
+    security(()),
+    responses((status = 200, body = String, content_type = "text/plain; version=0.0.4"))
+)]
+pub async fn get_metrics(
+    State(pool): State<PgPool>,
+    State(metrics): State<Arc<Metrics>>,
+) -> AppResult<impl IntoResponse> {
+    let mut out = Exposition::default();
+    metrics.write(&mut out);
+    write_pool(&pool, &mut out);
+    // Totals over every tenant
+    tenant::unrestricted(write_business(&pool, &mut out)).await?;
+
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        out.finish(),
+    ))
+}
+
+fn write_pool(pool: &PgPool, out: &mut Exposition) {
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+
+    out.describe(
+        "db_pool_connections",
+        "gauge",
+        "Database connections by state",
+    );
+    out.sample(
+        "db_pool_connections",
+        &[("state", "active")],
+        size.saturating_sub(idle),
+    );
+    out.sample("db_pool_connections", &[("state", "idle")], idle);
+
+    out.describe(
+        "db_pool_max_connections",
+        "gauge",
+        "Most connections the pool opens",
+    );
+    out.sample(
+        "db_pool_max_connections",
+        &[],
+        pool.options().get_max_connections(),
+    );
+}
+
+async fn write_business(pool: &PgPool, out: &mut Exposition) -> sqlx::Result<()> {
+    let active = sqlx::query!(
+        r#"
+        select type::text as "policy_type!", count(*) as "count!"
+        from policy
+        where status = 'active'
+        group by type
+        order by type
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    out.describe("insure_policies_active", "gauge", "Active policies by type");
+    for row in active {
+        out.sample(
+            "insure_policies_active",
+            &[("policy_type", &row.policy_type)],
+            row.count,
+        );
+    }
+
+    let counts = sqlx::query!(
+        r#"
+        select
+            (
+                select count(*)
+                from policy
+                where status = 'active' and end_date between current_date and current_date + $1::int
+            ) as "expiring!",
+            (
+                select count(*)
+                from policy_payment pp
+                join policy p on p.id = pp.policy_id
+                where pp.paid_on is null and pp.due_date < current_date and p.status = 'active'
+            ) as "overdue_payments!",
+            (
+                select count(*) from webhook_delivery where status = 'pending'
+            ) as "pending_webhooks!"
+        "#,
+        EXPIRING_DAYS
+    )
+    .fetch_one(pool)
+    .await?;
+
+    out.describe(
+        "insure_policies_expiring",
+        "gauge",
+        "Active policies ending within the next 7 days",
+    );
+    out.sample("insure_policies_expiring", &[], counts.expiring);
+
+    out.describe(
+        "insure_payments_overdue",
+        "gauge",
+        "Unpaid payments of active policies past their due date",
+    );
+    out.sample("insure_payments_overdue", &[], counts.overdue_payments);
+
+    out.describe(
+        "insure_webhook_deliveries_pending",
+        "gauge",
+        "Webhook deliveries waiting to be sent or retried",
+    );
+    out.sample(
+        "insure_webhook_deliveries_pending",
+        &[],
+        counts.pending_webhooks,
+    );
+
+    Ok(())
+}
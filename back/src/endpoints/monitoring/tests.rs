@@ -0,0 +1,45 @@
+use axum::http::StatusCode;
+use chrono::{Days, Local};
+use sqlx::PgPool;
+
+use crate::migrate;
+use crate::testing::TestApp;
+use crate::testing::fixtures::{self, PolicyBuilder};
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn ready(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+
+    let response = app.get("/readyz").await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.text);
+    assert_eq!(response.text, "ok");
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn not_ready_hides_the_cause(pool: PgPool) {
+    migrate::revert(&pool).await.unwrap();
+    let app = TestApp::new(pool).await;
+
+    let response = app.get("/readyz").await;
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.text, "Database is not ready");
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn business_metrics(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let end_date = Local::now().date_naive() + Days::new(3);
+    PolicyBuilder::osago(&insurer)
+        .with("end_date", end_date.to_string())
+        .create(&app)
+        .await;
+
+    let response = app.get("/metrics").await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.text);
+    assert!(
+        response.text.contains("\ninsure_policies_expiring 1\n"),
+        "{}",
+        response.text
+    );
+}
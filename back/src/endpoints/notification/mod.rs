@@ -0,0 +1,15 @@
+pub mod reminders;
+pub mod sms;
+
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(reminders::send_expiry_reminders))
+        .routes(routes!(reminders::send_payment_reminders))
+        .routes(routes!(reminders::send_birthday_greetings))
+        .routes(routes!(sms::get_sms_messages))
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use axum::{Extension, Json, extract::State};
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+use crate::notify::{self, Notifier, ReminderReport};
+
+pub fn configured(notifier: Option<Arc<Notifier>>) -> AppResult<Arc<Notifier>> {
+    notifier.ok_or(AppError::status(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Neither email nor SMS is configured",
+    ))
+}
+
+/// Sends due expiry reminders now instead of waiting for the hourly run.
+#[utoipa::path(
+    post,
+    path = "/notifications/expiry-reminders",
+    tag = "notifications",
+    responses(
+        (status = 200, body = ReminderReport),
+        (status = 503, description = "Neither email nor SMS is configured")
+    )
+)]
+pub async fn send_expiry_reminders(
+    State(pool): State<PgPool>,
+    Extension(notifier): Extension<Option<Arc<Notifier>>>,
+) -> AppResult<Json<ReminderReport>> {
+    let notifier = configured(notifier)?;
+
+    let mut report = ReminderReport::default();
+    notify::expiry::send_expiry_reminders(&pool, &notifier, &mut report).await?;
+    Ok(Json(report))
+}
+
+/// Sends due payment reminders now instead of waiting for the hourly run.
+#[utoipa::path(
+    post,
+    path = "/notifications/payment-reminders",
+    tag = "notifications",
+    responses(
+        (status = 200, body = ReminderReport),
+        (status = 503, description = "Neither email nor SMS is configured")
+    )
+)]
+pub async fn send_payment_reminders(
+    State(pool): State<PgPool>,
+    Extension(notifier): Extension<Option<Arc<Notifier>>>,
+) -> AppResult<Json<ReminderReport>> {
+    let notifier = configured(notifier)?;
+
+    let mut report = ReminderReport::default();
+    notify::payment::send_payment_reminders(&pool, &notifier, &mut report).await?;
+    Ok(Json(report))
+}
+
+/// Sends today's birthday greetings now, without waiting for `BIRTHDAY_SEND_HOUR`.
+#[utoipa::path(
+    post,
+    path = "/notifications/birthday-greetings",
+    tag = "notifications",
+    responses(
+        (status = 200, body = ReminderReport),
+        (status = 503, description = "Neither email nor SMS is configured")
+    )
+)]
+pub async fn send_birthday_greetings(
+    State(pool): State<PgPool>,
+    Extension(notifier): Extension<Option<Arc<Notifier>>>,
+) -> AppResult<Json<ReminderReport>> {
+    let notifier = configured(notifier)?;
+
+    let mut report = ReminderReport::default();
+    let today = chrono::Local::now().date_naive();
+    notify::birthday::send_birthday_greetings(&pool, &notifier, today, &mut report).await?;
+    Ok(Json(report))
+}
@@ -0,0 +1,92 @@
+use axum::Json;
+use axum::extract::{Query, State};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::error::AppResult;
+use crate::notify::sms::SmsStatus;
+use crate::shared::page::{Page, PageQuery};
+
+#[derive(Serialize, ToSchema)]
+pub struct SmsMessage {
+    pub id: i32,
+    pub person_id: i32,
+    pub policy_id: Option<i32>,
+    pub phone: String,
+    pub text: String,
+    pub provider: String,
+    pub status: SmsStatus,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct SmsQuery {
+    pub person_id: Option<i32>,
+    pub status: Option<SmsStatus>,
+}
+
+/// Sent SMS with their delivery status, newest first.
+#[utoipa::path(
+    get,
+    path = "/notifications/sms",
+    tag = "notifications",
+    params(SmsQuery, PageQuery),
+    responses((status = 200, body = Page<SmsMessage>))
+)]
+pub async fn get_sms_messages(
+    State(pool): State<PgPool>,
+    Query(query): Query<SmsQuery>,
+    Query(page): Query<PageQuery>,
+) -> AppResult<Json<Page<SmsMessage>>> {
+    let limit = page.limit(50);
+    let offset = page.offset();
+
+    let items = sqlx::query_as!(
+        SmsMessage,
+        r#"
+        select
+            id, person_id, policy_id, phone, text, provider,
+            status as "status: SmsStatus", error, created_at, updated_at
+        from sms_message
+        where
+            person_id in (select id from person where tenant_id = current_tenant())
+            and ($1::int is null or person_id = $1)
+            and ($2::sms_status is null or status = $2)
+        order by created_at desc, id desc
+        limit $3
+        offset $4
+        "#,
+        query.person_id,
+        query.status as Option<SmsStatus>,
+        limit,
+        offset
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"
+        select count(*) as "count!"
+        from sms_message
+        where
+            person_id in (select id from person where tenant_id = current_tenant())
+            and ($1::int is null or person_id = $1)
+            and ($2::sms_status is null or status = $2)
+        "#,
+        query.person_id,
+        query.status as Option<SmsStatus>
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(Page {
+        items,
+        total,
+        limit,
+        offset,
+    }))
+}
@@ -0,0 +1,36 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+
+#[utoipa::path(
+    delete,
+    path = "/payments/{id}",
+    tag = "payments",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "No such payment")
+    )
+)]
+pub async fn delete_payment(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+) -> AppResult<StatusCode> {
+    let result = sqlx::query!(
+        r#"
+        delete from policy_payment
+        where id = $1 and policy_id in (select id from policy where tenant_id = current_tenant())
+        "#,
+        id
+    )
+    .execute(&pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found());
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,33 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use sqlx::PgPool;
+
+use crate::error::AppResult;
+use crate::shared::payment::model::Payment;
+
+#[utoipa::path(
+    get,
+    path = "/policies/{id}/payments",
+    tag = "payments",
+    params(("id" = i32, Path, description = "Policy id")),
+    responses((status = 200, body = Vec<Payment>))
+)]
+pub async fn list_payments(
+    State(pool): State<PgPool>,
+    Path(policy_id): Path<i32>,
+) -> AppResult<Json<Vec<Payment>>> {
+    let payments = sqlx::query_as!(
+        Payment,
+        r#"
+        select id, policy_id, due_date, amount, paid_on
+        from policy_payment
+        where policy_id = $1 and policy_id in (select id from policy where tenant_id = current_tenant())
+        order by due_date, id
+        "#,
+        policy_id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(payments))
+}
@@ -0,0 +1,15 @@
+pub mod delete;
+pub mod list;
+pub mod post;
+pub mod update;
+
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(list::list_payments, post::create_payment))
+        .routes(routes!(update::update_payment, delete::delete_payment))
+}
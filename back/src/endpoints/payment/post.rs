@@ -0,0 +1,50 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+use crate::shared::payment::model::{Payment, PaymentNew};
+
+#[utoipa::path(
+    post,
+    path = "/policies/{id}/payments",
+    tag = "payments",
+    params(("id" = i32, Path, description = "Policy id")),
+    responses(
+        (status = 201, body = Payment),
+        (status = 400, description = "Amount is not positive")
+    )
+)]
+pub async fn create_payment(
+    State(pool): State<PgPool>,
+    Path(policy_id): Path<i32>,
+    Json(body): Json<PaymentNew>,
+) -> AppResult<(StatusCode, Json<Payment>)> {
+    if body.amount <= 0 {
+        return Err(AppError::status(
+            StatusCode::BAD_REQUEST,
+            "Amount must be positive",
+        ));
+    }
+
+    let payment = sqlx::query_as!(
+        Payment,
+        r#"
+        insert into policy_payment (policy_id, due_date, amount, paid_on)
+        select id, $2, $3, $4
+        from policy
+        where id = $1 and tenant_id = current_tenant()
+        returning id, policy_id, due_date, amount, paid_on
+        "#,
+        policy_id,
+        body.due_date,
+        body.amount,
+        body.paid_on
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::not_found())?;
+
+    Ok((StatusCode::CREATED, Json(payment)))
+}
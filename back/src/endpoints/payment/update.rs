@@ -0,0 +1,50 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+use crate::shared::payment::model::{Payment, PaymentNew};
+
+/// Also how a payment is marked paid, by setting `paid_on`.
+#[utoipa::path(
+    put,
+    path = "/payments/{id}",
+    tag = "payments",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 200, body = Payment),
+        (status = 400, description = "Amount is not positive"),
+        (status = 404, description = "No such payment")
+    )
+)]
+pub async fn update_payment(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+    Json(body): Json<PaymentNew>,
+) -> AppResult<Json<Payment>> {
+    if body.amount <= 0 {
+        return Err(AppError::status(
+            StatusCode::BAD_REQUEST,
+            "Amount must be positive",
+        ));
+    }
+
+    let payment = sqlx::query_as!(
+        Payment,
+        r#"
+        update policy_payment
+        set due_date = $2, amount = $3, paid_on = $4
+        where id = $1 and policy_id in (select id from policy where tenant_id = current_tenant())
+        returning id, policy_id, due_date, amount, paid_on
+        "#,
+        id,
+        body.due_date,
+        body.amount,
+        body.paid_on
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    payment.map(Json).ok_or(AppError::not_found())
+}
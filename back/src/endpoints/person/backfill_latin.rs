@@ -0,0 +1,92 @@
+use axum::Json;
+use axum::extract::State;
+use serde::Serialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::error::AppResult;
+use crate::shared::translit::latin_or;
+
+#[derive(Serialize, ToSchema)]
+pub struct BackfillLatinResponse {
+    pub updated: usize,
+}
+
+/// Fills empty `*_lat` names of existing people by transliteration.
+/// Spellings entered by hand are left as they are.
+///
+/// Open to every member of the tenant, like editing people is: it only
+/// touches the active tenant's people, only fills blanks and changes nothing
+/// when run again.
+#[utoipa::path(
+    post,
+    path = "/people/backfill-latin",
+    tag = "people",
+    responses((status = 200, body = BackfillLatinResponse))
+)]
+pub async fn backfill_latin(State(pool): State<PgPool>) -> AppResult<Json<BackfillLatinResponse>> {
+    let mut tx = pool.begin().await?;
+
+    let people = sqlx::query!(
+        r#"
+        select
+            id,
+            first_name,
+            first_name_lat,
+            last_name,
+            last_name_lat,
+            patronymic_name,
+            patronymic_name_lat
+        from person
+        where
+            tenant_id = current_tenant()
+            and (
+                coalesce(trim(first_name_lat), '') = ''
+                or coalesce(trim(last_name_lat), '') = ''
+                or (coalesce(trim(patronymic_name), '') <> '' and coalesce(trim(patronymic_name_lat), '') = '')
+            )
+        for update
+        "#
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut ids = Vec::with_capacity(people.len());
+    let mut first_names = Vec::with_capacity(people.len());
+    let mut last_names = Vec::with_capacity(people.len());
+    let mut patronymic_names = Vec::with_capacity(people.len());
+
+    for person in people {
+        ids.push(person.id);
+        first_names.push(latin_or(person.first_name_lat, Some(&person.first_name)));
+        last_names.push(latin_or(person.last_name_lat, Some(&person.last_name)));
+        patronymic_names.push(latin_or(
+            person.patronymic_name_lat,
+            person.patronymic_name.as_deref(),
+        ));
+    }
+
+    sqlx::query!(
+        r#"
+        update person
+        set
+            first_name_lat = latin.first_name_lat,
+            last_name_lat = latin.last_name_lat,
+            patronymic_name_lat = latin.patronymic_name_lat,
+            version = version + 1
+        from unnest($1::int4[], $2::text[], $3::text[], $4::text[])
+            as latin(id, first_name_lat, last_name_lat, patronymic_name_lat)
+        where person.id = latin.id and person.tenant_id = current_tenant()
+        "#,
+        &ids,
+        &first_names as &[Option<String>],
+        &last_names as &[Option<String>],
+        &patronymic_names as &[Option<String>],
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(BackfillLatinResponse { updated: ids.len() }))
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use axum::Json;
+use axum::extract::{Query, State};
+use serde::Serialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::error::AppResult;
+use crate::shared::page::{Page, PageQuery};
+use crate::shared::person::model::{PersonFull, PersonStatus, Sex};
+
+/// Minimum pg_trgm similarity of "last first" names for people sharing a birth date.
+const NAME_SIMILARITY: f32 = 0.5;
+
+#[derive(Clone, Copy, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+    TaxNumber,
+    Phone,
+    NameAndBirthDate,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DuplicatePair {
+    pub first: PersonFull,
+    pub second: PersonFull,
+    pub reasons: Vec<DuplicateReason>,
+}
+
+/// Suggests pairs of people that are likely the same client. Archived
+/// people, including those already merged away, are left out.
+#[utoipa::path(
+    get,
+    path = "/people/duplicates",
+    tag = "people",
+    params(PageQuery),
+    responses((status = 200, body = Page<DuplicatePair>))
+)]
+pub async fn find_duplicates(
+    State(pool): State<PgPool>,
+    Query(page): Query<PageQuery>,
+) -> AppResult<Json<Page<DuplicatePair>>> {
+    let limit = page.limit(30);
+    let offset = page.offset();
+
+    let pairs = sqlx::query!(
+        r#"
+        with candidate as (
+            select
+                id,
+                tax_number,
+                right(regexp_replace(phone, '[^0-9]', '', 'g'), 9) as phone_digits,
+                lower(last_name || ' ' || first_name) as name,
+                birth_date
+            from person
+            where status <> 'archived' and tenant_id = current_tenant()
+        ),
+        pair as (
+            select a.id as first_id, b.id as second_id, 'tax_number' as reason
+            from candidate a
+            join candidate b on a.tax_number = b.tax_number and a.id < b.id
+            where trim(a.tax_number) <> ''
+            union all
+            select a.id, b.id, 'phone'
+            from candidate a
+            join candidate b on a.phone_digits = b.phone_digits and a.id < b.id
+            where length(a.phone_digits) = 9
+            union all
+            select a.id, b.id, 'name_and_birth_date'
+            from candidate a
+            join candidate b on a.birth_date = b.birth_date and a.id < b.id
+            where similarity(a.name, b.name) >= $1
+        )
+        select
+            first_id as "first_id!",
+            second_id as "second_id!",
+            array_agg(distinct reason) as "reasons!",
+            count(*) over () as "total!"
+        from pair
+        group by first_id, second_id
+        order by count(distinct reason) desc, first_id, second_id
+        limit $2
+        offset $3
+        "#,
+        NAME_SIMILARITY,
+        limit,
+        offset,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let total = pairs.first().map(|p| p.total).unwrap_or(0);
+    let ids: Vec<i32> = pairs
+        .iter()
+        .flat_map(|p| [p.first_id, p.second_id])
+        .collect();
+
+    let people: HashMap<i32, PersonFull> = sqlx::query_as!(
+        PersonFull,
+        r#"
+        select
+            id,
+            first_name,
+            first_name_lat,
+            last_name,
+            last_name_lat,
+            patronymic_name,
+            patronymic_name_lat,
+            sex as "sex: Sex",
+            birth_date,
+            tax_number,
+            phone,
+            phone2,
+            email,
+            status as "status: PersonStatus",
+            version
+        from person
+        where id = any($1) and tenant_id = current_tenant()
+        "#,
+        &ids
+    )
+    .fetch_all(&pool)
+    .await?
+    .into_iter()
+    .map(|p| (p.id, p))
+    .collect();
+
+    let items = pairs
+        .into_iter()
+        .filter_map(|pair| {
+            // A person can appear in several pairs
+            let first = people.get(&pair.first_id).cloned()?;
+            let second = people.get(&pair.second_id).cloned()?;
+            let reasons = pair
+                .reasons
+                .iter()
+                .filter_map(|r| match r.as_str() {
+                    "tax_number" => Some(DuplicateReason::TaxNumber),
+                    "phone" => Some(DuplicateReason::Phone),
+                    "name_and_birth_date" => Some(DuplicateReason::NameAndBirthDate),
+                    _ => None,
+                })
+                .collect();
+            Some(DuplicatePair {
+                first,
+                second,
+                reasons,
+            })
+        })
+        .collect();
+
+    Ok(Json(Page {
+        items,
+        total,
+        limit,
+        offset,
+    }))
+}
@@ -1,12 +1,25 @@
 use axum::Json;
 use axum::extract::{Query, State};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::error::AppResult;
+use crate::shared::page::{Page, PageQuery};
 use crate::shared::person::model::{PersonFull, PersonStatus, Sex};
 
-#[derive(Deserialize)]
+const SORT_COLUMNS: &[&str] = &[
+    "first_name",
+    "last_name",
+    "patronymic_name",
+    "birth_date",
+    "tax_number",
+    "phone",
+    "email",
+    "status",
+];
+
+#[derive(Deserialize, IntoParams)]
 pub struct PersonQuery {
     pub search: Option<String>,
     pub statuses: Option<String>,
@@ -24,19 +37,81 @@ fn parse_status_list(s: Option<&str>) -> Vec<PersonStatus> {
         .unwrap_or_default()
 }
 
+/// Minimum pg_trgm word similarity for a fuzzy name match.
+const NAME_SIMILARITY: &str = "0.45";
+
+/// Field a search result matched on best.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchedField {
+    TaxNumber,
+    Phone,
+    Phone2,
+    Email,
+    Name,
+    NameLat,
+}
+
+impl MatchedField {
+    fn from_column(column: &str) -> Option<Self> {
+        match column {
+            "tax_number" => Some(MatchedField::TaxNumber),
+            "phone" => Some(MatchedField::Phone),
+            "phone2" => Some(MatchedField::Phone2),
+            "email" => Some(MatchedField::Email),
+            "name" => Some(MatchedField::Name),
+            "name_lat" => Some(MatchedField::NameLat),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PersonListItem {
+    #[serde(flatten)]
+    pub person: PersonFull,
+    /// Absent when listing without a search
+    pub matched_field: Option<MatchedField>,
+}
+
+/// Lists people, optionally filtered by a search over names in Cyrillic and
+/// Latin, tax number, phones and email. Names match fuzzily through trigram
+/// indexes, so typos, reordered parts and transliterated input still hit.
+/// Unless another sort is requested, exact tax number, phone and email
+/// matches come first, followed by the closest names.
+#[utoipa::path(
+    get,
+    path = "/people",
+    tag = "people",
+    params(PersonQuery, PageQuery),
+    responses((status = 200, body = Page<PersonListItem>))
+)]
 pub async fn get_people(
     State(pool): State<PgPool>,
     Query(query): Query<PersonQuery>,
-) -> AppResult<Json<Vec<PersonFull>>> {
-    let search = query.search.unwrap_or_default().to_lowercase();
+    Query(page): Query<PageQuery>,
+) -> AppResult<Json<Page<PersonListItem>>> {
+    let search = query.search.unwrap_or_default().trim().to_lowercase();
     let search_pattern = format!("%{}%", search);
     let phone_digits: String = search.chars().filter(|c| c.is_ascii_digit()).collect();
     let has_digits = !phone_digits.is_empty();
     let phone_pattern = format!("%{}%", phone_digits);
     let statuses: Vec<PersonStatus> = parse_status_list(query.statuses.as_deref());
 
-    let people = sqlx::query_as!(
-        PersonFull,
+    let sort = page.sort(SORT_COLUMNS)?;
+    let limit = page.limit(30);
+    let offset = page.offset();
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "select set_config('pg_trgm.word_similarity_threshold', $1, true)",
+        NAME_SIMILARITY
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let rows = sqlx::query!(
         r#"
         select
             id,
@@ -52,26 +127,156 @@ pub async fn get_people(
             phone,
             phone2,
             email,
-            status as "status: PersonStatus"
+            status as "status: PersonStatus",
+            version,
+            hit.field as "matched_field?"
         from person
+        left join lateral (
+            select field, score
+            from (
+                values
+                    ('tax_number', case
+                        when tax_number = $1 then 2
+                        when tax_number like $2 then 1
+                    end),
+                    ('phone', case
+                        when length($3) >= 9
+                            and right(regexp_replace(phone, '[^0-9]', '', 'g'), 9) = right($3, 9) then 2
+                        when $4 and regexp_replace(phone, '[^0-9]', '', 'g') like $5 then 1
+                    end),
+                    ('phone2', case
+                        when length($3) >= 9
+                            and right(regexp_replace(coalesce(phone2, ''), '[^0-9]', '', 'g'), 9) = right($3, 9) then 2
+                        when $4 and regexp_replace(coalesce(phone2, ''), '[^0-9]', '', 'g') like $5 then 1
+                    end),
+                    ('email', case
+                        when lower(email) = $1 then 2
+                        when lower(email) like $2 then 1
+                    end),
+                    ('name', greatest(
+                        case when person_name_doc(last_name, first_name, patronymic_name) like $2 then 1 end,
+                        word_similarity($1, person_name_doc(last_name, first_name, patronymic_name))
+                    )),
+                    ('name_lat', word_similarity(
+                        translit_uk($1),
+                        person_name_lat_doc(last_name, first_name, patronymic_name, last_name_lat, first_name_lat, patronymic_name_lat)
+                    ))
+            ) as candidate(field, score)
+            where $1 <> '' and score > 0
+            order by score desc
+            limit 1
+        ) hit on true
         where
             (
-                lower(first_name || ' ' || last_name) like $1
-                or lower(tax_number) like $1
-                or ($3 and regexp_replace(phone, '[^0-9]', '', 'g') like $2)
-                or ($3 and regexp_replace(coalesce(phone2, ''), '[^0-9]', '', 'g') like $2)
-                or lower(email) like $1
+                $1 = ''
+                or person_name_doc(last_name, first_name, patronymic_name) like $2
+                or $1 <% person_name_doc(last_name, first_name, patronymic_name)
+                or translit_uk($1) <% person_name_lat_doc(last_name, first_name, patronymic_name, last_name_lat, first_name_lat, patronymic_name_lat)
+                or tax_number like $2
+                or ($4 and regexp_replace(phone, '[^0-9]', '', 'g') like $5)
+                or ($4 and regexp_replace(coalesce(phone2, ''), '[^0-9]', '', 'g') like $5)
+                or lower(email) like $2
             )
-            and (cardinality($4::person_status[]) = 0 or status = any($4::person_status[]))
-        limit 30
+            and (cardinality($6::person_status[]) = 0 or status = any($6::person_status[]))
+            and tenant_id = current_tenant()
+        order by
+            case when $7::text = 'first_name' and not $8 then first_name end asc,
+            case when $7::text = 'first_name' and $8 then first_name end desc,
+            case when $7::text = 'last_name' and not $8 then last_name end asc,
+            case when $7::text = 'last_name' and $8 then last_name end desc,
+            case when $7::text = 'patronymic_name' and not $8 then patronymic_name end asc,
+            case when $7::text = 'patronymic_name' and $8 then patronymic_name end desc,
+            case when $7::text = 'birth_date' and not $8 then birth_date end asc,
+            case when $7::text = 'birth_date' and $8 then birth_date end desc,
+            case when $7::text = 'tax_number' and not $8 then tax_number end asc,
+            case when $7::text = 'tax_number' and $8 then tax_number end desc,
+            case when $7::text = 'phone' and not $8 then phone end asc,
+            case when $7::text = 'phone' and $8 then phone end desc,
+            case when $7::text = 'email' and not $8 then email end asc,
+            case when $7::text = 'email' and $8 then email end desc,
+            case when $7::text = 'status' and not $8 then status end asc,
+            case when $7::text = 'status' and $8 then status end desc,
+            hit.score desc nulls last,
+            last_name,
+            first_name,
+            id
+        limit $9
+        offset $10
         "#,
+        search,
         search_pattern,
+        phone_digits,
+        has_digits,
         phone_pattern,
+        &statuses as &[PersonStatus],
+        sort as Option<String>,
+        page.descending(),
+        limit,
+        offset,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"
+        select count(*) as "count!"
+        from person
+        where
+            (
+                $1 = ''
+                or person_name_doc(last_name, first_name, patronymic_name) like $2
+                or $1 <% person_name_doc(last_name, first_name, patronymic_name)
+                or translit_uk($1) <% person_name_lat_doc(last_name, first_name, patronymic_name, last_name_lat, first_name_lat, patronymic_name_lat)
+                or tax_number like $2
+                or ($3 and regexp_replace(phone, '[^0-9]', '', 'g') like $4)
+                or ($3 and regexp_replace(coalesce(phone2, ''), '[^0-9]', '', 'g') like $4)
+                or lower(email) like $2
+            )
+            and (cardinality($5::person_status[]) = 0 or status = any($5::person_status[]))
+            and tenant_id = current_tenant()
+        "#,
+        search,
+        search_pattern,
         has_digits,
+        phone_pattern,
         &statuses as &[PersonStatus],
     )
-    .fetch_all(&pool)
+    .fetch_one(&mut *tx)
     .await?;
 
-    Ok(Json(people))
+    tx.commit().await?;
+
+    let items = rows
+        .into_iter()
+        .map(|row| PersonListItem {
+            person: PersonFull {
+                id: row.id,
+                first_name: row.first_name,
+                first_name_lat: row.first_name_lat,
+                last_name: row.last_name,
+                last_name_lat: row.last_name_lat,
+                patronymic_name: row.patronymic_name,
+                patronymic_name_lat: row.patronymic_name_lat,
+                sex: row.sex,
+                birth_date: row.birth_date,
+                tax_number: row.tax_number,
+                phone: row.phone,
+                phone2: row.phone2,
+                email: row.email,
+                status: row.status,
+                version: row.version,
+            },
+            matched_field: row
+                .matched_field
+                .as_deref()
+                .and_then(MatchedField::from_column),
+        })
+        .collect();
+
+    Ok(Json(Page {
+        items,
+        total,
+        limit,
+        offset,
+    }))
 }
@@ -1,25 +1,43 @@
 use axum::Json;
 use axum::extract::{Path, State};
+use axum::response::IntoResponse;
 use serde::Serialize;
 use sqlx::PgPool;
+use utoipa::ToSchema;
 
 use crate::error::{AppError, AppResult};
 use crate::shared::{
+    attachment::{
+        self,
+        model::{Attachment, AttachmentOwner},
+    },
     person::model::{PersonFull, PersonStatus, Sex},
     policy::model::{PolicyShort, PolicyStatus, PolicyType},
+    version,
 };
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PersonWithPolicies {
     #[serde(flatten)]
     pub person: PersonFull,
     pub policies: Vec<PolicyShort>,
+    pub attachments: Vec<Attachment>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/people/{id}",
+    tag = "people",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 200, body = PersonWithPolicies, headers(("ETag" = String))),
+        (status = 404, description = "No such person")
+    )
+)]
 pub async fn get_person(
     State(pool): State<PgPool>,
     Path(id): Path<i32>,
-) -> AppResult<Json<PersonWithPolicies>> {
+) -> AppResult<impl IntoResponse> {
     let person = sqlx::query_as!(
         PersonFull,
         r#"
@@ -37,9 +55,10 @@ pub async fn get_person(
             phone,
             phone2,
             email,
-            status as "status: PersonStatus"
+            status as "status: PersonStatus",
+            version
         from person
-        where id = $1
+        where id = $1 and tenant_id = current_tenant()
         "#,
         id
     )
@@ -54,6 +73,7 @@ pub async fn get_person(
             policy.id,
             type as "policy_type: PolicyType",
             person.first_name || ' ' || person.last_name as "holder_name!",
+            insurer.name as insurer_name,
             series,
             number,
             start_date,
@@ -69,11 +89,12 @@ pub async fn get_person(
             ) as agent_names
         from policy
         join person on policy.holder_id = person.id
+        join insurer on policy.insurer_id = insurer.id
         left join green_card_policy on policy.id = green_card_policy.id
         left join osago_policy on policy.id = osago_policy.id
         left join car on car.id = coalesce(green_card_policy.car_id, osago_policy.car_id)
         left join medassistance_policy_member mpm on policy.id = mpm.medassistance_policy_id
-        where policy.holder_id = $1 or mpm.member_id = $1
+        where policy.tenant_id = current_tenant() and (policy.holder_id = $1 or mpm.member_id = $1)
         order by start_date desc
         "#,
         id
@@ -81,5 +102,14 @@ pub async fn get_person(
     .fetch_all(&pool)
     .await?;
 
-    Ok(Json(PersonWithPolicies { person, policies }))
+    let attachments = attachment::for_owner(&pool, AttachmentOwner::Person, id).await?;
+
+    Ok((
+        version::etag(person.version),
+        Json(PersonWithPolicies {
+            person,
+            policies,
+            attachments,
+        }),
+    ))
 }
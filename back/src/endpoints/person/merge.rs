@@ -0,0 +1,235 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use serde::Deserialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::error::{AppError, AppResult};
+use crate::shared::attachment::model::AttachmentOwner;
+use crate::shared::existing::OnExisting;
+use crate::shared::person::model::{PersonFull, PersonNew, PersonRef, PersonStatus};
+use crate::shared::person::resolver::{lock_person, resolve_person};
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PersonField {
+    FirstName,
+    FirstNameLat,
+    LastName,
+    LastNameLat,
+    PatronymicName,
+    PatronymicNameLat,
+    Sex,
+    BirthDate,
+    TaxNumber,
+    Phone,
+    Phone2,
+    Email,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct MergePersonRequest {
+    pub duplicate_id: i32,
+    /// Attributes whose value is taken from the duplicate, the rest keep the
+    /// surviving person's value
+    #[serde(default)]
+    pub take: Vec<PersonField>,
+}
+
+/// Merges `duplicate_id` into the person at `id`: policies held, medical
+/// assistance memberships and attachments move to the survivor, the chosen
+/// attributes are copied over, and the duplicate is archived.
+#[utoipa::path(
+    post,
+    path = "/people/{id}/merge",
+    tag = "people",
+    params(("id" = i32, Path, description = "Person that survives the merge")),
+    request_body = MergePersonRequest,
+    responses(
+        (status = 200, body = PersonFull),
+        (status = 400, description = "Merging a person into itself"),
+        (status = 404, description = "No such person or duplicate"),
+        (status = 409, description = "Either person is archived")
+    )
+)]
+pub async fn merge_person(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+    Json(body): Json<MergePersonRequest>,
+) -> AppResult<Json<PersonFull>> {
+    let duplicate_id = body.duplicate_id;
+    if id == duplicate_id {
+        return Err(AppError::status(
+            StatusCode::BAD_REQUEST,
+            "Cannot merge a person into itself",
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let survivor = lock_person(&mut tx, id)
+        .await?
+        .ok_or(AppError::not_found())?;
+    let duplicate = lock_person(&mut tx, duplicate_id)
+        .await?
+        .ok_or(AppError::not_found())?;
+
+    for person in [&survivor, &duplicate] {
+        if matches!(person.status, PersonStatus::Archived) {
+            return Err(AppError::status(
+                StatusCode::CONFLICT,
+                format!("Person {} is archived", person.id),
+            ));
+        }
+    }
+
+    let take = |field| body.take.contains(&field);
+    let merged = PersonNew {
+        first_name: if take(PersonField::FirstName) {
+            duplicate.first_name
+        } else {
+            survivor.first_name
+        },
+        first_name_lat: if take(PersonField::FirstNameLat) {
+            duplicate.first_name_lat
+        } else {
+            survivor.first_name_lat
+        },
+        last_name: if take(PersonField::LastName) {
+            duplicate.last_name
+        } else {
+            survivor.last_name
+        },
+        last_name_lat: if take(PersonField::LastNameLat) {
+            duplicate.last_name_lat
+        } else {
+            survivor.last_name_lat
+        },
+        patronymic_name: if take(PersonField::PatronymicName) {
+            duplicate.patronymic_name
+        } else {
+            survivor.patronymic_name
+        },
+        patronymic_name_lat: if take(PersonField::PatronymicNameLat) {
+            duplicate.patronymic_name_lat
+        } else {
+            survivor.patronymic_name_lat
+        },
+        sex: if take(PersonField::Sex) {
+            duplicate.sex
+        } else {
+            survivor.sex
+        },
+        birth_date: if take(PersonField::BirthDate) {
+            duplicate.birth_date
+        } else {
+            survivor.birth_date
+        },
+        tax_number: if take(PersonField::TaxNumber) {
+            duplicate.tax_number
+        } else {
+            survivor.tax_number
+        },
+        phone: if take(PersonField::Phone) {
+            duplicate.phone
+        } else {
+            survivor.phone
+        },
+        phone2: if take(PersonField::Phone2) {
+            duplicate.phone2
+        } else {
+            survivor.phone2
+        },
+        email: if take(PersonField::Email) {
+            duplicate.email
+        } else {
+            survivor.email
+        },
+        status: survivor.status,
+    };
+
+    // Archive first so the duplicate no longer competes for unique values
+    sqlx::query!(
+        r#"
+        update person
+        set status = 'archived', merged_into_id = $1, version = version + 1
+        where (id = $2 or merged_into_id = $2) and tenant_id = current_tenant()
+        "#,
+        id,
+        duplicate_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    resolve_person(
+        &mut tx,
+        PersonRef::ExistingWithUpdates {
+            id,
+            version: None,
+            data: Box::new(merged),
+        },
+        OnExisting::Conflict,
+    )
+    .await?;
+
+    sqlx::query!(
+        r#"
+        update policy set holder_id = $1, version = version + 1
+        where holder_id = $2 and tenant_id = current_tenant()
+        "#,
+        id,
+        duplicate_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // Both may be members of the same policy, keep a single row then
+    sqlx::query!(
+        r#"
+        delete from medassistance_policy_member m
+        where m.member_id = $2
+            and m.medassistance_policy_id in (select id from policy where tenant_id = current_tenant())
+            and exists (
+                select 1 from medassistance_policy_member s
+                where s.medassistance_policy_id = m.medassistance_policy_id and s.member_id = $1
+            )
+        "#,
+        id,
+        duplicate_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        update medassistance_policy_member set member_id = $1
+        where member_id = $2
+            and medassistance_policy_id in (select id from policy where tenant_id = current_tenant())
+        "#,
+        id,
+        duplicate_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        update attachment set owner_id = $1
+        where owner_type = $2 and owner_id = $3 and tenant_id = current_tenant()
+        "#,
+        id,
+        AttachmentOwner::Person as AttachmentOwner,
+        duplicate_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let person = lock_person(&mut tx, id)
+        .await?
+        .ok_or(AppError::not_found())?;
+
+    tx.commit().await?;
+
+    Ok(Json(person))
+}
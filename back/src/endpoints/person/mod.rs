@@ -1,18 +1,30 @@
+pub mod backfill_latin;
+pub mod duplicates;
 pub mod get;
 pub mod get_by_id;
+pub mod merge;
+pub mod notifications;
 pub mod post;
 pub mod search;
+#[cfg(test)]
+mod tests;
 pub mod update;
 
-use axum::{Router, routing::get};
-use sqlx::PgPool;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
-pub fn router() -> Router<PgPool> {
-    Router::new()
-        .route("/people", get(get::get_people).post(post::create_person))
-        .route("/people/search", get(search::search_people))
-        .route(
-            "/people/{id}",
-            get(get_by_id::get_person).put(update::update_person),
-        )
+use crate::state::AppState;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(get::get_people, post::create_person))
+        .routes(routes!(search::search_people))
+        .routes(routes!(duplicates::find_duplicates))
+        .routes(routes!(backfill_latin::backfill_latin))
+        .routes(routes!(get_by_id::get_person, update::update_person))
+        .routes(routes!(merge::merge_person))
+        .routes(routes!(
+            notifications::get_notification_preferences,
+            notifications::update_notification_preferences
+        ))
 }
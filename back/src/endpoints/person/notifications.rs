@@ -0,0 +1,72 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::error::{AppError, AppResult};
+
+/// Which reminders a person agreed to receive.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct NotificationPreferences {
+    pub sms_opt_out: bool,
+    pub birthday_opt_out: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/people/{id}/notifications",
+    tag = "people",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 200, body = NotificationPreferences),
+        (status = 404, description = "No such person")
+    )
+)]
+pub async fn get_notification_preferences(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+) -> AppResult<Json<NotificationPreferences>> {
+    let preferences = sqlx::query_as!(
+        NotificationPreferences,
+        "select sms_opt_out, birthday_opt_out from person where id = $1 and tenant_id = current_tenant()",
+        id
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    preferences.map(Json).ok_or(AppError::not_found())
+}
+
+#[utoipa::path(
+    put,
+    path = "/people/{id}/notifications",
+    tag = "people",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 200, body = NotificationPreferences),
+        (status = 404, description = "No such person")
+    )
+)]
+pub async fn update_notification_preferences(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+    Json(body): Json<NotificationPreferences>,
+) -> AppResult<Json<NotificationPreferences>> {
+    let preferences = sqlx::query_as!(
+        NotificationPreferences,
+        r#"
+        update person
+        set sms_opt_out = $2, birthday_opt_out = $3, version = version + 1
+        where id = $1 and tenant_id = current_tenant()
+        returning sms_opt_out, birthday_opt_out
+        "#,
+        id,
+        body.sms_opt_out,
+        body.birthday_opt_out
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    preferences.map(Json).ok_or(AppError::not_found())
+}
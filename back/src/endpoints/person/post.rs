@@ -3,11 +3,16 @@ use axum::extract::State;
 use axum::http::StatusCode;
 use serde::Deserialize;
 use sqlx::PgPool;
+use utoipa::ToSchema;
 
 use crate::error::AppResult;
+use crate::shared::existing::ExistingConflict;
 use crate::shared::person::model::{PersonFull, PersonStatus, Sex};
+use crate::shared::person::resolver::ensure_tax_number_free;
+use crate::shared::translit::latin_or;
+use crate::webhook::{self, EventType};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct CreatePerson {
     first_name: String,
     first_name_lat: Option<String>,
@@ -24,15 +29,32 @@ pub struct CreatePerson {
     status: PersonStatus,
 }
 
+#[utoipa::path(
+    post,
+    path = "/people",
+    tag = "people",
+    responses(
+        (status = 201, body = PersonFull),
+        (status = 409, body = ExistingConflict, description = "Tax number is taken")
+    )
+)]
 pub async fn create_person(
     State(pool): State<PgPool>,
     Json(body): Json<CreatePerson>,
 ) -> AppResult<(StatusCode, Json<PersonFull>)> {
+    let mut tx = pool.begin().await?;
+    ensure_tax_number_free(&mut *tx, &body.tax_number, None).await?;
+
+    let first_name_lat = latin_or(body.first_name_lat, Some(&body.first_name));
+    let last_name_lat = latin_or(body.last_name_lat, Some(&body.last_name));
+    let patronymic_name_lat = latin_or(body.patronymic_name_lat, body.patronymic_name.as_deref());
+
     let person = sqlx::query_as!(
         PersonFull,
         r#"
         insert into person (first_name, first_name_lat, last_name, last_name_lat, patronymic_name, patronymic_name_lat, sex, birth_date, tax_number, phone, phone2, email, status)
         values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        on conflict do nothing
         returning
             id,
             first_name,
@@ -47,14 +69,15 @@ pub async fn create_person(
             phone,
             phone2,
             email,
-            status as "status: PersonStatus"
+            status as "status: PersonStatus",
+            version
         "#,
         body.first_name,
-        body.first_name_lat,
+        first_name_lat,
         body.last_name,
-        body.last_name_lat,
+        last_name_lat,
         body.patronymic_name,
-        body.patronymic_name_lat,
+        patronymic_name_lat,
         body.sex as Sex,
         body.birth_date,
         body.tax_number,
@@ -63,8 +86,16 @@ pub async fn create_person(
         body.email,
         body.status as PersonStatus
     )
-    .fetch_one(&pool)
+    .fetch_optional(&mut *tx)
     .await?;
+    let Some(person) = person else {
+        // Taken by a concurrent request since the check above
+        ensure_tax_number_free(&mut *tx, &body.tax_number, None).await?;
+        return Err(anyhow::anyhow!("Person insert conflicted without a match").into());
+    };
+
+    webhook::publish(&mut tx, EventType::PersonCreated, &person).await?;
+    tx.commit().await?;
 
     Ok((StatusCode::CREATED, Json(person)))
 }
@@ -2,20 +2,28 @@ use axum::extract::{Query, State};
 use axum::Json;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::error::AppResult;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct SearchQuery {
     pub q: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PersonSearchResult {
     pub id: i32,
     pub label: String,
 }
 
+#[utoipa::path(
+    get,
+    path = "/people/search",
+    tag = "people",
+    params(SearchQuery),
+    responses((status = 200, body = Vec<PersonSearchResult>))
+)]
 pub async fn search_people(
     State(pool): State<PgPool>,
     Query(query): Query<SearchQuery>,
@@ -23,7 +31,7 @@ pub async fn search_people(
     let pattern = format!("%{}%", query.q.to_lowercase());
     let results = sqlx::query_as!(
         PersonSearchResult,
-        "SELECT id, first_name || ' ' || last_name AS \"label!\" FROM person WHERE LOWER(first_name || ' ' || last_name) LIKE $1",
+        "SELECT id, first_name || ' ' || last_name AS \"label!\" FROM person WHERE LOWER(first_name || ' ' || last_name) LIKE $1 AND tenant_id = current_tenant()",
         pattern
     )
     .fetch_all(&pool)
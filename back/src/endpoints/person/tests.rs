@@ -0,0 +1,408 @@
+use axum::http::{Method, StatusCode, header};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use crate::tenant::TENANT_ID;
+use crate::testing::fixtures::{self, PersonBuilder, PolicyBuilder, existing};
+use crate::testing::{TEST_USER, TestApp};
+
+/// Drops the unique index on tax numbers, which the migrations leave out
+/// while migrated data still has duplicates.
+async fn allow_duplicate_tax_numbers(app: &TestApp) {
+    sqlx::query!("drop index person_tax_number_key")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn sort_by_patronymic_name(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    for patronymic_name in ["Петрович", "Андрійович", "Борисович"] {
+        PersonBuilder::new()
+            .with("patronymic_name", patronymic_name)
+            .create(&app)
+            .await;
+    }
+    let patronymic_names = |page: Value| -> Vec<Value> {
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["patronymic_name"].clone())
+            .collect()
+    };
+
+    let page = app
+        .get("/people?sort=patronymic_name")
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(
+        patronymic_names(page),
+        ["Андрійович", "Борисович", "Петрович"]
+    );
+    let page = app
+        .get("/people?sort=patronymic_name&order=desc")
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(
+        patronymic_names(page),
+        ["Петрович", "Борисович", "Андрійович"]
+    );
+}
+
+/// Imported and migrated people can have blank rather than missing Latin
+/// names, which search transliterates the Cyrillic ones for all the same.
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn latin_search_past_blank_latin_names(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let person = PersonBuilder::new()
+        .with("last_name", "Шевченко")
+        .with("first_name", "Олена")
+        .create(&app)
+        .await;
+    sqlx::query!(
+        "update person set last_name_lat = '', first_name_lat = ' ', patronymic_name_lat = '' where id = $1",
+        person["id"].as_i64().unwrap() as i32
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let page = app
+        .get("/people?search=shevchenko%20olena")
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["id"], person["id"]);
+
+    let found = app.get("/search?q=shevchenko").await.expect(StatusCode::OK);
+    assert_eq!(found["people"][0]["id"], person["id"]);
+}
+
+async fn blank_latin_names(app: &TestApp, id: &Value) {
+    sqlx::query!(
+        "update person set last_name_lat = '', first_name_lat = null, patronymic_name_lat = ' ' where id = $1",
+        id.as_i64().unwrap() as i32
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn backfill_latin_in_the_active_tenant(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let blank = PersonBuilder::new()
+        .with("last_name", "Шевченко")
+        .with("first_name", "Олена")
+        .create(&app)
+        .await;
+    blank_latin_names(&app, &blank["id"]).await;
+    let typed = PersonBuilder::new()
+        .with("last_name", "Шевченко")
+        .create(&app)
+        .await;
+    blank_latin_names(&app, &typed["id"]).await;
+    sqlx::query!(
+        "update person set first_name_lat = 'Helen' where id = $1",
+        typed["id"].as_i64().unwrap() as i32
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let partner = sqlx::query_scalar!("insert into tenant (name) values ('Partner') returning id")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    sqlx::query!(
+        "insert into tenant_user (tenant_id, user_email) values ($1, $2)",
+        partner,
+        TEST_USER
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let partner = partner.to_string();
+    let theirs = app
+        .send(
+            Method::POST,
+            "/people",
+            &[(TENANT_ID, &partner)],
+            Some(PersonBuilder::new().body()),
+        )
+        .await
+        .expect(StatusCode::CREATED);
+    blank_latin_names(&app, &theirs["id"]).await;
+
+    let backfilled = app
+        .send(Method::POST, "/people/backfill-latin", &[], None)
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(backfilled["updated"], 2);
+
+    let person = app
+        .get(&format!("/people/{}", blank["id"]))
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(person["last_name_lat"], "Shevchenko");
+    assert_eq!(person["first_name_lat"], "Olena");
+    assert_eq!(person["patronymic_name_lat"], "Petrovych");
+    assert_eq!(person["version"], blank["version"].as_i64().unwrap() + 1);
+    let person = app
+        .get(&format!("/people/{}", typed["id"]))
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(person["first_name_lat"], "Helen");
+    assert_eq!(person["last_name_lat"], "Shevchenko");
+
+    let untouched = sqlx::query_scalar!(
+        "select last_name_lat from person where id = $1",
+        theirs["id"].as_i64().unwrap() as i32
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(untouched.as_deref(), Some(""));
+
+    let again = app
+        .send(Method::POST, "/people/backfill-latin", &[], None)
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(again["updated"], 0);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn notification_change_bumps_version(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let person = PersonBuilder::new();
+    let created = person.create(&app).await;
+    let uri = format!("/people/{}", created["id"]);
+
+    let notifications = json!({ "sms_opt_out": true, "birthday_opt_out": false });
+    app.put(&format!("{}/notifications", uri), notifications)
+        .await
+        .expect(StatusCode::OK);
+    let stored = app.get(&uri).await.expect(StatusCode::OK);
+    assert_eq!(stored["version"], 2);
+
+    // An edit read before the opt-out must not go through unnoticed
+    let if_match = [(header::IF_MATCH, "\"1\"")];
+    app.send(Method::PUT, &uri, &if_match, Some(person.body()))
+        .await
+        .expect(StatusCode::CONFLICT);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn merge_moves_everything_to_the_survivor(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let survivor = PersonBuilder::new().create(&app).await;
+    let duplicate = PersonBuilder::new()
+        .with("first_name", "Тарасик")
+        .create(&app)
+        .await;
+    let (survivor_id, duplicate_id) = (&survivor["id"], &duplicate["id"]);
+
+    let held = PolicyBuilder::osago(&insurer)
+        .with("holder", existing(duplicate_id))
+        .create(&app)
+        .await;
+    let both_members = PolicyBuilder::medassistance(&insurer)
+        .with(
+            "members",
+            json!([existing(survivor_id), existing(duplicate_id)]),
+        )
+        .create(&app)
+        .await;
+    let duplicate_member = PolicyBuilder::medassistance(&insurer)
+        .with("members", json!([existing(duplicate_id)]))
+        .create(&app)
+        .await;
+    let attachment = app
+        .upload(
+            &format!("/people/{}/attachments", duplicate_id),
+            "passport.pdf",
+            "application/pdf",
+            b"%PDF-1.7\n%%EOF\n",
+        )
+        .await
+        .expect(StatusCode::CREATED);
+
+    let merged = app
+        .post(
+            &format!("/people/{}/merge", survivor_id),
+            json!({ "duplicate_id": duplicate_id, "take": ["tax_number", "email"] }),
+        )
+        .await
+        .expect(StatusCode::OK);
+    // Picked attributes come from the duplicate, the rest stay
+    assert_eq!(merged["tax_number"], duplicate["tax_number"]);
+    assert_eq!(merged["email"], duplicate["email"]);
+    assert_eq!(merged["first_name"], "Тарас");
+    assert_eq!(merged["phone"], survivor["phone"]);
+    assert_eq!(merged["version"], survivor["version"].as_i64().unwrap() + 1);
+
+    let policy = app
+        .get(&format!("/policies/{}", held["id"]))
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(&policy["holder"]["id"], survivor_id);
+    for (policy, members) in [(both_members, 1), (duplicate_member, 1)] {
+        let policy = app
+            .get(&format!("/policies/{}", policy["id"]))
+            .await
+            .expect(StatusCode::OK);
+        assert_eq!(policy["members"].as_array().unwrap().len(), members);
+        assert_eq!(&policy["members"][0]["id"], survivor_id);
+    }
+    let attachments = app
+        .get(&format!("/people/{}/attachments", survivor_id))
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(attachments[0]["id"], attachment["id"]);
+
+    let archived = sqlx::query!(
+        r#"select status::text as "status!", merged_into_id from person where id = $1"#,
+        duplicate_id.as_i64().unwrap() as i32
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(archived.status, "archived");
+    assert_eq!(archived.merged_into_id.map(i64::from), survivor_id.as_i64());
+
+    // Archived people are merged no further
+    app.post(
+        &format!("/people/{}/merge", survivor_id),
+        json!({ "duplicate_id": duplicate_id }),
+    )
+    .await
+    .expect(StatusCode::CONFLICT);
+    app.post(
+        &format!("/people/{}/merge", survivor_id),
+        json!({ "duplicate_id": survivor_id }),
+    )
+    .await
+    .expect(StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn failed_merge_changes_nothing(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    allow_duplicate_tax_numbers(&app).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let survivor = PersonBuilder::new().create(&app).await;
+    let duplicate = PersonBuilder::new().create(&app).await;
+    // Shares the duplicate's tax number, so the survivor cannot take it
+    sqlx::query!(
+        "insert into person (first_name, last_name, sex, birth_date, tax_number, phone, email, status, tenant_id)
+        select first_name, last_name, sex, birth_date, tax_number, phone, email, status, tenant_id from person where id = $1",
+        duplicate["id"].as_i64().unwrap() as i32
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let held = PolicyBuilder::osago(&insurer)
+        .with("holder", existing(&duplicate["id"]))
+        .create(&app)
+        .await;
+
+    let response = app
+        .post(
+            &format!("/people/{}/merge", survivor["id"]),
+            json!({ "duplicate_id": duplicate["id"], "take": ["tax_number"] }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT, "{}", response.body);
+
+    // The duplicate was archived before the conflict came up, and the
+    // archiving was rolled back with the rest
+    let person = app
+        .get(&format!("/people/{}", duplicate["id"]))
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(person["status"], duplicate["status"]);
+    assert_eq!(person["version"], duplicate["version"]);
+    let policy = app
+        .get(&format!("/policies/{}", held["id"]))
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(policy["holder"]["id"], duplicate["id"]);
+}
+
+async fn create_id(app: &TestApp, builder: PersonBuilder) -> i64 {
+    builder.create(app).await["id"].as_i64().unwrap()
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn duplicates_by_tax_number_phone_and_name(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    allow_duplicate_tax_numbers(&app).await;
+    // Names and birth dates apart, so that only the intended pairs match
+    let person = |last_name: &str, birth_date: &str| {
+        PersonBuilder::new()
+            .with("last_name", last_name)
+            .with("birth_date", birth_date)
+    };
+
+    let tax_a = create_id(&app, person("Мельник", "1970-01-01")).await;
+    let tax_b = create_id(&app, person("Ткачук", "1971-01-01")).await;
+    // Formatted differently, the same last nine digits
+    let phone_a = create_id(
+        &app,
+        person("Гнатюк", "1972-01-01").with("phone", "+380 (67) 111-22-33"),
+    )
+    .await;
+    let phone_b = create_id(
+        &app,
+        person("Савчук", "1973-01-01").with("phone", "0671112233"),
+    )
+    .await;
+    let name_a = create_id(&app, person("Бондаренко", "1985-04-12")).await;
+    let name_b = create_id(&app, person("Бондаренка", "1985-04-12")).await;
+    // A namesake born on another day is someone else
+    create_id(&app, person("Бондаренко", "1990-01-01")).await;
+    // Archived people are already dealt with
+    let archived = create_id(&app, person("Лисенко", "1974-01-01")).await;
+
+    // The API refuses a taken tax number, migrated data can still have them
+    sqlx::query!(
+        "update person set tax_number = '3111111111' where id = any($1)",
+        &[tax_a as i32, tax_b as i32, archived as i32]
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "update person set status = 'archived' where id = $1",
+        archived as i32
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let page = app.get("/people/duplicates").await.expect(StatusCode::OK);
+    let pairs: Vec<(i64, i64, Value)> = page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|pair| {
+            (
+                pair["first"]["id"].as_i64().unwrap(),
+                pair["second"]["id"].as_i64().unwrap(),
+                pair["reasons"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            (tax_a, tax_b, json!(["tax_number"])),
+            (phone_a, phone_b, json!(["phone"])),
+            (name_a, name_b, json!(["name_and_birth_date"])),
+        ]
+    );
+    assert_eq!(page["total"], 3);
+}
@@ -1,12 +1,17 @@
 use axum::Json;
 use axum::extract::{Path, State};
+use axum::response::IntoResponse;
 use serde::Deserialize;
 use sqlx::PgPool;
+use utoipa::ToSchema;
 
 use crate::error::{AppError, AppResult};
 use crate::shared::person::model::{PersonFull, PersonStatus, Sex};
+use crate::shared::person::resolver::{ensure_tax_number_free, lock_person};
+use crate::shared::translit::latin_or;
+use crate::shared::version::{self, IfMatch, UpdateConflict};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct UpdatePerson {
     first_name: String,
     first_name_lat: Option<String>,
@@ -21,13 +26,45 @@ pub struct UpdatePerson {
     phone2: Option<String>,
     email: String,
     status: PersonStatus,
+    /// Alternative to `If-Match`
+    version: Option<i32>,
 }
 
+#[utoipa::path(
+    put,
+    path = "/people/{id}",
+    tag = "people",
+    params(
+        ("id" = i32, Path),
+        ("If-Match" = Option<String>, Header, description = "ETag of the person being edited")
+    ),
+    responses(
+        (status = 200, body = PersonFull, headers(("ETag" = String))),
+        (status = 404, description = "No such person"),
+        (status = 409, body = UpdateConflict<PersonFull>, description = "Tax number is taken by another person, or the person changed since it was read")
+    )
+)]
 pub async fn update_person(
     State(pool): State<PgPool>,
     Path(id): Path<i32>,
+    if_match: IfMatch,
     Json(body): Json<UpdatePerson>,
-) -> AppResult<Json<PersonFull>> {
+) -> AppResult<impl IntoResponse> {
+    let mut tx = pool.begin().await?;
+
+    let current = lock_person(&mut tx, id)
+        .await?
+        .ok_or(AppError::not_found())?;
+    if version::is_stale(if_match.or(body.version), current.version) {
+        return Err(version::conflict("person", current));
+    }
+
+    ensure_tax_number_free(&mut *tx, &body.tax_number, Some(id)).await?;
+
+    let first_name_lat = latin_or(body.first_name_lat, Some(&body.first_name));
+    let last_name_lat = latin_or(body.last_name_lat, Some(&body.last_name));
+    let patronymic_name_lat = latin_or(body.patronymic_name_lat, body.patronymic_name.as_deref());
+
     let person = sqlx::query_as!(
         PersonFull,
         r#"
@@ -45,8 +82,9 @@ pub async fn update_person(
             phone = $11,
             phone2 = $12,
             email = $13,
-            status = $14
-        where id = $1
+            status = $14,
+            version = version + 1
+        where id = $1 and tenant_id = current_tenant()
         returning
             id,
             first_name,
@@ -61,15 +99,16 @@ pub async fn update_person(
             phone,
             phone2,
             email,
-            status as "status: PersonStatus"
+            status as "status: PersonStatus",
+            version
         "#,
         id,
         body.first_name,
-        body.first_name_lat,
+        first_name_lat,
         body.last_name,
-        body.last_name_lat,
+        last_name_lat,
         body.patronymic_name,
-        body.patronymic_name_lat,
+        patronymic_name_lat,
         body.sex as Sex,
         body.birth_date,
         body.tax_number,
@@ -78,8 +117,10 @@ pub async fn update_person(
         body.email,
         body.status as PersonStatus
     )
-    .fetch_optional(&pool)
+    .fetch_one(&mut *tx)
     .await?;
 
-    person.map(Json).ok_or(AppError::not_found())
+    tx.commit().await?;
+
+    Ok((version::etag(person.version), Json(person)))
 }
@@ -2,20 +2,26 @@ use axum::Json;
 use axum::extract::State;
 use axum::http::StatusCode;
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
+use utoipa::ToSchema;
 
 use crate::{
     error::AppResult,
     shared::{
         car::{model::CarRef, resolver::resolve_car},
+        existing::{ExistingConflict, OnExisting},
         person::{model::PersonRef, resolver::resolve_person},
-        policy::model::{CarInsurancePeriodUnit, OsagoZone, PolicyStatus, PolicyType},
+        policy::{
+            model::{CarInsurancePeriodUnit, OsagoZone, PolicyStatus, PolicyType},
+            resolver::{ensure_agents, ensure_insurer},
+        },
     },
+    webhook::{self, EventType},
 };
 
 // === Policy Type Specific Data ===
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct GreenCardData {
     pub territory: String,
     pub period_in_units: i32,
@@ -24,7 +30,7 @@ pub struct GreenCardData {
     pub car: CarRef,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct MedassistanceData {
     pub territory: String,
     pub period_days: i32,
@@ -34,7 +40,7 @@ pub struct MedassistanceData {
     pub members: Vec<PersonRef>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct OsagoData {
     pub period_in_units: i32,
     pub period_unit: CarInsurancePeriodUnit,
@@ -44,7 +50,7 @@ pub struct OsagoData {
     pub car: CarRef,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[serde(tag = "policy_type")]
 pub enum PolicyData {
     GreenCard(GreenCardData),
@@ -54,26 +60,31 @@ pub enum PolicyData {
 
 // === Create Policy Request ===
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct CreatePolicyRequest {
     pub holder: PersonRef,
+    pub insurer_id: i32,
     pub series: String,
     pub number: String,
     pub start_date: chrono::NaiveDate,
     pub end_date: Option<chrono::NaiveDate>,
     pub status: PolicyStatus,
     pub agent_ids: Vec<i32>,
+    /// Applies to holders, members and cars submitted as new
+    #[serde(default)]
+    pub on_existing: OnExisting,
     #[serde(flatten)]
     pub data: PolicyData,
 }
 
 // === Response ===
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CreatePolicyResponse {
     pub id: i32,
     pub policy_type: PolicyType,
     pub holder_id: i32,
+    pub insurer_id: i32,
     pub series: String,
     pub number: String,
     pub start_date: chrono::NaiveDate,
@@ -83,13 +94,35 @@ pub struct CreatePolicyResponse {
 
 // === Endpoint ===
 
+#[utoipa::path(
+    post,
+    path = "/policies",
+    tag = "policies",
+    responses(
+        (status = 201, body = CreatePolicyResponse),
+        (status = 404, description = "A person, car, insurer or agent referenced by id does not exist"),
+        (status = 409, body = ExistingConflict, description = "A holder, member or car submitted as new already exists, or one updated with the policy changed since it was read (`VersionConflict`)")
+    )
+)]
 pub async fn create_policy(
     State(pool): State<PgPool>,
     Json(body): Json<CreatePolicyRequest>,
 ) -> AppResult<(StatusCode, Json<CreatePolicyResponse>)> {
     let mut tx = pool.begin().await?;
+    let policy = insert_policy(&mut tx, body).await?;
+    tx.commit().await?;
 
-    let holder_id = resolve_person(&mut tx, body.holder).await?;
+    Ok((StatusCode::CREATED, Json(policy)))
+}
+
+/// Inserts the policy with its type specific row, holder, car, members and
+/// agent links inside the caller's transaction.
+pub async fn insert_policy(
+    tx: &mut Transaction<'_, Postgres>,
+    body: CreatePolicyRequest,
+) -> AppResult<CreatePolicyResponse> {
+    ensure_insurer(&mut **tx, body.insurer_id).await?;
+    let holder_id = resolve_person(tx, body.holder, body.on_existing).await?;
 
     let policy_type = match &body.data {
         PolicyData::GreenCard(_) => PolicyType::GreenCard,
@@ -100,12 +133,13 @@ pub async fn create_policy(
     let policy = sqlx::query_as!(
         CreatePolicyResponse,
         r#"
-        insert into policy (type, holder_id, series, number, start_date, end_date, status)
-        values ($1, $2, $3, $4, $5, $6, $7)
+        insert into policy (type, holder_id, insurer_id, series, number, start_date, end_date, status)
+        values ($1, $2, $3, $4, $5, $6, $7, $8)
         returning
             id,
             type as "policy_type: PolicyType",
             holder_id,
+            insurer_id,
             series,
             number,
             start_date,
@@ -114,18 +148,19 @@ pub async fn create_policy(
         "#,
         policy_type as PolicyType,
         holder_id,
+        body.insurer_id,
         body.series,
         body.number,
         body.start_date,
         body.end_date,
         body.status as PolicyStatus
     )
-    .fetch_one(&mut *tx)
+    .fetch_one(&mut **tx)
     .await?;
 
     match body.data {
         PolicyData::GreenCard(data) => {
-            let car_id = resolve_car(&mut tx, data.car).await?;
+            let car_id = resolve_car(tx, data.car, body.on_existing).await?;
 
             sqlx::query!(
                 r#"
@@ -139,7 +174,7 @@ pub async fn create_policy(
                 data.premium,
                 car_id
             )
-            .execute(&mut *tx)
+            .execute(&mut **tx)
             .await?;
         }
         PolicyData::Medassistance(data) => {
@@ -155,11 +190,11 @@ pub async fn create_policy(
                 data.payout,
                 data.program
             )
-            .execute(&mut *tx)
+            .execute(&mut **tx)
             .await?;
 
             for member in data.members {
-                let member_id = resolve_person(&mut tx, member).await?;
+                let member_id = resolve_person(tx, member, body.on_existing).await?;
 
                 sqlx::query!(
                     r#"
@@ -169,12 +204,12 @@ pub async fn create_policy(
                     policy.id,
                     member_id
                 )
-                .execute(&mut *tx)
+                .execute(&mut **tx)
                 .await?;
             }
         }
         PolicyData::Osago(data) => {
-            let car_id = resolve_car(&mut tx, data.car).await?;
+            let car_id = resolve_car(tx, data.car, body.on_existing).await?;
 
             sqlx::query!(
                 r#"
@@ -189,12 +224,13 @@ pub async fn create_policy(
                 data.exempt,
                 data.premium
             )
-            .execute(&mut *tx)
+            .execute(&mut **tx)
             .await?;
         }
     }
 
     // Insert agent_policy links
+    ensure_agents(&mut **tx, &body.agent_ids).await?;
     for agent_id in body.agent_ids {
         sqlx::query!(
             r#"
@@ -204,11 +240,11 @@ pub async fn create_policy(
             agent_id,
             policy.id
         )
-        .execute(&mut *tx)
+        .execute(&mut **tx)
         .await?;
     }
 
-    tx.commit().await?;
+    webhook::publish(tx, EventType::PolicyCreated, &policy).await?;
 
-    Ok((StatusCode::CREATED, Json(policy)))
+    Ok(policy)
 }
@@ -0,0 +1,39 @@
+use std::io;
+
+use axum::body::Bytes;
+use futures_util::TryStreamExt;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+
+use super::{CHUNK_SIZE, HEADERS, fetch_rows};
+use crate::endpoints::policy::get::PolicyFilters;
+
+/// UTF-8 byte order mark, so Excel opens Cyrillic text correctly.
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+pub async fn write(
+    pool: &PgPool,
+    filters: &PolicyFilters,
+    tx: &mpsc::Sender<io::Result<Bytes>>,
+) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(BOM.to_vec());
+    writer.write_record(HEADERS)?;
+
+    let mut rows = fetch_rows(pool, filters);
+    while let Some(row) = rows.try_next().await? {
+        writer.write_record(row.into_cells().iter().map(|cell| cell.to_text()))?;
+
+        if writer.get_ref().len() >= CHUNK_SIZE {
+            let chunk = writer.into_inner()?;
+            if tx.send(Ok(chunk.into())).await.is_err() {
+                // Client went away
+                return Ok(());
+            }
+            writer = csv::Writer::from_writer(Vec::with_capacity(CHUNK_SIZE));
+        }
+    }
+
+    let chunk = writer.into_inner()?;
+    let _ = tx.send(Ok(chunk.into())).await;
+    Ok(())
+}
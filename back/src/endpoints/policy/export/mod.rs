@@ -0,0 +1,285 @@
+mod csv_export;
+mod xlsx_export;
+
+use std::io;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use chrono::NaiveDate;
+use futures_util::stream::{self, BoxStream};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use utoipa::{IntoParams, ToSchema};
+
+use super::get::{PolicyFilters, PolicyQuery};
+use crate::error::AppResult;
+use crate::shared::policy::labels::{period_label, status_label, type_label, zone_label};
+use crate::shared::policy::model::{CarInsurancePeriodUnit, OsagoZone, PolicyStatus, PolicyType};
+use crate::tenant;
+
+/// Bytes buffered before a chunk is handed to the response body.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+const HEADERS: [&str; 21] = [
+    "ID",
+    "Тип",
+    "Серія",
+    "Номер",
+    "Статус",
+    "Початок",
+    "Закінчення",
+    "Страхувальник",
+    "ІПН",
+    "Телефон",
+    "Страховик",
+    "Премія",
+    "Страхова сума",
+    "Програма",
+    "Територія",
+    "Зона",
+    "Строк",
+    "Транспортний засіб",
+    "Реєстраційний номер",
+    "Номер кузова",
+    "Агенти",
+];
+
+#[derive(Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct ExportQuery {
+    pub format: ExportFormat,
+}
+
+pub struct PolicyExportRow {
+    pub id: i32,
+    pub policy_type: PolicyType,
+    pub series: String,
+    pub number: String,
+    pub status: PolicyStatus,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub holder_name: String,
+    pub holder_tax_number: String,
+    pub holder_phone: String,
+    pub insurer_name: String,
+    pub premium: Option<i32>,
+    pub payout: Option<i32>,
+    pub program: Option<String>,
+    pub territory: Option<String>,
+    pub zone: Option<OsagoZone>,
+    pub period_in_units: Option<i32>,
+    pub period_unit: Option<CarInsurancePeriodUnit>,
+    pub period_days: Option<i32>,
+    pub car_model: Option<String>,
+    pub car_plate: Option<String>,
+    pub car_chassis: Option<String>,
+    pub agent_names: Option<String>,
+}
+
+pub enum Cell {
+    Text(String),
+    Number(i32),
+    Date(NaiveDate),
+    Empty,
+}
+
+impl Cell {
+    fn text(value: Option<String>) -> Self {
+        value.map(Cell::Text).unwrap_or(Cell::Empty)
+    }
+
+    fn number(value: Option<i32>) -> Self {
+        value.map(Cell::Number).unwrap_or(Cell::Empty)
+    }
+
+    pub fn to_text(&self) -> String {
+        match self {
+            Cell::Text(s) => s.clone(),
+            Cell::Number(n) => n.to_string(),
+            Cell::Date(d) => d.format("%d.%m.%Y").to_string(),
+            Cell::Empty => String::new(),
+        }
+    }
+}
+
+impl PolicyExportRow {
+    /// Cells in the same order as `HEADERS`.
+    pub fn into_cells(self) -> [Cell; 21] {
+        let period = match (self.period_in_units, self.period_unit, self.period_days) {
+            (Some(units), Some(unit), _) => Some(period_label(units, unit)),
+            (_, _, Some(days)) => Some(format!("{} дн.", days)),
+            _ => None,
+        };
+
+        [
+            Cell::Number(self.id),
+            Cell::Text(type_label(self.policy_type).to_owned()),
+            Cell::Text(self.series),
+            Cell::Text(self.number),
+            Cell::Text(status_label(self.status).to_owned()),
+            Cell::Date(self.start_date),
+            self.end_date.map(Cell::Date).unwrap_or(Cell::Empty),
+            Cell::Text(self.holder_name),
+            Cell::Text(self.holder_tax_number),
+            Cell::Text(self.holder_phone),
+            Cell::Text(self.insurer_name),
+            Cell::number(self.premium),
+            Cell::number(self.payout),
+            Cell::text(self.program),
+            Cell::text(self.territory),
+            Cell::text(self.zone.map(|z| zone_label(z).to_owned())),
+            Cell::text(period),
+            Cell::text(self.car_model),
+            Cell::text(self.car_plate),
+            Cell::text(self.car_chassis),
+            Cell::text(self.agent_names),
+        ]
+    }
+}
+
+fn fetch_rows<'a>(
+    pool: &'a PgPool,
+    filters: &'a PolicyFilters,
+) -> BoxStream<'a, sqlx::Result<PolicyExportRow>> {
+    sqlx::query_as!(
+        PolicyExportRow,
+        r#"
+        select
+            policy.id,
+            type as "policy_type: PolicyType",
+            series,
+            number,
+            policy.status as "status: PolicyStatus",
+            start_date,
+            end_date,
+            concat_ws(' ', person.last_name, person.first_name, person.patronymic_name) as "holder_name!",
+            person.tax_number as holder_tax_number,
+            person.phone as holder_phone,
+            insurer.name as insurer_name,
+            coalesce(green_card_policy.premium, medassistance_policy.premium, osago_policy.premium) as premium,
+            medassistance_policy.payout as "payout?",
+            medassistance_policy.program as "program?",
+            coalesce(green_card_policy.territory, medassistance_policy.territory) as territory,
+            osago_policy.zone as "zone?: OsagoZone",
+            coalesce(green_card_policy.period_in_units, osago_policy.period_in_units) as period_in_units,
+            coalesce(green_card_policy.period_unit, osago_policy.period_unit) as "period_unit: CarInsurancePeriodUnit",
+            medassistance_policy.period_days as "period_days?",
+            car.make || ' ' || car.model as car_model,
+            car.plate as "car_plate?",
+            car.chassis as "car_chassis?",
+            (
+                select string_agg(a.full_name, ', ' order by a.full_name)
+                from agent a
+                join agent_policy ap on a.id = ap.agent_id
+                where ap.policy_id = policy.id
+            ) as agent_names
+        from policy
+        join person on policy.holder_id = person.id
+        join insurer on policy.insurer_id = insurer.id
+        left join green_card_policy on policy.id = green_card_policy.id
+        left join medassistance_policy on policy.id = medassistance_policy.id
+        left join osago_policy on policy.id = osago_policy.id
+        left join car on car.id = coalesce(green_card_policy.car_id, osago_policy.car_id)
+        where
+            policy.tenant_id = current_tenant()
+            and ($1::text is null or lower(series || number) like $1)
+            and ($2::text is null or lower(person.first_name || ' ' || person.last_name) like $2)
+            and ($3::text is null or lower(coalesce(car.make || ' ' || car.model || ' ' || coalesce(car.plate, ''), '')) like $3)
+            and ($4::date is null or start_date >= $4)
+            and ($5::date is null or start_date <= $5)
+            and ($6::date is null or end_date >= $6)
+            and ($7::date is null or end_date <= $7)
+            and (cardinality($8::policy_type[]) = 0 or type = any($8::policy_type[]))
+            and (cardinality($9::policy_status[]) = 0 or policy.status = any($9::policy_status[]))
+            and (cardinality($10::int4[]) = 0 or exists (
+                select 1 from agent_policy ap2 where ap2.policy_id = policy.id and ap2.agent_id = any($10::int4[])
+            ))
+            and (cardinality($11::int4[]) = 0 or policy.insurer_id = any($11::int4[]))
+        order by start_date desc, policy.id desc
+        "#,
+        filters.number_pattern.as_deref(),
+        filters.holder_pattern.as_deref(),
+        filters.car_pattern.as_deref(),
+        filters.start_date_from,
+        filters.start_date_to,
+        filters.end_date_from,
+        filters.end_date_to,
+        &filters.policy_types as &[PolicyType],
+        &filters.statuses as &[PolicyStatus],
+        &filters.agent_ids,
+        &filters.insurer_ids,
+    )
+    .fetch(pool)
+}
+
+/// Streams every policy matching the `PolicyQuery` filters as CSV or XLSX.
+/// Rows are written as they arrive from the database, so memory use does not
+/// grow with the size of the export.
+#[utoipa::path(
+    get,
+    path = "/policies/export",
+    tag = "policies",
+    params(PolicyQuery, ExportQuery),
+    responses(
+        (status = 200, description = "Policies as a file download", content(
+            ("text/csv"),
+            ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+        ))
+    )
+)]
+pub async fn export_policies(
+    State(pool): State<PgPool>,
+    Query(query): Query<PolicyQuery>,
+    Query(export): Query<ExportQuery>,
+) -> AppResult<impl IntoResponse> {
+    let filters = query.filters();
+    let (tx, rx) = mpsc::channel::<io::Result<Bytes>>(16);
+
+    tokio::spawn(tenant::propagate(async move {
+        let result = match export.format {
+            ExportFormat::Csv => csv_export::write(&pool, &filters, &tx).await,
+            ExportFormat::Xlsx => xlsx_export::write(&pool, &filters, &tx).await,
+        };
+        if let Err(err) = result {
+            tracing::error!("Policy export failed: {:?}", err);
+            let _ = tx.send(Err(io::Error::other("export failed"))).await;
+        }
+    }));
+
+    let body = Body::from_stream(stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }));
+
+    let (content_type, extension) = match export.format {
+        ExportFormat::Csv => ("text/csv; charset=utf-8", "csv"),
+        ExportFormat::Xlsx => (
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "xlsx",
+        ),
+    };
+    let file_name = format!(
+        "policies-{}.{}",
+        chrono::Local::now().format("%Y-%m-%d"),
+        extension
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        body,
+    ))
+}
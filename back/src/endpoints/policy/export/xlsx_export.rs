@@ -0,0 +1,91 @@
+use std::io::{self, Write};
+
+use axum::body::Bytes;
+use futures_util::TryStreamExt;
+use rust_xlsxwriter::{Format, Workbook};
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+
+use super::{CHUNK_SIZE, Cell, HEADERS, fetch_rows};
+use crate::endpoints::policy::get::PolicyFilters;
+
+/// Forwards the workbook bytes to the response body as they are produced.
+struct ChannelWriter {
+    tx: mpsc::Sender<io::Result<Bytes>>,
+    buffer: Vec<u8>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::take(&mut self.buffer);
+        self.tx
+            .blocking_send(Ok(chunk.into()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected"))
+    }
+}
+
+pub async fn write(
+    pool: &PgPool,
+    filters: &PolicyFilters,
+    tx: &mpsc::Sender<io::Result<Bytes>>,
+) -> anyhow::Result<()> {
+    let mut workbook = Workbook::new();
+    // Constant memory mode spills rows to a temp file instead of keeping them
+    let mut worksheet = workbook.new_worksheet_with_constant_memory();
+    worksheet.set_name("Поліси")?;
+
+    let header_format = Format::new().set_bold();
+    let date_format = Format::new().set_num_format("dd.mm.yyyy");
+
+    for (col, header) in HEADERS.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)?;
+    }
+
+    let mut rows = fetch_rows(pool, filters);
+    let mut row_index = 1;
+    while let Some(row) = rows.try_next().await? {
+        for (col, cell) in row.into_cells().into_iter().enumerate() {
+            let col = col as u16;
+            match cell {
+                Cell::Text(s) => {
+                    worksheet.write_string(row_index, col, s)?;
+                }
+                Cell::Number(n) => {
+                    worksheet.write_number(row_index, col, n)?;
+                }
+                Cell::Date(d) => {
+                    worksheet.write_date_with_format(row_index, col, d, &date_format)?;
+                }
+                Cell::Empty => {}
+            }
+        }
+        row_index += 1;
+    }
+
+    workbook.push_worksheet(worksheet);
+
+    let tx = tx.clone();
+    tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+        let mut writer = ChannelWriter {
+            tx,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+        };
+        workbook.save_to_writer(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    })
+    .await??;
+
+    Ok(())
+}
@@ -3,11 +3,27 @@ use axum::extract::{Query, State};
 use chrono::NaiveDate;
 use serde::Deserialize;
 use sqlx::PgPool;
+use utoipa::IntoParams;
 
 use crate::error::AppResult;
+use crate::shared::page::{Page, PageQuery};
 use crate::shared::policy::model::{PolicyShort, PolicyStatus, PolicyType};
 
-#[derive(Deserialize)]
+const SORT_COLUMNS: &[&str] = &[
+    "policy_type",
+    "holder_name",
+    "insurer_name",
+    "series",
+    "number",
+    "start_date",
+    "end_date",
+    "status",
+    "car_model",
+    "car_plate",
+    "agent_names",
+];
+
+#[derive(Deserialize, IntoParams)]
 pub struct PolicyQuery {
     pub number: Option<String>,
     pub holder: Option<String>,
@@ -19,6 +35,7 @@ pub struct PolicyQuery {
     pub policy_types: Option<String>,
     pub statuses: Option<String>,
     pub agent_ids: Option<String>,
+    pub insurer_ids: Option<String>,
 }
 
 fn parse_enum_list<T>(s: Option<&str>) -> Vec<T>
@@ -36,39 +53,73 @@ where
         .unwrap_or_default()
 }
 
+fn parse_id_list(s: Option<&str>) -> Vec<i32> {
+    s.filter(|s| !s.is_empty())
+        .map(|s| s.split(',').filter_map(|t| t.parse().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// `PolicyQuery` turned into values that bind directly into the filter clause
+/// shared by the list and export queries.
+pub struct PolicyFilters {
+    pub number_pattern: Option<String>,
+    pub holder_pattern: Option<String>,
+    pub car_pattern: Option<String>,
+    pub start_date_from: Option<NaiveDate>,
+    pub start_date_to: Option<NaiveDate>,
+    pub end_date_from: Option<NaiveDate>,
+    pub end_date_to: Option<NaiveDate>,
+    pub policy_types: Vec<PolicyType>,
+    pub statuses: Vec<PolicyStatus>,
+    pub agent_ids: Vec<i32>,
+    pub insurer_ids: Vec<i32>,
+}
+
+impl PolicyQuery {
+    pub fn filters(&self) -> PolicyFilters {
+        let pattern = |s: &Option<String>| s.as_deref().map(|s| format!("%{}%", s.to_lowercase()));
+
+        PolicyFilters {
+            number_pattern: pattern(&self.number),
+            holder_pattern: pattern(&self.holder),
+            car_pattern: pattern(&self.car),
+            start_date_from: self.start_date_from,
+            start_date_to: self.start_date_to,
+            end_date_from: self.end_date_from,
+            end_date_to: self.end_date_to,
+            policy_types: parse_enum_list(self.policy_types.as_deref()),
+            statuses: parse_enum_list(self.statuses.as_deref()),
+            agent_ids: parse_id_list(self.agent_ids.as_deref()),
+            insurer_ids: parse_id_list(self.insurer_ids.as_deref()),
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/policies",
+    tag = "policies",
+    params(PolicyQuery, PageQuery),
+    responses((status = 200, body = Page<PolicyShort>))
+)]
 pub async fn get_policies(
     State(pool): State<PgPool>,
     Query(query): Query<PolicyQuery>,
-) -> AppResult<Json<Vec<PolicyShort>>> {
-    let number_pattern = query
-        .number
-        .as_deref()
-        .map(|s| format!("%{}%", s.to_lowercase()));
-    let holder_pattern = query
-        .holder
-        .as_deref()
-        .map(|s| format!("%{}%", s.to_lowercase()));
-    let car_pattern = query
-        .car
-        .as_deref()
-        .map(|s| format!("%{}%", s.to_lowercase()));
+    Query(page): Query<PageQuery>,
+) -> AppResult<Json<Page<PolicyShort>>> {
+    let filters = query.filters();
+    let sort = page.sort(SORT_COLUMNS)?;
+    let limit = page.limit(150);
+    let offset = page.offset();
 
-    let policy_types: Vec<PolicyType> = parse_enum_list(query.policy_types.as_deref());
-    let statuses: Vec<PolicyStatus> = parse_enum_list(query.statuses.as_deref());
-    let agent_ids: Vec<i32> = query
-        .agent_ids
-        .as_deref()
-        .filter(|s| !s.is_empty())
-        .map(|s| s.split(',').filter_map(|t| t.parse().ok()).collect())
-        .unwrap_or_default();
-
-    let policies = sqlx::query_as!(
+    let items = sqlx::query_as!(
         PolicyShort,
         r#"
         select
             policy.id,
             type as "policy_type: PolicyType",
             person.first_name || ' ' || person.last_name as "holder_name!",
+            insurer.name as insurer_name,
             series,
             number,
             start_date,
@@ -76,19 +127,22 @@ pub async fn get_policies(
             policy.status as "status: PolicyStatus",
             car.make || ' ' || car.model as car_model,
             car.plate as "car_plate?",
-            (
-                select string_agg(a.full_name, ', ' order by a.full_name)
-                from agent a
-                join agent_policy ap on a.id = ap.agent_id
-                where ap.policy_id = policy.id
-            ) as agent_names
+            agents.agent_names
         from policy
         join person on policy.holder_id = person.id
+        join insurer on policy.insurer_id = insurer.id
         left join green_card_policy on policy.id = green_card_policy.id
         left join osago_policy on policy.id = osago_policy.id
         left join car on car.id = coalesce(green_card_policy.car_id, osago_policy.car_id)
+        left join lateral (
+            select string_agg(a.full_name, ', ' order by a.full_name) as agent_names
+            from agent a
+            join agent_policy ap on a.id = ap.agent_id
+            where ap.policy_id = policy.id
+        ) agents on true
         where
-            ($1::text is null or lower(series || number) like $1)
+            policy.tenant_id = current_tenant()
+            and ($1::text is null or lower(series || number) like $1)
             and ($2::text is null or lower(person.first_name || ' ' || person.last_name) like $2)
             and ($3::text is null or lower(coalesce(car.make || ' ' || car.model || ' ' || coalesce(car.plate, ''), '')) like $3)
             and ($4::date is null or start_date >= $4)
@@ -100,28 +154,105 @@ pub async fn get_policies(
             and (cardinality($10::int4[]) = 0 or exists (
                 select 1 from agent_policy ap2 where ap2.policy_id = policy.id and ap2.agent_id = any($10::int4[])
             ))
+            and (cardinality($11::int4[]) = 0 or policy.insurer_id = any($11::int4[]))
         order by
-            case policy.status
-                when 'active' then 1
-                when 'prolonged' then 2
-                else 3
+            case when $12::text = 'policy_type' and not $13 then type end asc,
+            case when $12::text = 'policy_type' and $13 then type end desc,
+            case when $12::text = 'holder_name' and not $13 then person.first_name || ' ' || person.last_name end asc,
+            case when $12::text = 'holder_name' and $13 then person.first_name || ' ' || person.last_name end desc,
+            case when $12::text = 'insurer_name' and not $13 then insurer.name end asc,
+            case when $12::text = 'insurer_name' and $13 then insurer.name end desc,
+            case when $12::text = 'series' and not $13 then series end asc,
+            case when $12::text = 'series' and $13 then series end desc,
+            case when $12::text = 'number' and not $13 then series || number end asc,
+            case when $12::text = 'number' and $13 then series || number end desc,
+            case when $12::text = 'start_date' and not $13 then start_date end asc,
+            case when $12::text = 'start_date' and $13 then start_date end desc,
+            case when $12::text = 'end_date' and not $13 then end_date end asc,
+            case when $12::text = 'end_date' and $13 then end_date end desc,
+            case when $12::text = 'status' and not $13 then policy.status end asc,
+            case when $12::text = 'status' and $13 then policy.status end desc,
+            case when $12::text = 'car_model' and not $13 then car.make || ' ' || car.model end asc,
+            case when $12::text = 'car_model' and $13 then car.make || ' ' || car.model end desc,
+            case when $12::text = 'car_plate' and not $13 then car.plate end asc,
+            case when $12::text = 'car_plate' and $13 then car.plate end desc,
+            case when $12::text = 'agent_names' and not $13 then agents.agent_names end asc,
+            case when $12::text = 'agent_names' and $13 then agents.agent_names end desc,
+            -- Default ordering: active first, then most recent
+            case when $12::text is null then
+                case policy.status
+                    when 'active' then 1
+                    when 'prolonged' then 2
+                    else 3
+                end
             end,
-            start_date desc
-        limit 150
+            start_date desc,
+            policy.id desc
+        limit $14
+        offset $15
         "#,
-        number_pattern as Option<String>,
-        holder_pattern as Option<String>,
-        car_pattern as Option<String>,
-        query.start_date_from as Option<NaiveDate>,
-        query.start_date_to as Option<NaiveDate>,
-        query.end_date_from as Option<NaiveDate>,
-        query.end_date_to as Option<NaiveDate>,
-        &policy_types as &[PolicyType],
-        &statuses as &[PolicyStatus],
-        &agent_ids as &[i32],
+        filters.number_pattern.as_deref(),
+        filters.holder_pattern.as_deref(),
+        filters.car_pattern.as_deref(),
+        filters.start_date_from,
+        filters.start_date_to,
+        filters.end_date_from,
+        filters.end_date_to,
+        &filters.policy_types as &[PolicyType],
+        &filters.statuses as &[PolicyStatus],
+        &filters.agent_ids,
+        &filters.insurer_ids,
+        sort as Option<String>,
+        page.descending(),
+        limit,
+        offset,
     )
     .fetch_all(&pool)
     .await?;
 
-    Ok(Json(policies))
+    let total = sqlx::query_scalar!(
+        r#"
+        select count(*) as "count!"
+        from policy
+        join person on policy.holder_id = person.id
+        left join green_card_policy on policy.id = green_card_policy.id
+        left join osago_policy on policy.id = osago_policy.id
+        left join car on car.id = coalesce(green_card_policy.car_id, osago_policy.car_id)
+        where
+            policy.tenant_id = current_tenant()
+            and ($1::text is null or lower(series || number) like $1)
+            and ($2::text is null or lower(person.first_name || ' ' || person.last_name) like $2)
+            and ($3::text is null or lower(coalesce(car.make || ' ' || car.model || ' ' || coalesce(car.plate, ''), '')) like $3)
+            and ($4::date is null or start_date >= $4)
+            and ($5::date is null or start_date <= $5)
+            and ($6::date is null or end_date >= $6)
+            and ($7::date is null or end_date <= $7)
+            and (cardinality($8::policy_type[]) = 0 or type = any($8::policy_type[]))
+            and (cardinality($9::policy_status[]) = 0 or policy.status = any($9::policy_status[]))
+            and (cardinality($10::int4[]) = 0 or exists (
+                select 1 from agent_policy ap2 where ap2.policy_id = policy.id and ap2.agent_id = any($10::int4[])
+            ))
+            and (cardinality($11::int4[]) = 0 or policy.insurer_id = any($11::int4[]))
+        "#,
+        filters.number_pattern.as_deref(),
+        filters.holder_pattern.as_deref(),
+        filters.car_pattern.as_deref(),
+        filters.start_date_from,
+        filters.start_date_to,
+        filters.end_date_from,
+        filters.end_date_to,
+        &filters.policy_types as &[PolicyType],
+        &filters.statuses as &[PolicyStatus],
+        &filters.agent_ids,
+        &filters.insurer_ids,
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(Page {
+        items,
+        total,
+        limit,
+        offset,
+    }))
 }
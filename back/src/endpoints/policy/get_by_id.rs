@@ -1,18 +1,26 @@
 use axum::Json;
 use axum::extract::{Path, State};
+use axum::response::IntoResponse;
 use serde::Serialize;
 use sqlx::PgPool;
+use utoipa::ToSchema;
 
 use crate::error::{AppError, AppResult};
 use crate::shared::{
+    attachment::{
+        self,
+        model::{Attachment, AttachmentOwner},
+    },
     car::model::CarFull,
+    insurer::model::Insurer,
     person::model::{PersonFull, PersonStatus},
     policy::model::{CarInsurancePeriodUnit, OsagoZone, PolicyStatus, PolicyType},
+    version,
 };
 
 // === Response Models ===
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct GreenCardDetails {
     pub territory: String,
     pub period_in_units: i32,
@@ -21,7 +29,7 @@ pub struct GreenCardDetails {
     pub car: CarFull,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct MedassistanceDetails {
     pub territory: String,
     pub period_days: i32,
@@ -31,7 +39,7 @@ pub struct MedassistanceDetails {
     pub members: Vec<PersonFull>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct OsagoDetails {
     pub period_in_units: i32,
     pub period_unit: CarInsurancePeriodUnit,
@@ -41,7 +49,7 @@ pub struct OsagoDetails {
     pub car: CarFull,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(tag = "policy_type")]
 pub enum PolicyDetails {
     GreenCard(GreenCardDetails),
@@ -49,24 +57,28 @@ pub enum PolicyDetails {
     Osago(OsagoDetails),
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct Agent {
     pub id: i32,
     pub full_name: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct PolicyFull {
     pub id: i32,
     pub holder: PersonFull,
+    pub insurer: Insurer,
     pub series: String,
     pub number: String,
     pub start_date: chrono::NaiveDate,
     pub end_date: Option<chrono::NaiveDate>,
     pub status: PolicyStatus,
     pub agents: Vec<Agent>,
+    pub attachments: Vec<Attachment>,
     #[serde(flatten)]
     pub details: PolicyDetails,
+    /// Send back in `If-Match` when updating, see `shared::version`
+    pub version: i32,
 }
 
 // === Helper Structs ===
@@ -75,11 +87,13 @@ struct PolicyBase {
     id: i32,
     policy_type: PolicyType,
     holder_id: i32,
+    insurer_id: i32,
     series: String,
     number: String,
     start_date: chrono::NaiveDate,
     end_date: Option<chrono::NaiveDate>,
     status: PolicyStatus,
+    version: i32,
 }
 
 struct GreenCardRow {
@@ -109,10 +123,25 @@ struct OsagoRow {
 
 // === Endpoint ===
 
+#[utoipa::path(
+    get,
+    path = "/policies/{id}",
+    tag = "policies",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 200, body = PolicyFull, headers(("ETag" = String))),
+        (status = 404, description = "No such policy")
+    )
+)]
 pub async fn get_policy_by_id(
     State(pool): State<PgPool>,
     Path(id): Path<i32>,
-) -> AppResult<Json<PolicyFull>> {
+) -> AppResult<impl IntoResponse> {
+    let policy = load_policy(&pool, id).await?;
+    Ok((version::etag(policy.version), Json(policy)))
+}
+
+pub async fn load_policy(pool: &PgPool, id: i32) -> AppResult<PolicyFull> {
     let policy = sqlx::query_as!(
         PolicyBase,
         r#"
@@ -120,17 +149,19 @@ pub async fn get_policy_by_id(
             id,
             type as "policy_type: PolicyType",
             holder_id,
+            insurer_id,
             series,
             number,
             start_date,
             end_date,
-            status as "status: PolicyStatus"
+            status as "status: PolicyStatus",
+            version
         from policy
-        where id = $1
+        where id = $1 and tenant_id = current_tenant()
         "#,
         id
     )
-    .fetch_optional(&pool)
+    .fetch_optional(pool)
     .await?
     .ok_or(AppError::not_found())?;
 
@@ -151,13 +182,26 @@ pub async fn get_policy_by_id(
             phone,
             phone2,
             email,
-            status as "status: PersonStatus"
+            status as "status: PersonStatus",
+            version
         from person
-        where id = $1
+        where id = $1 and tenant_id = current_tenant()
         "#,
         policy.holder_id
     )
-    .fetch_one(&pool)
+    .fetch_one(pool)
+    .await?;
+
+    let insurer = sqlx::query_as!(
+        Insurer,
+        r#"
+        select id, name
+        from insurer
+        where id = $1 and tenant_id = current_tenant()
+        "#,
+        policy.insurer_id
+    )
+    .fetch_one(pool)
     .await?;
 
     let details = match policy.policy_type {
@@ -176,7 +220,7 @@ pub async fn get_policy_by_id(
                 "#,
                 id
             )
-            .fetch_one(&pool)
+            .fetch_one(pool)
             .await?;
 
             let car = sqlx::query_as!(
@@ -194,13 +238,14 @@ pub async fn get_policy_by_id(
                     mileage_km,
                     unladen_weight,
                     laden_weight,
-                    seats
+                    seats,
+                    version
                 from car
-                where id = $1
+                where id = $1 and tenant_id = current_tenant()
                 "#,
                 row.car_id
             )
-            .fetch_one(&pool)
+            .fetch_one(pool)
             .await?;
 
             PolicyDetails::GreenCard(GreenCardDetails {
@@ -226,7 +271,7 @@ pub async fn get_policy_by_id(
                 "#,
                 id
             )
-            .fetch_one(&pool)
+            .fetch_one(pool)
             .await?;
 
             let members = sqlx::query_as!(
@@ -246,14 +291,15 @@ pub async fn get_policy_by_id(
                     p.phone,
                     p.phone2,
                     p.email,
-                    p.status as "status: PersonStatus"
+                    p.status as "status: PersonStatus",
+                    p.version
                 from person p
                 join medassistance_policy_member m on p.id = m.member_id
-                where m.medassistance_policy_id = $1
+                where m.medassistance_policy_id = $1 and p.tenant_id = current_tenant()
                 "#,
                 id
             )
-            .fetch_all(&pool)
+            .fetch_all(pool)
             .await?;
 
             PolicyDetails::Medassistance(MedassistanceDetails {
@@ -281,7 +327,7 @@ pub async fn get_policy_by_id(
                 "#,
                 id
             )
-            .fetch_one(&pool)
+            .fetch_one(pool)
             .await?;
 
             let car = sqlx::query_as!(
@@ -299,13 +345,14 @@ pub async fn get_policy_by_id(
                     mileage_km,
                     unladen_weight,
                     laden_weight,
-                    seats
+                    seats,
+                    version
                 from car
-                where id = $1
+                where id = $1 and tenant_id = current_tenant()
                 "#,
                 row.car_id
             )
-            .fetch_one(&pool)
+            .fetch_one(pool)
             .await?;
 
             PolicyDetails::Osago(OsagoDetails {
@@ -330,18 +377,23 @@ pub async fn get_policy_by_id(
         "#,
         id
     )
-    .fetch_all(&pool)
+    .fetch_all(pool)
     .await?;
 
-    Ok(Json(PolicyFull {
+    let attachments = attachment::for_owner(pool, AttachmentOwner::Policy, id).await?;
+
+    Ok(PolicyFull {
         id: policy.id,
         holder,
+        insurer,
         series: policy.series,
         number: policy.number,
         start_date: policy.start_date,
         end_date: policy.end_date,
         status: policy.status,
         agents,
+        attachments,
         details,
-    }))
+        version: policy.version,
+    })
 }
@@ -0,0 +1,690 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::{Postgres, Transaction};
+use utoipa::ToSchema;
+
+use crate::endpoints::policy::create::{
+    CreatePolicyRequest, GreenCardData, MedassistanceData, OsagoData, PolicyData,
+};
+use crate::shared::car::model::{CarNew, CarRef};
+use crate::shared::car::resolver::{find_car, normalize_plate};
+use crate::shared::existing::OnExisting;
+use crate::shared::person::model::{PersonNew, PersonRef, PersonStatus, Sex};
+use crate::shared::person::resolver::find_person;
+use crate::shared::policy::labels::{status_label, type_label, zone_label};
+use crate::shared::policy::model::{CarInsurancePeriodUnit, OsagoZone, PolicyStatus, PolicyType};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Field {
+    PolicyType,
+    Series,
+    Number,
+    Status,
+    StartDate,
+    EndDate,
+    Insurer,
+    Agents,
+    Premium,
+    Territory,
+    Period,
+    Zone,
+    Exempt,
+    Payout,
+    Program,
+    TaxNumber,
+    LastName,
+    FirstName,
+    PatronymicName,
+    Sex,
+    BirthDate,
+    Phone,
+    Phone2,
+    Email,
+    Chassis,
+    Plate,
+    Make,
+    Model,
+    Registration,
+    Year,
+    EngineDisplacement,
+    Mileage,
+    UnladenWeight,
+    LadenWeight,
+    Seats,
+}
+
+/// Header names accepted for each field, compared case-insensitively.
+/// The first one is used in error messages.
+const HEADERS: &[(Field, &[&str])] = &[
+    (Field::PolicyType, &["Тип", "policy_type"]),
+    (Field::Series, &["Серія", "series"]),
+    (Field::Number, &["Номер", "number"]),
+    (Field::Status, &["Статус", "status"]),
+    (Field::StartDate, &["Початок", "start_date"]),
+    (Field::EndDate, &["Закінчення", "Припинення", "end_date"]),
+    (
+        Field::Insurer,
+        &["Страховик", "Страхова компанія", "СК", "insurer"],
+    ),
+    (Field::Agents, &["Агенти", "Агент", "agents"]),
+    (Field::Premium, &["Премія", "Страхова премія", "premium"]),
+    (
+        Field::Territory,
+        &["Територія", "Територія дії", "territory"],
+    ),
+    (Field::Period, &["Строк", "Срок", "period"]),
+    (Field::Zone, &["Зона", "zone"]),
+    (Field::Exempt, &["Пільга", "exempt"]),
+    (Field::Payout, &["Страхова сума", "payout"]),
+    (Field::Program, &["Програма", "program"]),
+    (Field::TaxNumber, &["ІПН", "tax_number"]),
+    (Field::LastName, &["Прізвище", "last_name"]),
+    (Field::FirstName, &["Ім'я", "first_name"]),
+    (Field::PatronymicName, &["По батькові", "patronymic_name"]),
+    (Field::Sex, &["Стать", "sex"]),
+    (Field::BirthDate, &["Дата народження", "birth_date"]),
+    (Field::Phone, &["Телефон", "phone"]),
+    (Field::Phone2, &["Телефон 2", "phone2"]),
+    (Field::Email, &["Email", "E-mail", "Ел. пошта"]),
+    (Field::Chassis, &["Номер кузова", "VIN", "chassis"]),
+    (
+        Field::Plate,
+        &[
+            "Реєстраційний номер",
+            "Номерний знак",
+            "Реєстраційний №",
+            "plate",
+        ],
+    ),
+    (Field::Make, &["Марка", "make"]),
+    (Field::Model, &["Модель", "model"]),
+    (
+        Field::Registration,
+        &["Місце реєстрації", "Пункт реєстрації", "registration"],
+    ),
+    (Field::Year, &["Рік випуску", "year"]),
+    (
+        Field::EngineDisplacement,
+        &["Об'єм двигуна", "engine_displacement_litres"],
+    ),
+    (Field::Mileage, &["Пробіг", "mileage_km"]),
+    (
+        Field::UnladenWeight,
+        &["Маса без навантаження", "unladen_weight"],
+    ),
+    (Field::LadenWeight, &["Повна маса", "laden_weight"]),
+    (Field::Seats, &["Кількість місць", "seats"]),
+];
+
+const REQUIRED: [Field; 6] = [
+    Field::PolicyType,
+    Field::Series,
+    Field::Number,
+    Field::StartDate,
+    Field::Insurer,
+    Field::TaxNumber,
+];
+
+fn field_name(field: Field) -> &'static str {
+    HEADERS
+        .iter()
+        .find(|(f, _)| *f == field)
+        .map(|(_, names)| names[0])
+        .unwrap_or_default()
+}
+
+fn normalize_header(header: &str) -> String {
+    header.trim().to_lowercase().replace(['’', 'ʼ', '`'], "'")
+}
+
+/// Positions of the recognised columns in the uploaded sheet.
+pub struct Columns(HashMap<Field, usize>);
+
+impl Columns {
+    pub fn new(headers: &[String]) -> Result<Self, String> {
+        let mut positions = HashMap::new();
+        for (i, header) in headers.iter().enumerate() {
+            let header = normalize_header(header);
+            let field = HEADERS
+                .iter()
+                .find(|(_, names)| names.iter().any(|n| normalize_header(n) == header));
+            if let Some((field, _)) = field {
+                positions.entry(*field).or_insert(i);
+            }
+        }
+
+        let missing: Vec<_> = REQUIRED
+            .iter()
+            .filter(|f| !positions.contains_key(f))
+            .map(|f| field_name(*f))
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("Missing columns: {}", missing.join(", ")));
+        }
+
+        Ok(Columns(positions))
+    }
+}
+
+/// One data row being mapped; problems are collected instead of failing fast
+/// so the preview can list all of them at once.
+pub struct Row<'a> {
+    columns: &'a Columns,
+    cells: &'a [String],
+    pub errors: Vec<String>,
+}
+
+impl<'a> Row<'a> {
+    pub fn new(columns: &'a Columns, cells: &'a [String]) -> Self {
+        Row {
+            columns,
+            cells,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, field: Field) -> Option<&'a str> {
+        let i = *self.columns.0.get(&field)?;
+        self.cells
+            .get(i)
+            .map(|s| s.as_str())
+            .filter(|s| !s.is_empty())
+    }
+
+    fn required(&mut self, field: Field) -> Option<&'a str> {
+        let value = self.get(field);
+        if value.is_none() {
+            self.errors
+                .push(format!("Column '{}' is empty", field_name(field)));
+        }
+        value
+    }
+
+    fn text(&self, field: Field) -> Option<String> {
+        self.get(field).map(str::to_owned)
+    }
+
+    fn parse_value<T>(
+        &mut self,
+        field: Field,
+        value: &str,
+        parse: fn(&str) -> Option<T>,
+    ) -> Option<T> {
+        let parsed = parse(value);
+        if parsed.is_none() {
+            self.errors.push(format!(
+                "Column '{}' has invalid value '{}'",
+                field_name(field),
+                value
+            ));
+        }
+        parsed
+    }
+
+    fn parse<T>(&mut self, field: Field, parse: fn(&str) -> Option<T>) -> Option<T> {
+        let value = self.required(field)?;
+        self.parse_value(field, value, parse)
+    }
+
+    fn optional<T>(&mut self, field: Field, parse: fn(&str) -> Option<T>) -> Option<T> {
+        let value = self.get(field)?;
+        self.parse_value(field, value, parse)
+    }
+}
+
+// === Value Parsers ===
+
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    ["%d.%m.%Y", "%Y-%m-%d", "%d/%m/%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(s, format).ok())
+}
+
+fn parse_int(s: &str) -> Option<i32> {
+    let s: String = s
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| if c == ',' { '.' } else { c })
+        .collect();
+    let value: f64 = s.parse().ok()?;
+    (value.fract() == 0.0 && value.abs() <= i32::MAX as f64).then_some(value as i32)
+}
+
+/// Matches either the Ukrainian label or the API name of an enum variant.
+fn parse_variant<T: Copy + Serialize>(
+    s: &str,
+    variants: &[T],
+    label: fn(T) -> &'static str,
+) -> Option<T> {
+    let s = s.trim().to_lowercase();
+    variants.iter().copied().find(|v| {
+        label(*v).to_lowercase() == s
+            || serde_json::to_value(v)
+                .ok()
+                .and_then(|json| json.as_str().map(|name| name.to_lowercase() == s))
+                .unwrap_or(false)
+    })
+}
+
+fn parse_policy_type(s: &str) -> Option<PolicyType> {
+    match s.trim().to_lowercase().as_str() {
+        "зк" | "green_card" => Some(PolicyType::GreenCard),
+        "осаго" | "osago" => Some(PolicyType::Osago),
+        _ => parse_variant(
+            s,
+            &[
+                PolicyType::GreenCard,
+                PolicyType::Medassistance,
+                PolicyType::Osago,
+            ],
+            type_label,
+        ),
+    }
+}
+
+fn parse_status(s: &str) -> Option<PolicyStatus> {
+    parse_variant(
+        s,
+        &[
+            PolicyStatus::Active,
+            PolicyStatus::Prolonged,
+            PolicyStatus::Rejected,
+            PolicyStatus::Stopped,
+            PolicyStatus::Postponed,
+            PolicyStatus::Cancelled,
+            PolicyStatus::Project,
+            PolicyStatus::Replaced,
+            PolicyStatus::Expired,
+        ],
+        status_label,
+    )
+}
+
+fn parse_zone(s: &str) -> Option<OsagoZone> {
+    match s.trim() {
+        "1" => Some(OsagoZone::Zone1),
+        "2" => Some(OsagoZone::Zone2),
+        "3" => Some(OsagoZone::Zone3),
+        "4" => Some(OsagoZone::Zone4),
+        "5" => Some(OsagoZone::Zone5),
+        _ => parse_variant(
+            s,
+            &[
+                OsagoZone::Zone1,
+                OsagoZone::Zone2,
+                OsagoZone::Zone3,
+                OsagoZone::Zone4,
+                OsagoZone::Zone5,
+                OsagoZone::Outside,
+            ],
+            zone_label,
+        ),
+    }
+}
+
+fn parse_sex(s: &str) -> Option<Sex> {
+    match s.trim().to_lowercase().chars().next() {
+        Some('ч' | 'м' | 'm') => Some(Sex::M),
+        Some('ж' | 'f') => Some(Sex::F),
+        _ => None,
+    }
+}
+
+/// "12 міс.", "1 рік", "15 дн."; a bare number means months.
+fn parse_period(s: &str) -> Option<(i32, CarInsurancePeriodUnit)> {
+    let s = s.trim().to_lowercase();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let units = s[..split].parse().ok()?;
+    let unit = match s[split..].trim().chars().next() {
+        None | Some('м' | 'm') => CarInsurancePeriodUnit::Month,
+        Some('д' | 'd') => CarInsurancePeriodUnit::Day,
+        Some('р' | 'y') => CarInsurancePeriodUnit::Year,
+        _ => return None,
+    };
+    Some((units, unit))
+}
+
+/// "15" or "15 дн."
+fn parse_days(s: &str) -> Option<i32> {
+    match parse_period(s)? {
+        (days, CarInsurancePeriodUnit::Day) => Some(days),
+        (days, CarInsurancePeriodUnit::Month) if s.trim().chars().all(|c| c.is_ascii_digit()) => {
+            Some(days)
+        }
+        _ => None,
+    }
+}
+
+// === Matching ===
+
+/// How a holder or car of a row was resolved.
+#[derive(Serialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Matched {
+    Existing {
+        id: i32,
+    },
+    /// Created by an earlier row of the same file
+    Imported {
+        row: usize,
+    },
+    New,
+}
+
+/// Holders and cars created by earlier rows, keyed by tax number and by
+/// chassis and plate, so later rows report them instead of a rolled back id.
+#[derive(Default)]
+pub struct Imported {
+    people: HashMap<String, usize>,
+    cars: HashMap<String, usize>,
+}
+
+fn car_key(matched_on: &str, value: &str) -> String {
+    format!("{}:{}", matched_on, normalize_plate(value))
+}
+
+impl Imported {
+    pub fn record(&mut self, row: usize, mapped: &MappedRow) {
+        if let PersonRef::New(person) = &mapped.request.holder {
+            self.people.insert(person.tax_number.trim().to_owned(), row);
+        }
+
+        let car = match &mapped.request.data {
+            PolicyData::GreenCard(data) => Some(&data.car),
+            PolicyData::Osago(data) => Some(&data.car),
+            PolicyData::Medassistance(_) => None,
+        };
+        if let Some(CarRef::New(car)) = car {
+            for (matched_on, value) in [("chassis", &car.chassis), ("plate", &car.plate)] {
+                if !value.trim().is_empty() {
+                    self.cars.insert(car_key(matched_on, value), row);
+                }
+            }
+        }
+    }
+
+    fn matched(found: Option<i32>, imported: &HashMap<String, usize>, key: &str) -> Matched {
+        match (found, imported.get(key)) {
+            (Some(_), Some(row)) => Matched::Imported { row: *row },
+            (Some(id), None) => Matched::Existing { id },
+            (None, _) => Matched::New,
+        }
+    }
+}
+
+pub struct MappedRow {
+    pub request: CreatePolicyRequest,
+    pub holder: Matched,
+    pub car: Option<Matched>,
+}
+
+// === Row Mapping ===
+
+fn map_holder(row: &mut Row<'_>, tax_number: &str) -> Option<PersonNew> {
+    let last_name = row.required(Field::LastName);
+    let first_name = row.required(Field::FirstName);
+    let birth_date = row.parse(Field::BirthDate, parse_date);
+    let phone = row.required(Field::Phone);
+    let sex = row.optional(Field::Sex, parse_sex).unwrap_or(Sex::Unknown);
+
+    Some(PersonNew {
+        first_name: first_name?.to_owned(),
+        first_name_lat: None,
+        last_name: last_name?.to_owned(),
+        last_name_lat: None,
+        patronymic_name: row.text(Field::PatronymicName),
+        patronymic_name_lat: None,
+        sex,
+        birth_date: birth_date?,
+        tax_number: tax_number.to_owned(),
+        phone: phone?.to_owned(),
+        phone2: row.text(Field::Phone2),
+        email: row.text(Field::Email).unwrap_or_default(),
+        status: PersonStatus::Active,
+    })
+}
+
+/// Spreadsheets rarely carry technical details, so everything except the
+/// identification and make, model and year defaults to empty or zero.
+fn map_car(row: &mut Row<'_>) -> Option<CarNew> {
+    let make = row.required(Field::Make);
+    let model = row.required(Field::Model);
+    let year = row.parse(Field::Year, parse_int);
+    let engine = row.optional(Field::EngineDisplacement, parse_int);
+    let mileage = row.optional(Field::Mileage, parse_int);
+    let unladen_weight = row.optional(Field::UnladenWeight, parse_int);
+    let laden_weight = row.optional(Field::LadenWeight, parse_int);
+    let seats = row.optional(Field::Seats, parse_int);
+
+    Some(CarNew {
+        chassis: row.text(Field::Chassis).unwrap_or_default(),
+        make: make?.to_owned(),
+        model: model?.to_owned(),
+        registration: row.text(Field::Registration).unwrap_or_default(),
+        plate: row.text(Field::Plate).unwrap_or_default(),
+        year: year?,
+        engine_displacement_litres: engine.unwrap_or_default(),
+        mileage_km: mileage.unwrap_or_default(),
+        unladen_weight: unladen_weight.unwrap_or_default(),
+        laden_weight: laden_weight.unwrap_or_default(),
+        seats: seats.unwrap_or_default(),
+    })
+}
+
+async fn resolve_car_ref(
+    tx: &mut Transaction<'_, Postgres>,
+    row: &mut Row<'_>,
+    imported: &Imported,
+) -> sqlx::Result<Option<(CarRef, Matched)>> {
+    let chassis = row.get(Field::Chassis).unwrap_or_default();
+    let plate = row.get(Field::Plate).unwrap_or_default();
+    if chassis.is_empty() && plate.is_empty() {
+        row.errors.push(format!(
+            "Either '{}' or '{}' is required",
+            field_name(Field::Chassis),
+            field_name(Field::Plate)
+        ));
+        return Ok(None);
+    }
+
+    let found = find_car(&mut **tx, chassis, plate).await?;
+    let key = match found {
+        Some((_, "chassis")) => car_key("chassis", chassis),
+        _ => car_key("plate", plate),
+    };
+    let matched = Imported::matched(found.map(|(id, _)| id), &imported.cars, &key);
+    let car_ref = match found {
+        Some((id, _)) => Some(CarRef::Existing { id }),
+        None => map_car(row).map(|car| CarRef::New(Box::new(car))),
+    };
+    Ok(car_ref.map(|car_ref| (car_ref, matched)))
+}
+
+/// Builds a `CreatePolicyRequest` from a row, matching the holder by tax
+/// number and the car by chassis or plate. Returns `None` when the row has
+/// errors, which are left in `row.errors`.
+pub async fn map_row(
+    tx: &mut Transaction<'_, Postgres>,
+    row: &mut Row<'_>,
+    imported: &Imported,
+) -> sqlx::Result<Option<MappedRow>> {
+    let policy_type = row.parse(Field::PolicyType, parse_policy_type);
+    let series = row.required(Field::Series);
+    let number = row.required(Field::Number);
+    let status = row
+        .optional(Field::Status, parse_status)
+        .unwrap_or(PolicyStatus::Active);
+    let start_date = row.parse(Field::StartDate, parse_date);
+    let end_date = row.optional(Field::EndDate, parse_date);
+    let premium = row.parse(Field::Premium, parse_int);
+
+    if let (Some(series), Some(number)) = (series, number) {
+        let exists = sqlx::query_scalar!(
+            r#"select exists(select 1 from policy where series = $1 and number = $2 and tenant_id = current_tenant()) as "e!""#,
+            series,
+            number
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        if exists {
+            row.errors
+                .push(format!("Policy {}{} already exists", series, number));
+        }
+    }
+
+    let mut insurer_id = None;
+    if let Some(name) = row.required(Field::Insurer) {
+        insurer_id = sqlx::query_scalar!(
+            "select id from insurer where lower(name) = lower($1) and tenant_id = current_tenant()",
+            name
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+        if insurer_id.is_none() {
+            row.errors.push(format!("Unknown insurer '{}'", name));
+        }
+    }
+
+    let mut agent_ids = Vec::new();
+    let agent_names = row.get(Field::Agents).unwrap_or_default();
+    for name in agent_names
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        let id = sqlx::query_scalar!(
+            "select id from agent where lower(full_name) = lower($1) and tenant_id = current_tenant() order by id limit 1",
+            name
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+        match id {
+            Some(id) => agent_ids.push(id),
+            None => row.errors.push(format!("Unknown agent '{}'", name)),
+        }
+    }
+
+    let mut holder = None;
+    if let Some(tax_number) = row.required(Field::TaxNumber) {
+        let found = find_person(&mut **tx, tax_number).await?;
+        let matched = Imported::matched(found, &imported.people, tax_number);
+        let person_ref = match found {
+            Some(id) => Some(PersonRef::Existing { id }),
+            None => map_holder(row, tax_number).map(|p| PersonRef::New(Box::new(p))),
+        };
+        holder = person_ref.map(|person_ref| (person_ref, matched));
+    }
+
+    let mut car = None;
+    let data = match policy_type {
+        Some(PolicyType::GreenCard) => {
+            let territory = row.required(Field::Territory);
+            let period = row.parse(Field::Period, parse_period);
+            let car_ref = resolve_car_ref(tx, row, imported).await?;
+            match (territory, period, premium, car_ref) {
+                (
+                    Some(territory),
+                    Some((period_in_units, period_unit)),
+                    Some(premium),
+                    Some((car_ref, matched)),
+                ) => {
+                    car = Some(matched);
+                    Some(PolicyData::GreenCard(GreenCardData {
+                        territory: territory.to_owned(),
+                        period_in_units,
+                        period_unit,
+                        premium,
+                        car: car_ref,
+                    }))
+                }
+                _ => None,
+            }
+        }
+        Some(PolicyType::Osago) => {
+            let period = row.parse(Field::Period, parse_period);
+            let zone = row.parse(Field::Zone, parse_zone);
+            let car_ref = resolve_car_ref(tx, row, imported).await?;
+            match (period, zone, premium, car_ref) {
+                (
+                    Some((period_in_units, period_unit)),
+                    Some(zone),
+                    Some(premium),
+                    Some((car_ref, matched)),
+                ) => {
+                    car = Some(matched);
+                    Some(PolicyData::Osago(OsagoData {
+                        period_in_units,
+                        period_unit,
+                        zone,
+                        exempt: row.text(Field::Exempt).unwrap_or_default(),
+                        premium,
+                        car: car_ref,
+                    }))
+                }
+                _ => None,
+            }
+        }
+        Some(PolicyType::Medassistance) => {
+            let territory = row.required(Field::Territory);
+            let period_days = row.parse(Field::Period, parse_days);
+            let payout = row.parse(Field::Payout, parse_int);
+            let program = row.required(Field::Program);
+            match (territory, period_days, premium, payout, program) {
+                (
+                    Some(territory),
+                    Some(period_days),
+                    Some(premium),
+                    Some(payout),
+                    Some(program),
+                ) => {
+                    Some(PolicyData::Medassistance(MedassistanceData {
+                        territory: territory.to_owned(),
+                        period_days,
+                        premium,
+                        payout,
+                        program: program.to_owned(),
+                        // Members are not part of the flat row format
+                        members: Vec::new(),
+                    }))
+                }
+                _ => None,
+            }
+        }
+        None => None,
+    };
+
+    if !row.errors.is_empty() {
+        return Ok(None);
+    }
+    let (
+        Some(series),
+        Some(number),
+        Some(start_date),
+        Some(insurer_id),
+        Some((holder, holder_matched)),
+        Some(data),
+    ) = (series, number, start_date, insurer_id, holder, data)
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(MappedRow {
+        request: CreatePolicyRequest {
+            holder,
+            insurer_id,
+            series: series.to_owned(),
+            number: number.to_owned(),
+            start_date,
+            end_date,
+            status,
+            agent_ids,
+            // Holders and cars were matched above, anything new is really new
+            on_existing: OnExisting::Reuse,
+            data,
+        },
+        holder: holder_matched,
+        car,
+    }))
+}
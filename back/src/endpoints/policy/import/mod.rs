@@ -0,0 +1,180 @@
+mod columns;
+mod sheet;
+#[cfg(test)]
+mod tests;
+
+use axum::Json;
+use axum::extract::{Multipart, Query, State};
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use sqlx::{Acquire, PgPool};
+use utoipa::{IntoParams, ToSchema};
+
+use super::create::insert_policy;
+use crate::endpoints::attachment::upload::UploadForm;
+use crate::error::{AppError, AppResult};
+use columns::{Columns, Field, Imported, Matched, Row};
+use sheet::SheetFormat;
+
+pub const MAX_FILE_BYTES: usize = 10 * 1024 * 1024;
+
+#[derive(Deserialize, IntoParams)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ImportRow {
+    pub row: usize,
+    pub series: Option<String>,
+    pub number: Option<String>,
+    pub holder: Option<Matched>,
+    pub car: Option<Matched>,
+    /// Only set once the import is committed
+    pub policy_id: Option<i32>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub committed: bool,
+    pub total: usize,
+    pub failed: usize,
+    pub rows: Vec<ImportRow>,
+}
+
+/// Database errors caused by the row's data are reported against the row,
+/// anything else aborts the import.
+fn row_error(err: AppError) -> AppResult<String> {
+    if let AppError::Internal(inner) = &err
+        && let Some(sqlx::Error::Database(db)) = inner.downcast_ref::<sqlx::Error>()
+    {
+        return Ok(db.message().to_owned());
+    }
+    Err(err)
+}
+
+/// Imports policies from the first sheet of a CSV or XLSX file, one policy
+/// per row. Every row is inserted inside a single transaction, each under its
+/// own savepoint so that a failing row does not hide problems in later ones.
+/// The transaction is committed only if this is not a dry run and no row
+/// failed; otherwise it is rolled back and the report serves as a preview.
+#[utoipa::path(
+    post,
+    path = "/policies/import",
+    tag = "policies",
+    params(ImportQuery),
+    request_body(content = UploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, body = ImportReport, description = "Dry run, nothing was saved"),
+        (status = 201, body = ImportReport, description = "Every row was imported"),
+        (status = 400, description = "Missing file or unreadable sheet"),
+        (status = 413, description = "File is larger than 10 MiB"),
+        (status = 415, description = "Neither CSV nor XLSX"),
+        (status = 422, body = ImportReport, description = "Some rows failed, nothing was saved")
+    )
+)]
+pub async fn import_policies(
+    State(pool): State<PgPool>,
+    Query(query): Query<ImportQuery>,
+    mut multipart: Multipart,
+) -> AppResult<(StatusCode, Json<ImportReport>)> {
+    let field = loop {
+        let field = multipart
+            .next_field()
+            .await
+            .map_err(|e| AppError::status(StatusCode::BAD_REQUEST, e.body_text()))?
+            .ok_or(AppError::status(
+                StatusCode::BAD_REQUEST,
+                "Missing file field",
+            ))?;
+        if field.name() == Some("file") {
+            break field;
+        }
+    };
+
+    let file_name = field.file_name().unwrap_or_default().to_owned();
+    let content = field
+        .bytes()
+        .await
+        .map_err(|e| AppError::status(StatusCode::BAD_REQUEST, e.body_text()))?;
+
+    let format = SheetFormat::detect(&file_name, &content).ok_or(AppError::status(
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        "Expected a CSV or XLSX file",
+    ))?;
+    let sheet =
+        sheet::read(format, &content).map_err(|e| AppError::status(StatusCode::BAD_REQUEST, e))?;
+    let columns =
+        Columns::new(&sheet.headers).map_err(|e| AppError::status(StatusCode::BAD_REQUEST, e))?;
+
+    let mut tx = pool.begin().await?;
+    let mut imported = Imported::default();
+    let mut rows = Vec::with_capacity(sheet.rows.len());
+
+    for (line, cells) in &sheet.rows {
+        let mut row = Row::new(&columns, cells);
+        let mut savepoint = tx.begin().await?;
+
+        let mut result = ImportRow {
+            row: *line,
+            series: row.get(Field::Series).map(str::to_owned),
+            number: row.get(Field::Number).map(str::to_owned),
+            holder: None,
+            car: None,
+            policy_id: None,
+            errors: Vec::new(),
+        };
+
+        if let Some(mapped) = columns::map_row(&mut savepoint, &mut row, &imported).await? {
+            imported.record(*line, &mapped);
+            result.holder = Some(mapped.holder);
+            result.car = mapped.car;
+            match insert_policy(&mut savepoint, mapped.request).await {
+                Ok(policy) => result.policy_id = Some(policy.id),
+                Err(err) => row.errors.push(row_error(err)?),
+            }
+        }
+
+        if row.errors.is_empty() {
+            savepoint.commit().await?;
+        } else {
+            savepoint.rollback().await?;
+        }
+        result.errors = row.errors;
+        rows.push(result);
+    }
+
+    let failed = rows.iter().filter(|r| !r.errors.is_empty()).count();
+    let committed = !query.dry_run && failed == 0;
+
+    if committed {
+        tx.commit().await?;
+    } else {
+        tx.rollback().await?;
+        for row in &mut rows {
+            row.policy_id = None;
+        }
+    }
+
+    let status = if committed {
+        StatusCode::CREATED
+    } else if query.dry_run {
+        StatusCode::OK
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+
+    Ok((
+        status,
+        Json(ImportReport {
+            dry_run: query.dry_run,
+            committed,
+            total: rows.len(),
+            failed,
+            rows,
+        }),
+    ))
+}
@@ -0,0 +1,101 @@
+use std::io::Cursor;
+
+use calamine::{Data, DataType, Reader, Xlsx};
+
+pub enum SheetFormat {
+    Csv,
+    Xlsx,
+}
+
+impl SheetFormat {
+    pub fn detect(file_name: &str, content: &[u8]) -> Option<Self> {
+        let extension = file_name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_lowercase());
+        match extension.as_deref() {
+            Some("csv") => Some(SheetFormat::Csv),
+            Some("xlsx") => Some(SheetFormat::Xlsx),
+            // XLSX is a zip archive
+            _ if content.starts_with(b"PK\x03\x04") => Some(SheetFormat::Xlsx),
+            _ => None,
+        }
+    }
+}
+
+/// First sheet of an uploaded file with every cell rendered as text.
+pub struct Sheet {
+    pub headers: Vec<String>,
+    /// Data rows with their 1-based line numbers as shown in a spreadsheet app.
+    pub rows: Vec<(usize, Vec<String>)>,
+}
+
+pub fn read(format: SheetFormat, content: &[u8]) -> Result<Sheet, String> {
+    let mut lines = match format {
+        SheetFormat::Csv => read_csv(content)?,
+        SheetFormat::Xlsx => read_xlsx(content)?,
+    }
+    .into_iter()
+    .enumerate()
+    .map(|(i, cells)| (i + 1, cells));
+
+    let (_, headers) = lines.next().ok_or("File has no header row")?;
+    let rows = lines
+        .filter(|(_, cells)| cells.iter().any(|c| !c.is_empty()))
+        .collect();
+
+    Ok(Sheet { headers, rows })
+}
+
+fn read_csv(content: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
+
+    // Excel with a Ukrainian locale saves CSV with semicolons
+    let first_line = content.split(|b| *b == b'\n').next().unwrap_or_default();
+    let semicolons = first_line.iter().filter(|b| **b == b';').count();
+    let commas = first_line.iter().filter(|b| **b == b',').count();
+    let delimiter = if semicolons > commas { b';' } else { b',' };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(content);
+
+    reader
+        .records()
+        .map(|record| {
+            record
+                .map(|r| r.iter().map(|c| c.trim().to_owned()).collect())
+                .map_err(|e| format!("Invalid CSV: {}", e))
+        })
+        .collect()
+}
+
+fn read_xlsx(content: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let mut workbook =
+        Xlsx::new(Cursor::new(content)).map_err(|e| format!("Invalid XLSX: {}", e))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or("Workbook has no sheets")?
+        .map_err(|e| format!("Invalid XLSX: {}", e))?;
+
+    Ok(range
+        .rows()
+        .map(|row| row.iter().map(cell_text).collect())
+        .collect())
+}
+
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(s) => s.trim().to_owned(),
+        Data::Int(n) => n.to_string(),
+        // Whole numbers come back as floats, and "12" must not turn into "12.0"
+        Data::Float(f) if f.fract() == 0.0 => format!("{}", *f as i64),
+        Data::DateTime(_) | Data::DateTimeIso(_) => cell
+            .as_date()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| cell.to_string()),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,265 @@
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use super::columns::{Columns, Field, Row};
+use crate::testing::TestApp;
+use crate::testing::fixtures::{self, CarBuilder, PersonBuilder, PolicyBuilder};
+
+const HEADER: &str = "Тип;Серія;Номер;Початок;Страховик;Премія;Строк;Зона;\
+    ІПН;Прізвище;Ім'я;Дата народження;Телефон;VIN;Номерний знак;Марка;Модель;Рік випуску";
+
+fn csv(rows: &[&str]) -> Vec<u8> {
+    let mut lines = vec![HEADER];
+    lines.extend_from_slice(rows);
+    lines.join("\n").into_bytes()
+}
+
+/// An OSAGO row for the holder with `tax_number` and the car with `chassis`.
+fn osago(number: &str, tax_number: &str, chassis: &str) -> String {
+    format!(
+        "ОСАГО;ЕР;{};01.03.2026;ТАС;1200;12 міс.;1;{};Шевченко;Тарас;09.03.1980;+380501234567;{};;Skoda;Octavia;2018",
+        number, tax_number, chassis
+    )
+}
+
+async fn import(app: &TestApp, query: &str, rows: &[&str]) -> (StatusCode, Value) {
+    let response = app
+        .upload(
+            &format!("/policies/import{}", query),
+            "policies.csv",
+            "text/csv",
+            &csv(rows),
+        )
+        .await;
+    (response.status, response.body)
+}
+
+async fn count(app: &TestApp, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("select count(*) from {}", table))
+        .fetch_one(&app.pool)
+        .await
+        .unwrap()
+}
+
+#[test]
+fn maps_headers() {
+    let headers: Vec<String> = [
+        " тип ",
+        "Серія",
+        "number",
+        "ПОЧАТОК",
+        "Страхова компанія",
+        "ІПН",
+        "Ім’я",
+        "Номер",
+        "Невідома колонка",
+    ]
+    .map(str::to_owned)
+    .to_vec();
+    let cells: Vec<String> = [
+        "ОСАГО",
+        "ЕР",
+        "1",
+        "01.03.2026",
+        "ТАС",
+        "",
+        "Тарас",
+        "2",
+        "x",
+    ]
+    .map(str::to_owned)
+    .to_vec();
+
+    let columns = Columns::new(&headers).unwrap();
+    let row = Row::new(&columns, &cells);
+    assert_eq!(row.get(Field::PolicyType), Some("ОСАГО"));
+    assert_eq!(row.get(Field::Insurer), Some("ТАС"));
+    // Apostrophes vary between keyboards
+    assert_eq!(row.get(Field::FirstName), Some("Тарас"));
+    // The first of two columns for a field wins
+    assert_eq!(row.get(Field::Number), Some("1"));
+    // Empty cells read as missing
+    assert_eq!(row.get(Field::TaxNumber), None);
+    assert_eq!(row.get(Field::Plate), None);
+
+    let headers: Vec<String> = ["Тип", "Номер", "Страховик"].map(str::to_owned).to_vec();
+    assert_eq!(
+        Columns::new(&headers).err().unwrap(),
+        "Missing columns: Серія, Початок, ІПН"
+    );
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn rejects_unreadable_files(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+
+    let response = app
+        .upload(
+            "/policies/import",
+            "policies.pdf",
+            "application/pdf",
+            b"%PDF-1.7",
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let response = app
+        .upload(
+            "/policies/import",
+            "policies.csv",
+            "text/csv",
+            "Тип;Номер\nОСАГО;1".as_bytes(),
+        )
+        .await;
+    let body = response.expect(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        "Missing columns: Серія, Початок, Страховик, ІПН"
+    );
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn dry_run_reports_row_errors(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    fixtures::insurer(&app, "ТАС").await;
+    let valid = osago("0000001", "3000000001", "TMBJJ7NE0J0000001");
+    let invalid = "ОСАГО;ЕР;0000002;31.02.2026;Оранта;1200;12 міс.;9;3000000002;;Олена;09.03.1980;+380501234568;;;Skoda;Octavia;2018";
+    let no_car = "ОСАГО;ЕР;0000003;01.03.2026;ТАС;1200;12 міс.;1;3000000003;Бондар;Олена;09.03.1980;+380501234569;;;Skoda;Octavia;2018";
+
+    let (status, report) = import(&app, "?dry_run=true", &[&valid, invalid, no_car]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["committed"], false);
+    assert_eq!(report["total"], 3);
+    assert_eq!(report["failed"], 2);
+
+    let rows = &report["rows"];
+    assert_eq!(rows[0]["row"], 2);
+    assert_eq!(rows[0]["errors"], json!([]));
+    assert_eq!(rows[0]["holder"], json!({ "kind": "new" }));
+    assert_eq!(rows[0]["car"], json!({ "kind": "new" }));
+    // Ids of a preview would point at rolled back rows
+    assert_eq!(rows[0]["policy_id"], Value::Null);
+
+    assert_eq!(rows[1]["row"], 3);
+    assert_eq!(
+        rows[1]["errors"],
+        json!([
+            "Column 'Початок' has invalid value '31.02.2026'",
+            "Unknown insurer 'Оранта'",
+            "Column 'Прізвище' is empty",
+            "Column 'Зона' has invalid value '9'",
+            "Either 'Номер кузова' or 'Реєстраційний номер' is required",
+        ])
+    );
+    assert_eq!(rows[2]["series"], "ЕР");
+    assert_eq!(rows[2]["number"], "0000003");
+    assert_eq!(
+        rows[2]["errors"],
+        json!(["Either 'Номер кузова' or 'Реєстраційний номер' is required"])
+    );
+
+    assert_eq!(count(&app, "policy").await, 0);
+    assert_eq!(count(&app, "person").await, 0);
+    assert_eq!(count(&app, "car").await, 0);
+
+    // A clean file is only previewed as well
+    let (status, report) = import(&app, "?dry_run=true", &[&valid]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["committed"], false);
+    assert_eq!(count(&app, "policy").await, 0);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn matches_existing_people_and_cars(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let holder = PersonBuilder::new().with("tax_number", "3000000001");
+    let car = CarBuilder::new().with("chassis", "TMBJJ7NE0J0000001");
+    PolicyBuilder::green_card(&insurer)
+        .with("holder", holder.as_new())
+        .with("car", car.as_new())
+        .create(&app)
+        .await;
+    let person_id: i32 = sqlx::query_scalar("select id from person")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    let car_id: i32 = sqlx::query_scalar("select id from car")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+
+    let (status, report) = import(
+        &app,
+        "",
+        &[
+            // Chassis compare case-insensitively
+            &osago("0000001", "3000000001", "tmbjj7ne0j0000001"),
+            &osago("0000002", "3000000002", "TMBJJ7NE0J0000002"),
+            &osago("0000003", "3000000002", "TMBJJ7NE0J0000002"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", report);
+    assert_eq!(report["committed"], true);
+
+    let rows = &report["rows"];
+    assert_eq!(
+        rows[0]["holder"],
+        json!({ "kind": "existing", "id": person_id })
+    );
+    assert_eq!(rows[0]["car"], json!({ "kind": "existing", "id": car_id }));
+    assert_eq!(rows[1]["holder"], json!({ "kind": "new" }));
+    assert_eq!(rows[1]["car"], json!({ "kind": "new" }));
+    // The holder and car the row before created
+    assert_eq!(rows[2]["holder"], json!({ "kind": "imported", "row": 3 }));
+    assert_eq!(rows[2]["car"], json!({ "kind": "imported", "row": 3 }));
+
+    assert_eq!(count(&app, "person").await, 2);
+    assert_eq!(count(&app, "car").await, 2);
+    assert_eq!(count(&app, "policy").await, 4);
+
+    let policy = app
+        .get(&format!("/policies/{}", rows[0]["policy_id"]))
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(policy["holder"]["id"], person_id);
+    assert_eq!(policy["number"], "0000001");
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn commits_all_rows_or_none(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    fixtures::insurer(&app, "ТАС").await;
+
+    // The second row repeats the first one's policy number
+    let (status, report) = import(
+        &app,
+        "",
+        &[
+            &osago("0000001", "3000000001", "TMBJJ7NE0J0000001"),
+            &osago("0000001", "3000000002", "TMBJJ7NE0J0000002"),
+            &osago("0000003", "3000000003", "TMBJJ7NE0J0000003"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(report["committed"], false);
+    assert_eq!(report["failed"], 1);
+    assert_eq!(report["rows"][0]["errors"], json!([]));
+    assert_eq!(
+        report["rows"][1]["errors"],
+        json!(["Policy ЕР0000001 already exists"])
+    );
+    // Later rows are still checked
+    assert_eq!(report["rows"][2]["errors"], json!([]));
+    for row in report["rows"].as_array().unwrap() {
+        assert_eq!(row["policy_id"], Value::Null);
+    }
+
+    assert_eq!(count(&app, "policy").await, 0);
+    assert_eq!(count(&app, "person").await, 0);
+    assert_eq!(count(&app, "car").await, 0);
+}
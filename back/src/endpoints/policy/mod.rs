@@ -1,19 +1,29 @@
 pub mod create;
+pub mod export;
 pub mod get;
 pub mod get_by_id;
+pub mod import;
+pub mod pdf;
 pub mod update;
 
-use axum::{Router, routing::get};
-use sqlx::PgPool;
+#[cfg(test)]
+mod tests;
 
-pub fn router() -> Router<PgPool> {
-    Router::new()
-        .route(
-            "/policies",
-            get(get::get_policies).post(create::create_policy),
-        )
-        .route(
-            "/policies/{id}",
-            get(get_by_id::get_policy_by_id).put(update::update_policy),
-        )
+use axum::extract::DefaultBodyLimit;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    let import = OpenApiRouter::new()
+        .routes(routes!(import::import_policies))
+        .layer(DefaultBodyLimit::max(import::MAX_FILE_BYTES));
+
+    OpenApiRouter::new()
+        .routes(routes!(get::get_policies, create::create_policy))
+        .routes(routes!(export::export_policies))
+        .merge(import)
+        .routes(routes!(get_by_id::get_policy_by_id, update::update_policy))
+        .routes(routes!(pdf::get_policy_pdf))
 }
@@ -0,0 +1,59 @@
+mod render;
+mod template;
+
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use serde::Deserialize;
+use sqlx::PgPool;
+use utoipa::IntoParams;
+
+use super::get_by_id::load_policy;
+use crate::error::AppResult;
+
+#[derive(Deserialize, IntoParams)]
+pub struct PdfQuery {
+    pub qr: Option<bool>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/policies/{id}/pdf",
+    tag = "policies",
+    params(("id" = i32, Path), PdfQuery),
+    responses(
+        (status = 200, description = "Printable policy", content_type = "application/pdf"),
+        (status = 404, description = "No such policy")
+    )
+)]
+pub async fn get_policy_pdf(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+    Query(query): Query<PdfQuery>,
+) -> AppResult<impl IntoResponse> {
+    let policy = load_policy(&pool, id).await?;
+
+    let file_name = format!("policy-{}{}.pdf", policy.series, policy.number).replace(
+        |c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '.',
+        "_",
+    );
+    let qr_payload = query
+        .qr
+        .unwrap_or(false)
+        .then(|| format!("{} {}", policy.series, policy.number));
+
+    let document = template::for_policy(&policy);
+    let pdf = tokio::task::spawn_blocking(move || render::render(&document, qr_payload.as_deref()))
+        .await??;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_owned()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}\"", file_name),
+            ),
+        ],
+        pdf,
+    ))
+}
@@ -0,0 +1,220 @@
+use std::io::Cursor;
+
+use printpdf::{IndirectFontRef, Mm, PdfDocument, PdfLayerReference, Rect};
+use qrcode::{Color, QrCode};
+use ttf_parser::Face;
+
+use super::template::Document;
+
+const REGULAR_FONT: &[u8] = include_bytes!("../../../../assets/fonts/DejaVuSans.ttf");
+const BOLD_FONT: &[u8] = include_bytes!("../../../../assets/fonts/DejaVuSans-Bold.ttf");
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const VALUE_X: f32 = 75.0;
+/// Space between a label and its value
+const GUTTER: f32 = 3.0;
+const QR_SIZE: f32 = 30.0;
+const QR_TOP: f32 = PAGE_HEIGHT - MARGIN + 5.0;
+const MM_PER_PT: f32 = 25.4 / 72.0;
+
+struct Font {
+    reference: IndirectFontRef,
+    face: Face<'static>,
+}
+
+fn width(face: &Face, text: &str, size: f32) -> f32 {
+    let units: u32 = text
+        .chars()
+        .filter_map(|c| face.glyph_index(c))
+        .filter_map(|glyph| face.glyph_hor_advance(glyph))
+        .map(u32::from)
+        .sum();
+    units as f32 / face.units_per_em() as f32 * size * MM_PER_PT
+}
+
+/// Lines of at most `max_width` mm, broken between words, or inside a word
+/// that does not fit on a line of its own.
+fn wrap(face: &Face, text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let joined = if line.is_empty() {
+            word.to_owned()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if width(face, &joined, size) <= max_width {
+            line = joined;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            line.push(c);
+            if width(face, &line, size) > max_width && line.chars().count() > 1 {
+                line.pop();
+                lines.push(std::mem::replace(&mut line, c.to_string()));
+            }
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+struct Writer {
+    doc: printpdf::PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: Font,
+    bold: Font,
+    y: f32,
+}
+
+impl Writer {
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let (page, layer) = self
+                .doc
+                .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn font(&self, bold: bool) -> &Font {
+        if bold { &self.bold } else { &self.regular }
+    }
+
+    fn text(&self, text: &str, size: f32, x: f32, bold: bool) {
+        let font = &self.font(bold).reference;
+        self.layer.use_text(text, size, Mm(x), Mm(self.y), font);
+    }
+
+    /// Writes `lines` below each other from the current line on, leaving `y`
+    /// for the caller to advance.
+    fn lines(&self, lines: &[String], size: f32, x: f32, bold: bool, line_height: f32) {
+        let font = &self.font(bold).reference;
+        for (i, line) in lines.iter().enumerate() {
+            let y = self.y - i as f32 * line_height;
+            self.layer.use_text(line, size, Mm(x), Mm(y), font);
+        }
+    }
+}
+
+/// Renders the document on A4 pages, optionally with a QR code in the top right corner.
+pub fn render(document: &Document, qr_payload: Option<&str>) -> anyhow::Result<Vec<u8>> {
+    let (doc, page, layer) =
+        PdfDocument::new(&document.title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+    let regular = Font {
+        reference: doc.add_external_font(Cursor::new(REGULAR_FONT))?,
+        face: Face::parse(REGULAR_FONT, 0)?,
+    };
+    let bold = Font {
+        reference: doc.add_external_font(Cursor::new(BOLD_FONT))?,
+        face: Face::parse(BOLD_FONT, 0)?,
+    };
+    let layer = doc.get_page(page).get_layer(layer);
+
+    let mut title_width = PAGE_WIDTH - 2.0 * MARGIN;
+    if let Some(payload) = qr_payload {
+        draw_qr(&layer, payload)?;
+        title_width -= QR_SIZE + GUTTER;
+    }
+
+    let mut writer = Writer {
+        doc,
+        layer,
+        regular,
+        bold,
+        y: PAGE_HEIGHT - MARGIN - 6.0,
+    };
+
+    let title = wrap(&writer.bold.face, &document.title, 16.0, title_width);
+    writer.lines(&title, 16.0, MARGIN, true, 7.0);
+    writer.y -= (title.len() - 1) as f32 * 7.0 + 12.0;
+    if qr_payload.is_some() {
+        // Rows run across the page, so they start below the code
+        writer.y = writer.y.min(QR_TOP - QR_SIZE - 6.0);
+    }
+
+    for section in &document.sections {
+        writer.ensure_space(14.0);
+        writer.text(section.title, 12.0, MARGIN, true);
+        writer.y -= 7.0;
+
+        for (label, value) in &section.rows {
+            let face = &writer.regular.face;
+            let (labels, values, value_x) = if label.is_empty() {
+                let values = wrap(face, value, 10.0, PAGE_WIDTH - 2.0 * MARGIN);
+                (Vec::new(), values, MARGIN)
+            } else {
+                let labels = wrap(face, label, 10.0, VALUE_X - MARGIN - GUTTER);
+                let values = wrap(face, value, 10.0, PAGE_WIDTH - MARGIN - VALUE_X);
+                (labels, values, VALUE_X)
+            };
+            let height = labels.len().max(values.len()) as f32 * 5.0 + 1.0;
+            writer.ensure_space(height);
+            writer.lines(&labels, 10.0, MARGIN, false, 5.0);
+            writer.lines(&values, 10.0, value_x, false, 5.0);
+            writer.y -= height;
+        }
+        writer.y -= 4.0;
+    }
+
+    Ok(writer.doc.save_to_bytes()?)
+}
+
+fn draw_qr(layer: &PdfLayerReference, payload: &str) -> anyhow::Result<()> {
+    let code = QrCode::new(payload.as_bytes())?;
+    let width = code.width();
+    let module = QR_SIZE / width as f32;
+    let left = PAGE_WIDTH - MARGIN - QR_SIZE;
+    let top = QR_TOP;
+
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        if color == Color::Dark {
+            let x = left + (i % width) as f32 * module;
+            let y = top - (i / width) as f32 * module;
+            layer.add_rect(Rect::new(Mm(x), Mm(y - module), Mm(x + module), Mm(y)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_to_the_width() {
+        let face = Face::parse(REGULAR_FONT, 0).unwrap();
+        let text = "Страхова компанія «Універсальна страхова група» з дуже довгою назвою";
+        let max_width = PAGE_WIDTH - MARGIN - VALUE_X;
+
+        let lines = wrap(&face, text, 10.0, max_width);
+        assert!(lines.len() > 1, "{:?}", lines);
+        assert!(
+            lines
+                .iter()
+                .all(|line| width(&face, line, 10.0) <= max_width)
+        );
+        // Only spaces are broken at
+        assert_eq!(lines.join(" "), text);
+
+        let vin = "WVWZZZ1JZ".repeat(8);
+        let lines = wrap(&face, &vin, 10.0, max_width);
+        assert!(
+            lines
+                .iter()
+                .all(|line| width(&face, line, 10.0) <= max_width)
+        );
+        assert_eq!(lines.concat(), vin);
+
+        assert_eq!(wrap(&face, "", 10.0, max_width), [""]);
+        assert_eq!(wrap(&face, "Так", 10.0, max_width), ["Так"]);
+    }
+}
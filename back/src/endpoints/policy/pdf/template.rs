@@ -0,0 +1,171 @@
+use chrono::NaiveDate;
+
+use crate::endpoints::policy::get_by_id::{
+    GreenCardDetails, MedassistanceDetails, OsagoDetails, PolicyDetails, PolicyFull,
+};
+use crate::shared::car::model::CarFull;
+use crate::shared::person::model::PersonFull;
+use crate::shared::policy::labels::{period_label, status_label, zone_label};
+
+pub struct Section {
+    pub title: &'static str,
+    pub rows: Vec<(&'static str, String)>,
+}
+
+pub struct Document {
+    pub title: String,
+    pub sections: Vec<Section>,
+}
+
+pub fn for_policy(policy: &PolicyFull) -> Document {
+    let (title, type_sections) = match &policy.details {
+        PolicyDetails::GreenCard(details) => ("Поліс «Зелена картка»", green_card(details)),
+        PolicyDetails::Medassistance(details) => {
+            ("Поліс медичного асистансу", medassistance(details))
+        }
+        PolicyDetails::Osago(details) => ("Поліс ОСЦПВ (ОСАГО)", osago(details)),
+    };
+
+    let mut sections = vec![
+        Section {
+            title: "Поліс",
+            rows: vec![
+                (
+                    "Серія та номер",
+                    format!("{} {}", policy.series, policy.number),
+                ),
+                ("Страховик", policy.insurer.name.clone()),
+                ("Статус", status_label(policy.status).to_owned()),
+                (
+                    "Період дії",
+                    format!(
+                        "{} — {}",
+                        format_date(policy.start_date),
+                        policy.end_date.map(format_date).unwrap_or("…".to_owned())
+                    ),
+                ),
+            ],
+        },
+        Section {
+            title: "Страхувальник",
+            rows: person_rows(&policy.holder),
+        },
+    ];
+    sections.extend(type_sections);
+
+    if !policy.agents.is_empty() {
+        sections.push(Section {
+            title: "Агенти",
+            rows: policy
+                .agents
+                .iter()
+                .map(|agent| ("", agent.full_name.clone()))
+                .collect(),
+        });
+    }
+
+    Document {
+        title: format!("{} {} {}", title, policy.series, policy.number),
+        sections,
+    }
+}
+
+fn green_card(details: &GreenCardDetails) -> Vec<Section> {
+    vec![
+        Section {
+            title: "Умови страхування",
+            rows: vec![
+                ("Територія дії", details.territory.clone()),
+                (
+                    "Строк",
+                    period_label(details.period_in_units, details.period_unit),
+                ),
+                ("Страхова премія", format_money(details.premium)),
+            ],
+        },
+        car_section(&details.car),
+    ]
+}
+
+fn medassistance(details: &MedassistanceDetails) -> Vec<Section> {
+    vec![
+        Section {
+            title: "Умови страхування",
+            rows: vec![
+                ("Територія дії", details.territory.clone()),
+                ("Строк", format!("{} дн.", details.period_days)),
+                ("Програма", details.program.clone()),
+                ("Страхова сума", format_money(details.payout)),
+                ("Страхова премія", format_money(details.premium)),
+            ],
+        },
+        Section {
+            title: "Застраховані особи",
+            rows: details
+                .members
+                .iter()
+                .map(|member| {
+                    (
+                        "",
+                        format!("{}, {}", full_name(member), format_date(member.birth_date)),
+                    )
+                })
+                .collect(),
+        },
+    ]
+}
+
+fn osago(details: &OsagoDetails) -> Vec<Section> {
+    vec![
+        Section {
+            title: "Умови страхування",
+            rows: vec![
+                (
+                    "Строк",
+                    period_label(details.period_in_units, details.period_unit),
+                ),
+                ("Зона", zone_label(details.zone).to_owned()),
+                ("Пільга", details.exempt.clone()),
+                ("Страхова премія", format_money(details.premium)),
+            ],
+        },
+        car_section(&details.car),
+    ]
+}
+
+fn car_section(car: &CarFull) -> Section {
+    Section {
+        title: "Транспортний засіб",
+        rows: vec![
+            ("Марка та модель", format!("{} {}", car.make, car.model)),
+            ("Реєстраційний номер", car.plate.clone()),
+            ("Номер кузова", car.chassis.clone()),
+            ("Рік випуску", car.year.to_string()),
+        ],
+    }
+}
+
+fn person_rows(person: &PersonFull) -> Vec<(&'static str, String)> {
+    vec![
+        ("ПІБ", full_name(person)),
+        ("Дата народження", format_date(person.birth_date)),
+        ("ІПН", person.tax_number.clone()),
+        ("Телефон", person.phone.clone()),
+        ("Email", person.email.clone()),
+    ]
+}
+
+fn full_name(person: &PersonFull) -> String {
+    match &person.patronymic_name {
+        Some(patronymic) => format!("{} {} {}", person.last_name, person.first_name, patronymic),
+        None => format!("{} {}", person.last_name, person.first_name),
+    }
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%d.%m.%Y").to_string()
+}
+
+fn format_money(amount: i32) -> String {
+    format!("{} грн", amount)
+}
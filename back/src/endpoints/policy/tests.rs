@@ -0,0 +1,443 @@
+use std::time::Duration;
+
+use axum::http::{Method, StatusCode, header};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use crate::idempotency::{IDEMPOTENCY_KEY, IDEMPOTENT_REPLAYED};
+use crate::testing::fixtures::{self, CarBuilder, PersonBuilder, PolicyBuilder, existing};
+use crate::testing::{DEFAULT_TENANT, TestApp, TestResponse};
+
+/// `PUT /policies/{id}` body with the fields of `policy` and the holder it
+/// was stored with.
+fn update_body(policy: &PolicyBuilder, stored: &Value) -> Value {
+    let mut body = policy.body();
+    body["holder"] = existing(&stored["holder"]["id"]);
+    body
+}
+
+async fn list(app: &TestApp, query: &str) -> Value {
+    app.get(&format!("/policies?{}", query))
+        .await
+        .expect(StatusCode::OK)
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn green_card(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let agent = fixtures::agent(&app, "Олена Іваненко").await;
+    PolicyBuilder::osago(&insurer).create(&app).await;
+
+    let car = CarBuilder::new().with("plate", "KA1234AA");
+    let policy = PolicyBuilder::green_card(&insurer)
+        .with("car", car.as_new())
+        .with("agent_ids", json!([agent]));
+    let created = policy.create(&app).await;
+    assert_eq!(created["policy_type"], "GreenCard");
+    let uri = format!("/policies/{}", created["id"]);
+
+    let stored = app.get(&uri).await.expect(StatusCode::OK);
+    assert_eq!(stored["policy_type"], "GreenCard");
+    assert_eq!(stored["territory"], "Європа");
+    assert_eq!(stored["car"]["plate"], "KA1234AA");
+    assert_eq!(stored["insurer"]["name"], "ТАС");
+    assert_eq!(stored["agents"][0]["full_name"], "Олена Іваненко");
+    assert_eq!(stored["holder"]["first_name_lat"], "Taras");
+
+    let mut body = update_body(&policy, &stored);
+    body["car"] = existing(&stored["car"]["id"]);
+    body["territory"] = json!("Молдова");
+    body["premium"] = json!(1800);
+    body["status"] = json!("Prolonged");
+    let updated = app.put(&uri, body).await.expect(StatusCode::OK);
+    assert_eq!(updated["premium"], 1800);
+
+    let stored = app.get(&uri).await.expect(StatusCode::OK);
+    assert_eq!(stored["territory"], "Молдова");
+    assert_eq!(stored["status"], "Prolonged");
+    assert_eq!(stored["car"]["id"], updated["car"]["id"]);
+
+    let page = list(&app, "policy_types=GreenCard").await;
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["id"], created["id"]);
+    assert_eq!(page["items"][0]["car_plate"], "KA1234AA");
+    assert_eq!(page["items"][0]["agent_names"], "Олена Іваненко");
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn medassistance(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "Уніка").await;
+    PolicyBuilder::green_card(&insurer).create(&app).await;
+
+    let member = PersonBuilder::new().create(&app).await;
+    let policy = PolicyBuilder::medassistance(&insurer).with(
+        "members",
+        json!([existing(&member["id"]), PersonBuilder::new().as_new()]),
+    );
+    let created = policy.create(&app).await;
+    assert_eq!(created["policy_type"], "Medassistance");
+    let uri = format!("/policies/{}", created["id"]);
+
+    let stored = app.get(&uri).await.expect(StatusCode::OK);
+    assert_eq!(stored["policy_type"], "Medassistance");
+    assert_eq!(stored["program"], "Стандарт");
+    assert_eq!(stored["members"].as_array().unwrap().len(), 2);
+    assert!(stored.get("car").is_none());
+
+    let mut body = update_body(&policy, &stored);
+    body["members"] = json!([existing(&member["id"])]);
+    body["payout"] = json!(50_000);
+    let updated = app.put(&uri, body).await.expect(StatusCode::OK);
+    assert_eq!(updated["members"].as_array().unwrap().len(), 1);
+
+    let stored = app.get(&uri).await.expect(StatusCode::OK);
+    assert_eq!(stored["payout"], 50_000);
+    assert_eq!(stored["members"][0]["id"], member["id"]);
+
+    let page = list(&app, "policy_types=Medassistance").await;
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["id"], created["id"]);
+    assert_eq!(page["items"][0]["car_plate"], Value::Null);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn osago(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ARX").await;
+    PolicyBuilder::medassistance(&insurer).create(&app).await;
+
+    let holder = PersonBuilder::new().with("last_name", "Шевченко");
+    let car = CarBuilder::new();
+    let policy = PolicyBuilder::osago(&insurer)
+        .with("holder", holder.as_new())
+        .with("car", car.as_new());
+    let created = policy.create(&app).await;
+    assert_eq!(created["policy_type"], "Osago");
+    let uri = format!("/policies/{}", created["id"]);
+
+    let stored = app.get(&uri).await.expect(StatusCode::OK);
+    assert_eq!(stored["policy_type"], "Osago");
+    assert_eq!(stored["zone"], "Zone1");
+    assert_eq!(stored["holder"]["last_name"], "Шевченко");
+
+    // Correcting the car's plate keeps the same car
+    let mut renewed_car = car.with("plate", "BC7777CB").as_new();
+    renewed_car["kind"] = json!("ExistingWithUpdates");
+    renewed_car["id"] = stored["car"]["id"].clone();
+    let mut body = update_body(&policy, &stored);
+    body["car"] = renewed_car;
+    body["zone"] = json!("Zone2");
+    app.put(&uri, body).await.expect(StatusCode::OK);
+
+    let stored_after = app.get(&uri).await.expect(StatusCode::OK);
+    assert_eq!(stored_after["zone"], "Zone2");
+    assert_eq!(stored_after["car"]["id"], stored["car"]["id"]);
+    assert_eq!(stored_after["car"]["plate"], "BC7777CB");
+
+    let query = format!(
+        "policy_types=Osago&number={}",
+        created["number"].as_str().unwrap()
+    );
+    let page = list(&app, &query).await;
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["car_plate"], "BC7777CB");
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn sort_by_series(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    for (series, number) in [("ВВ", "1"), ("АА", "3"), ("ББ", "2")] {
+        PolicyBuilder::osago(&insurer)
+            .with("series", series)
+            .with("number", number)
+            .create(&app)
+            .await;
+    }
+    let series = |page: Value| -> Vec<Value> {
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["series"].clone())
+            .collect()
+    };
+
+    let page = list(&app, "sort=series").await;
+    assert_eq!(series(page), ["АА", "ББ", "ВВ"]);
+    let page = list(&app, "sort=series&order=desc").await;
+    assert_eq!(series(page), ["ВВ", "ББ", "АА"]);
+
+    app.get("/policies?sort=premium")
+        .await
+        .expect(StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn update_keeps_policy_type(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let created = PolicyBuilder::osago(&insurer).create(&app).await;
+    let uri = format!("/policies/{}", created["id"]);
+    let stored = app.get(&uri).await.expect(StatusCode::OK);
+
+    let body = update_body(&PolicyBuilder::green_card(&insurer), &stored);
+    let response = app.put(&uri, body).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn missing_policy(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+
+    app.get("/policies/1").await.expect(StatusCode::NOT_FOUND);
+    let body = PolicyBuilder::osago(&insurer).body();
+    app.put("/policies/1", body)
+        .await
+        .expect(StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn missing_insurer(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let created = PolicyBuilder::osago(&insurer).create(&app).await;
+    let uri = format!("/policies/{}", created["id"]);
+    let stored = app.get(&uri).await.expect(StatusCode::OK);
+
+    let policy = PolicyBuilder::osago(&json!(999));
+    let missing = app
+        .post("/policies", policy.body())
+        .await
+        .expect(StatusCode::NOT_FOUND);
+    assert_eq!(missing["message"], "No insurer with id 999");
+    app.put(&uri, update_body(&policy, &stored))
+        .await
+        .expect(StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn new_holder_with_taken_tax_number(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let holder = PersonBuilder::new();
+    let stored = holder.create(&app).await;
+
+    let policy = PolicyBuilder::green_card(&insurer).with("holder", holder.as_new());
+    let conflict = app
+        .post("/policies", policy.body())
+        .await
+        .expect(StatusCode::CONFLICT);
+    assert_eq!(conflict["existing_id"], stored["id"]);
+
+    let reused = policy.with("on_existing", "reuse").create(&app).await;
+    assert_eq!(reused["holder_id"], stored["id"]);
+}
+
+/// Sends `request` while another transaction stores a person with the tax
+/// number, committing only after the request has looked for one. Returns the
+/// id of that person and the response.
+async fn race_person(
+    app: &TestApp,
+    tax_number: &str,
+    request: impl Future<Output = TestResponse>,
+) -> (i32, TestResponse) {
+    let mut other = app.pool.begin().await.unwrap();
+    let id = sqlx::query_scalar!(
+        r#"
+        insert into person (first_name, last_name, sex, birth_date, tax_number, phone, email, status, tenant_id)
+        values ('Олена', 'Шевченко', 'f', '1990-01-01', $1, '+380500000000', 'olena@insure.test', 'active', $2)
+        returning id
+        "#,
+        tax_number,
+        DEFAULT_TENANT
+    )
+    .fetch_one(&mut *other)
+    .await
+    .unwrap();
+
+    let commit = async {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        other.commit().await.unwrap();
+    };
+    let (response, ()) = tokio::join!(request, commit);
+    (id, response)
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn tax_number_taken_concurrently(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+
+    let holder = PersonBuilder::new();
+    let policy = PolicyBuilder::green_card(&insurer).with("holder", holder.as_new());
+    let tax_number = holder.body()["tax_number"].as_str().unwrap().to_owned();
+    let (stored, response) =
+        race_person(&app, &tax_number, app.post("/policies", policy.body())).await;
+    assert_eq!(response.expect(StatusCode::CONFLICT)["existing_id"], stored);
+
+    let person = PersonBuilder::new();
+    let tax_number = person.body()["tax_number"].as_str().unwrap().to_owned();
+    let (stored, response) =
+        race_person(&app, &tax_number, app.post("/people", person.body())).await;
+    assert_eq!(response.expect(StatusCode::CONFLICT)["existing_id"], stored);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn stale_update(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let policy = PolicyBuilder::osago(&insurer);
+    let created = policy.create(&app).await;
+    let uri = format!("/policies/{}", created["id"]);
+
+    let read = app.get(&uri).await;
+    let etag = read.headers[header::ETAG].to_str().unwrap().to_owned();
+    assert_eq!(etag, "\"1\"");
+    let stored = read.expect(StatusCode::OK);
+    let mut body = update_body(&policy, &stored);
+    body["car"] = existing(&stored["car"]["id"]);
+
+    let if_match = [(header::IF_MATCH, etag.as_str())];
+    let mut first = body.clone();
+    first["premium"] = json!(2600);
+    let updated = app.send(Method::PUT, &uri, &if_match, Some(first)).await;
+    assert_eq!(updated.headers[header::ETAG], "\"2\"");
+    assert_eq!(updated.expect(StatusCode::OK)["version"], 2);
+
+    // A second edit based on the same read must not undo the first
+    let conflict = app
+        .send(Method::PUT, &uri, &if_match, Some(body.clone()))
+        .await
+        .expect(StatusCode::CONFLICT);
+    assert_eq!(conflict["current"]["premium"], 2600);
+    assert_eq!(conflict["current"]["version"], 2);
+
+    body["version"] = json!(1);
+    app.put(&uri, body.clone())
+        .await
+        .expect(StatusCode::CONFLICT);
+
+    body["version"] = json!(2);
+    app.put(&uri, body).await.expect(StatusCode::OK);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn stale_nested_updates(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let holder = PersonBuilder::new();
+    let person = holder.create(&app).await;
+    let policy = PolicyBuilder::osago(&insurer);
+    let created = policy.create(&app).await;
+    let uri = format!("/policies/{}", created["id"]);
+    let stored = app.get(&uri).await.expect(StatusCode::OK);
+
+    // Someone else corrects the person in the meantime
+    let person_uri = format!("/people/{}", person["id"]);
+    let mut edited = holder.body();
+    edited["phone2"] = json!("+380441234567");
+    edited["version"] = person["version"].clone();
+    app.put(&person_uri, edited.clone())
+        .await
+        .expect(StatusCode::OK);
+    app.put(&person_uri, edited)
+        .await
+        .expect(StatusCode::CONFLICT);
+
+    let mut body = update_body(&policy, &stored);
+    body["car"] = existing(&stored["car"]["id"]);
+    body["holder"] = holder.with("email", "typo@insure.test").as_new();
+    body["holder"]["kind"] = json!("ExistingWithUpdates");
+    body["holder"]["id"] = person["id"].clone();
+    body["holder"]["version"] = person["version"].clone();
+    let conflict = app
+        .put(&uri, body.clone())
+        .await
+        .expect(StatusCode::CONFLICT);
+    assert_eq!(conflict["current"]["id"], person["id"]);
+    assert_eq!(conflict["current"]["phone2"], "+380441234567");
+
+    let mut car = CarBuilder::new().as_new();
+    car["kind"] = json!("ExistingWithUpdates");
+    car["id"] = stored["car"]["id"].clone();
+    car["version"] = json!(0);
+    body["holder"] = existing(&person["id"]);
+    body["car"] = car;
+    let conflict = app.put(&uri, body).await.expect(StatusCode::CONFLICT);
+    assert_eq!(conflict["current"]["id"], stored["car"]["id"]);
+
+    // Neither refused update left anything behind
+    let after = app.get(&uri).await.expect(StatusCode::OK);
+    assert_eq!(after["version"], stored["version"]);
+    assert_eq!(after["holder"]["id"], stored["holder"]["id"]);
+    assert_eq!(after["car"]["plate"], stored["car"]["plate"]);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn retried_create(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let policy = PolicyBuilder::green_card(&insurer);
+    let key = [(IDEMPOTENCY_KEY, "retry-1")];
+    let create = || app.send(Method::POST, "/policies", &key, Some(policy.body()));
+
+    let first = create().await;
+    assert!(first.headers.get(IDEMPOTENT_REPLAYED).is_none());
+    let created = first.expect(StatusCode::CREATED);
+
+    let retry = create().await;
+    assert_eq!(retry.headers[IDEMPOTENT_REPLAYED], "true");
+    assert_eq!(retry.expect(StatusCode::CREATED), created);
+    assert_eq!(list(&app, "policy_types=GreenCard").await["total"], 1);
+
+    let mut other = policy.body();
+    other["premium"] = json!(1600);
+    app.send(Method::POST, "/policies", &key, Some(other))
+        .await
+        .expect(StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Once the key expires the request is handled anew, here hitting the
+    // holder created the first time
+    sqlx::query!("update idempotency_key set expires_at = now() - interval '1 second'")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let conflict = create().await;
+    assert!(conflict.headers.get(IDEMPOTENT_REPLAYED).is_none());
+    conflict.expect(StatusCode::CONFLICT);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn pdf_of_every_type(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    // Long values wrap instead of running off the page
+    let insurer = fixtures::insurer(
+        &app,
+        "Приватне акціонерне товариство «Страхова компанія з дуже довгою назвою»",
+    )
+    .await;
+
+    for policy in [
+        PolicyBuilder::green_card(&insurer).with(
+            "territory",
+            "Європа, Туреччина, Марокко, Туніс, Азербайджан, Молдова",
+        ),
+        PolicyBuilder::osago(&insurer),
+        PolicyBuilder::medassistance(&insurer),
+    ] {
+        let holder = PersonBuilder::new().with("last_name", "Шевченко-Квітка-Основ'яненко");
+        let policy = policy.with("holder", holder.as_new()).create(&app).await;
+        for query in ["", "?qr=true"] {
+            let response = app
+                .get(&format!("/policies/{}/pdf{}", policy["id"], query))
+                .await;
+            assert_eq!(response.status, StatusCode::OK);
+            assert_eq!(response.headers[header::CONTENT_TYPE], "application/pdf");
+            assert!(response.text.starts_with("%PDF-"), "{}", policy["id"]);
+        }
+    }
+}
@@ -1,30 +1,44 @@
 use axum::Json;
 use axum::extract::{Path, State};
-use serde::Deserialize;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use utoipa::ToSchema;
 
 use super::create::PolicyData;
-use super::get_by_id::PolicyFull;
+use super::get_by_id::{PolicyFull, load_policy};
 use crate::error::{AppError, AppResult};
+use crate::shared::attachment::{self, model::AttachmentOwner};
 use crate::shared::car::model::CarFull;
 use crate::shared::car::resolver::resolve_car;
+use crate::shared::existing::OnExisting;
+use crate::shared::insurer::model::Insurer;
 use crate::shared::person::resolver::resolve_person;
+use crate::shared::policy::resolver::{ensure_agents, ensure_insurer};
+use crate::shared::version::{self, IfMatch, UpdateConflict};
 use crate::shared::{
     person::model::{PersonFull, PersonRef, PersonStatus},
     policy::model::{CarInsurancePeriodUnit, OsagoZone, PolicyStatus, PolicyType},
 };
+use crate::webhook::{self, EventType};
 
 // === Update Request ===
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct UpdatePolicyRequest {
     pub holder: PersonRef,
+    pub insurer_id: i32,
     pub series: String,
     pub number: String,
     pub start_date: chrono::NaiveDate,
     pub end_date: Option<chrono::NaiveDate>,
     pub status: PolicyStatus,
     pub agent_ids: Vec<i32>,
+    /// Applies to holders, members and cars submitted as new
+    #[serde(default)]
+    pub on_existing: OnExisting,
+    /// Alternative to `If-Match`
+    pub version: Option<i32>,
     #[serde(flatten)]
     pub data: PolicyData,
 }
@@ -33,24 +47,54 @@ pub struct UpdatePolicyRequest {
 
 struct PolicyTypeRow {
     policy_type: PolicyType,
+    status: PolicyStatus,
+    version: i32,
+}
+
+/// Payload of `policy.status_changed`, e.g. a renewal or a cancellation.
+#[derive(Serialize)]
+struct StatusChange<'a> {
+    id: i32,
+    policy_type: PolicyType,
+    series: &'a str,
+    number: &'a str,
+    previous_status: PolicyStatus,
+    status: PolicyStatus,
 }
 
 // === Endpoint ===
 
+#[utoipa::path(
+    put,
+    path = "/policies/{id}",
+    tag = "policies",
+    params(
+        ("id" = i32, Path),
+        ("If-Match" = Option<String>, Header, description = "ETag of the policy being edited")
+    ),
+    responses(
+        (status = 200, body = PolicyFull, headers(("ETag" = String))),
+        (status = 400, description = "Policy type differs from the stored one"),
+        (status = 404, description = "No such policy, or a person, car, insurer or agent referenced by id"),
+        (status = 409, body = UpdateConflict<PolicyFull>, description = "A holder, member or car submitted as new already exists, or the policy or a person or car updated with it changed since it was read")
+    )
+)]
 pub async fn update_policy(
     State(pool): State<PgPool>,
     Path(id): Path<i32>,
+    if_match: IfMatch,
     Json(body): Json<UpdatePolicyRequest>,
-) -> AppResult<Json<PolicyFull>> {
+) -> AppResult<impl IntoResponse> {
     let mut tx = pool.begin().await?;
 
-    // Verify the policy exists and get its type
+    // Verify the policy exists and get its type, holding it until commit
     let existing = sqlx::query_as!(
         PolicyTypeRow,
         r#"
-        select type as "policy_type: PolicyType"
+        select type as "policy_type: PolicyType", status as "status: PolicyStatus", version
         from policy
-        where id = $1
+        where id = $1 and tenant_id = current_tenant()
+        for update
         "#,
         id
     )
@@ -58,6 +102,11 @@ pub async fn update_policy(
     .await?
     .ok_or(AppError::not_found())?;
 
+    if version::is_stale(if_match.or(body.version), existing.version) {
+        tx.rollback().await?;
+        return Err(version::conflict("policy", load_policy(&pool, id).await?));
+    }
+
     // Verify the policy type matches (cannot change policy type)
     let request_type = match &body.data {
         PolicyData::GreenCard(_) => PolicyType::GreenCard,
@@ -69,37 +118,43 @@ pub async fn update_policy(
         return Err(AppError::bad_request());
     }
 
+    ensure_insurer(&mut *tx, body.insurer_id).await?;
+
     // Resolve holder
-    let holder_id = resolve_person(&mut tx, body.holder).await?;
+    let holder_id = resolve_person(&mut tx, body.holder, body.on_existing).await?;
 
     // Update main policy table
-    sqlx::query!(
+    let version = sqlx::query_scalar!(
         r#"
         update policy
         set
             holder_id = $2,
-            series = $3,
-            number = $4,
-            start_date = $5,
-            end_date = $6,
-            status = $7
-        where id = $1
+            insurer_id = $3,
+            series = $4,
+            number = $5,
+            start_date = $6,
+            end_date = $7,
+            status = $8,
+            version = version + 1
+        where id = $1 and tenant_id = current_tenant()
+        returning version
         "#,
         id,
         holder_id,
+        body.insurer_id,
         body.series,
         body.number,
         body.start_date,
         body.end_date,
         body.status as PolicyStatus
     )
-    .execute(&mut *tx)
+    .fetch_one(&mut *tx)
     .await?;
 
     // Update type-specific data
     let details = match body.data {
         PolicyData::GreenCard(data) => {
-            let car_id = resolve_car(&mut tx, data.car).await?;
+            let car_id = resolve_car(&mut tx, data.car, body.on_existing).await?;
 
             sqlx::query!(
                 r#"
@@ -137,9 +192,10 @@ pub async fn update_policy(
                     mileage_km,
                     unladen_weight,
                     laden_weight,
-                    seats
+                    seats,
+                    version
                 from car
-                where id = $1
+                where id = $1 and tenant_id = current_tenant()
                 "#,
                 car_id
             )
@@ -179,7 +235,7 @@ pub async fn update_policy(
             // Resolve all member IDs first
             let mut member_ids = Vec::with_capacity(data.members.len());
             for member in data.members {
-                let member_id = resolve_person(&mut tx, member).await?;
+                let member_id = resolve_person(&mut tx, member, body.on_existing).await?;
                 member_ids.push(member_id);
             }
 
@@ -226,9 +282,10 @@ pub async fn update_policy(
                     phone,
                     phone2,
                     email,
-                    status as "status: PersonStatus"
+                    status as "status: PersonStatus",
+                    version
                 from person
-                where id = any($1)
+                where id = any($1) and tenant_id = current_tenant()
                 "#,
                 &member_ids
             )
@@ -245,7 +302,7 @@ pub async fn update_policy(
             })
         }
         PolicyData::Osago(data) => {
-            let car_id = resolve_car(&mut tx, data.car).await?;
+            let car_id = resolve_car(&mut tx, data.car, body.on_existing).await?;
 
             sqlx::query!(
                 r#"
@@ -285,9 +342,10 @@ pub async fn update_policy(
                     mileage_km,
                     unladen_weight,
                     laden_weight,
-                    seats
+                    seats,
+                    version
                 from car
-                where id = $1
+                where id = $1 and tenant_id = current_tenant()
                 "#,
                 car_id
             )
@@ -306,6 +364,7 @@ pub async fn update_policy(
     };
 
     // Update agent_policy links
+    ensure_agents(&mut *tx, &body.agent_ids).await?;
     sqlx::query!(
         r#"
         DELETE FROM agent_policy WHERE policy_id = $1
@@ -346,15 +405,29 @@ pub async fn update_policy(
             phone,
             phone2,
             email,
-            status as "status: PersonStatus"
+            status as "status: PersonStatus",
+            version
         from person
-        where id = $1
+        where id = $1 and tenant_id = current_tenant()
         "#,
         holder_id
     )
     .fetch_one(&mut *tx)
     .await?;
 
+    // Fetch insurer
+    let insurer = sqlx::query_as!(
+        Insurer,
+        r#"
+        select id, name
+        from insurer
+        where id = $1 and tenant_id = current_tenant()
+        "#,
+        body.insurer_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
     // Fetch agents
     let agents = sqlx::query_as!(
         super::get_by_id::Agent,
@@ -369,17 +442,37 @@ pub async fn update_policy(
     .fetch_all(&mut *tx)
     .await?;
 
-    tx.commit().await?;
+    let attachments = attachment::for_owner(&mut *tx, AttachmentOwner::Policy, id).await?;
 
-    Ok(Json(PolicyFull {
+    let policy = PolicyFull {
         id,
         holder,
+        insurer,
         series: body.series,
         number: body.number,
         start_date: body.start_date,
         end_date: body.end_date,
         status: body.status,
         agents,
+        attachments,
         details,
-    }))
+        version,
+    };
+
+    webhook::publish(&mut tx, EventType::PolicyUpdated, &policy).await?;
+    if policy.status != existing.status {
+        let change = StatusChange {
+            id,
+            policy_type: existing.policy_type,
+            series: &policy.series,
+            number: &policy.number,
+            previous_status: existing.status,
+            status: policy.status,
+        };
+        webhook::publish(&mut tx, EventType::PolicyStatusChanged, &change).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok((version::etag(policy.version), Json(policy)))
 }
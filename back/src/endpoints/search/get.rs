@@ -0,0 +1,396 @@
+use axum::extract::{Query, State};
+use axum::{Extension, Json};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::error::AppResult;
+use crate::shared::policy::labels::type_label;
+use crate::shared::policy::model::PolicyType;
+use crate::tenant::{self, UserTenants};
+
+const DEFAULT_LIMIT: i64 = 10;
+const MAX_LIMIT: i64 = 50;
+
+/// Queries shorter than this match too much to be useful
+const MIN_QUERY_CHARS: usize = 2;
+
+/// Phone numbers are only searched from this many digits on
+const MIN_PHONE_DIGITS: usize = 5;
+
+/// Minimum pg_trgm word similarity for a fuzzy name match
+const NAME_SIMILARITY: &str = "0.45";
+
+/// Rows taken from each index before ranking. Broad queries like a common
+/// surname or a VIN prefix match thousands of rows; ranking all of them is
+/// what makes search slow, not finding them.
+const CANDIDATE_LIMIT: i64 = 200;
+
+#[derive(Deserialize, IntoParams)]
+pub struct SearchQuery {
+    pub q: String,
+    /// Hits per group
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SearchHit {
+    pub id: i32,
+    pub label: String,
+    pub detail: Option<String>,
+    /// Front-end route of the record, for a car its policies
+    pub link: String,
+    pub score: f32,
+}
+
+#[derive(Serialize, Default, ToSchema)]
+pub struct SearchResponse {
+    pub people: Vec<SearchHit>,
+    pub cars: Vec<SearchHit>,
+    pub policies: Vec<SearchHit>,
+}
+
+struct SearchRow {
+    id: i32,
+    label: String,
+    detail: Option<String>,
+    score: f32,
+}
+
+/// Same folding as the `ident_key` SQL function.
+fn ident_key(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_uppercase)
+        .map(|c| match c {
+            'А' => 'A',
+            'В' => 'B',
+            'Е' => 'E',
+            'І' => 'I',
+            'К' => 'K',
+            'М' => 'M',
+            'Н' => 'H',
+            'О' => 'O',
+            'Р' => 'P',
+            'С' => 'C',
+            'Т' => 'T',
+            'Х' => 'X',
+            c => c,
+        })
+        .collect()
+}
+
+async fn search_people(
+    pool: &PgPool,
+    tenant_id: i32,
+    text: &str,
+    digits: &str,
+    limit: i64,
+) -> sqlx::Result<Vec<SearchRow>> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "select set_config('pg_trgm.word_similarity_threshold', $1, true)",
+        NAME_SIMILARITY
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let rows = sqlx::query_as!(
+        SearchRow,
+        r#"
+        with candidate as (
+            (
+                select id from person
+                where tenant_id = $5
+                    and status <> 'archived'
+                    and person_name_doc(last_name, first_name, patronymic_name) like $1 || '%'
+                limit $4
+            )
+            union
+            (
+                select id from person
+                where tenant_id = $5
+                    and status <> 'archived'
+                    and person_name_doc(last_name, first_name, patronymic_name) like '%' || $1 || '%'
+                limit $4
+            )
+            union
+            (
+                select id from person
+                where tenant_id = $5
+                    and status <> 'archived'
+                    and $1 <% person_name_doc(last_name, first_name, patronymic_name)
+                limit $4
+            )
+            union
+            (
+                select id from person
+                where tenant_id = $5
+                    and status <> 'archived'
+                    and translit_uk($1) <% person_name_lat_doc(last_name, first_name, patronymic_name, last_name_lat, first_name_lat, patronymic_name_lat)
+                limit $4
+            )
+            union
+            (
+                select id from person
+                where tenant_id = $5
+                    and status <> 'archived'
+                    and tax_number like '%' || $1 || '%'
+                limit $4
+            )
+            union
+            (
+                select id from person
+                where tenant_id = $5
+                    and status <> 'archived'
+                    and $2 <> ''
+                    and regexp_replace(phone, '[^0-9]', '', 'g') like '%' || $2 || '%'
+                limit $4
+            )
+        )
+        select
+            id,
+            last_name || ' ' || first_name || coalesce(' ' || patronymic_name, '') as "label!",
+            nullif(concat_ws(' · ', nullif(tax_number, ''), nullif(phone, '')), '') as detail,
+            greatest(
+                case
+                    when tax_number = $1 then 3
+                    when $2 <> '' and right(regexp_replace(phone, '[^0-9]', '', 'g'), 9) = right($2, 9) then 3
+                    when person_name_doc(last_name, first_name, patronymic_name) like $1 || '%' then 2
+                    when person_name_doc(last_name, first_name, patronymic_name) like '%' || $1 || '%' then 1
+                    when tax_number like '%' || $1 || '%' then 1
+                    when $2 <> '' and regexp_replace(phone, '[^0-9]', '', 'g') like '%' || $2 || '%' then 1
+                end,
+                word_similarity($1, person_name_doc(last_name, first_name, patronymic_name)),
+                word_similarity(
+                    translit_uk($1),
+                    person_name_lat_doc(last_name, first_name, patronymic_name, last_name_lat, first_name_lat, patronymic_name_lat)
+                )
+            )::real as "score!"
+        from person
+        join candidate using (id)
+        order by 4 desc, last_name, first_name, id
+        limit $3
+        "#,
+        text,
+        digits,
+        limit,
+        CANDIDATE_LIMIT,
+        tenant_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(rows)
+}
+
+/// Cars have no page of their own, their hits link to the policy list
+/// filtered by the plate, or make and model without one.
+async fn search_cars(
+    pool: &PgPool,
+    tenant_id: i32,
+    text: &str,
+    key: &str,
+    limit: i64,
+) -> sqlx::Result<Vec<(SearchRow, String)>> {
+    let rows = sqlx::query!(
+        r#"
+        with candidate as (
+            (select id from car where tenant_id = $5 and plate_key like $2 || '%' limit $4)
+            union
+            (select id from car where tenant_id = $5 and plate_key like '%' || $2 || '%' limit $4)
+            union
+            (select id from car where tenant_id = $5 and chassis_key like $2 || '%' limit $4)
+            union
+            (select id from car where tenant_id = $5 and chassis_key like '%' || $2 || '%' limit $4)
+            union
+            (select id from car where tenant_id = $5 and $1 <% lower(make || ' ' || model) limit $4)
+        )
+        select
+            id,
+            make || ' ' || model as "label!",
+            nullif(concat_ws(' · ', nullif(plate, ''), nullif(chassis, '')), '') as detail,
+            coalesce(nullif(plate, ''), make || ' ' || model) as "policy_filter!",
+            greatest(
+                case
+                    when plate_key = $2 or chassis_key = $2 then 3
+                    when plate_key like $2 || '%' or chassis_key like $2 || '%' then 2
+                    when plate_key like '%' || $2 || '%' or chassis_key like '%' || $2 || '%' then 1
+                end,
+                word_similarity($1, lower(make || ' ' || model))
+            )::real as "score!"
+        from car
+        join candidate using (id)
+        order by 5 desc, plate, id
+        limit $3
+        "#,
+        text,
+        key,
+        limit,
+        CANDIDATE_LIMIT,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                SearchRow {
+                    id: row.id,
+                    label: row.label,
+                    detail: row.detail,
+                    score: row.score,
+                },
+                row.policy_filter,
+            )
+        })
+        .collect())
+}
+
+async fn search_policies(
+    pool: &PgPool,
+    tenant_id: i32,
+    key: &str,
+    limit: i64,
+) -> sqlx::Result<Vec<(SearchRow, PolicyType)>> {
+    let rows = sqlx::query!(
+        r#"
+        with candidate as (
+            (select id from policy where tenant_id = $4 and number_key like $1 || '%' limit $3)
+            union
+            (select id from policy where tenant_id = $4 and number_key like '%' || $1 || '%' limit $3)
+        )
+        select
+            policy.id,
+            series || ' ' || number as "label!",
+            type as "policy_type: PolicyType",
+            person.last_name || ' ' || person.first_name as "holder_name!",
+            case
+                when number_key = $1 or ident_key(number) = $1 then 3
+                when number_key like $1 || '%' or ident_key(number) like $1 || '%' then 2
+                else 1
+            end::real as "score!"
+        from policy
+        join candidate using (id)
+        join person on policy.holder_id = person.id
+        order by 5 desc, start_date desc, policy.id desc
+        limit $2
+        "#,
+        key,
+        limit,
+        CANDIDATE_LIMIT,
+        tenant_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                SearchRow {
+                    id: row.id,
+                    label: row.label,
+                    detail: Some(row.holder_name),
+                    score: row.score,
+                },
+                row.policy_type,
+            )
+        })
+        .collect())
+}
+
+fn hits(rows: Vec<SearchRow>, route: &str) -> Vec<SearchHit> {
+    rows.into_iter()
+        .map(|row| SearchHit {
+            link: format!("/{}/{}", route, row.id),
+            id: row.id,
+            label: row.label,
+            detail: row.detail,
+            score: row.score,
+        })
+        .collect()
+}
+
+/// Searches people, cars and policies at once for a single string such as
+/// a surname, phone, plate or policy number. Each group is ranked on its
+/// own: exact identifier matches first, then prefixes, substrings and
+/// similar names.
+#[utoipa::path(
+    get,
+    path = "/search",
+    tag = "search",
+    params(SearchQuery),
+    responses((status = 200, body = SearchResponse))
+)]
+pub async fn search(
+    State(pool): State<PgPool>,
+    Extension(tenants): Extension<UserTenants>,
+    Query(query): Query<SearchQuery>,
+) -> AppResult<Json<SearchResponse>> {
+    let text = query.q.trim().to_lowercase();
+    if text.chars().count() < MIN_QUERY_CHARS {
+        return Ok(Json(SearchResponse::default()));
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let key = ident_key(&text);
+    let digits: String = text.chars().filter(|c| c.is_ascii_digit()).collect();
+    let digits = if digits.len() >= MIN_PHONE_DIGITS {
+        digits
+    } else {
+        String::new()
+    };
+
+    // Unlike other queries these filter by tenant themselves and run with
+    // the owner's access. Under row-level security Postgres uses no index for
+    // conditions that are not leakproof, such as `like` and `<%`, so every
+    // search would read the whole tables.
+    let tenant_id = tenants.active;
+    let (people, cars, policies) = tenant::unrestricted(async {
+        tokio::try_join!(
+            search_people(&pool, tenant_id, &text, &digits, limit),
+            search_cars(&pool, tenant_id, &text, &key, limit),
+            search_policies(&pool, tenant_id, &key, limit),
+        )
+    })
+    .await?;
+
+    let cars = cars
+        .into_iter()
+        .map(|(row, policy_filter)| SearchHit {
+            link: format!(
+                "/policies?car={}",
+                utf8_percent_encode(&policy_filter, NON_ALPHANUMERIC)
+            ),
+            id: row.id,
+            label: row.label,
+            detail: row.detail,
+            score: row.score,
+        })
+        .collect();
+
+    let policies = policies
+        .into_iter()
+        .map(|(row, policy_type)| SearchHit {
+            link: format!("/policies/{}", row.id),
+            id: row.id,
+            label: row.label,
+            detail: row
+                .detail
+                .map(|holder| format!("{} · {}", type_label(policy_type), holder)),
+            score: row.score,
+        })
+        .collect();
+
+    Ok(Json(SearchResponse {
+        people: hits(people, "people"),
+        cars,
+        policies,
+    }))
+}
@@ -0,0 +1,270 @@
+use std::time::{Duration, Instant};
+
+use axum::http::{Method, StatusCode};
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::tenant::TENANT_ID;
+use crate::testing::fixtures::{self, CarBuilder, PersonBuilder, PolicyBuilder};
+use crate::testing::{DEFAULT_TENANT, TEST_USER, TestApp};
+
+async fn search(app: &TestApp, q: &str) -> Value {
+    app.get(&format!("/search?q={}", q))
+        .await
+        .expect(StatusCode::OK)
+}
+
+fn labels(hits: &Value) -> Vec<&str> {
+    hits.as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["label"].as_str().unwrap())
+        .collect()
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn groups(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let holder = PersonBuilder::new().with("last_name", "Шевченко");
+    PolicyBuilder::green_card(&insurer)
+        .with("holder", holder.as_new())
+        .with("number", "7654321")
+        .with("car", CarBuilder::new().with("plate", "KA7777KA").as_new())
+        .create(&app)
+        .await;
+
+    let found = search(&app, "Шевченко").await;
+    assert_eq!(labels(&found["people"]), ["Шевченко Тарас Петрович"]);
+    assert_eq!(found["cars"], Value::Array(vec![]));
+    assert_eq!(found["policies"], Value::Array(vec![]));
+
+    // Typed with Cyrillic look-alikes
+    let found = search(&app, "КА7777КА").await;
+    assert_eq!(labels(&found["cars"]), ["Volkswagen Golf"]);
+    assert_eq!(found["people"], Value::Array(vec![]));
+
+    let found = search(&app, "7654321").await;
+    assert_eq!(labels(&found["policies"]), ["AM 7654321"]);
+    assert_eq!(
+        found["policies"][0]["detail"],
+        "Зелена картка · Шевченко Тарас"
+    );
+
+    assert_eq!(search(&app, "ш").await["people"], Value::Array(vec![]));
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn ranking(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    for last_name in ["Дуковаль", "Ковальчук", "Коваль"] {
+        PersonBuilder::new()
+            .with("last_name", last_name)
+            .create(&app)
+            .await;
+    }
+    let by_tax_number = PersonBuilder::new()
+        .with("last_name", "Мельник")
+        .with("tax_number", "2999999999")
+        .create(&app)
+        .await;
+
+    // Name prefixes before substrings
+    let found = search(&app, "коваль").await;
+    assert_eq!(
+        labels(&found["people"]),
+        [
+            "Коваль Тарас Петрович",
+            "Ковальчук Тарас Петрович",
+            "Дуковаль Тарас Петрович"
+        ]
+    );
+
+    // An exact identifier wins
+    let found = search(&app, "2999999999").await;
+    assert_eq!(found["people"][0]["id"], by_tax_number["id"]);
+    assert_eq!(found["people"][0]["score"], 3.0);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn links(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let holder = PersonBuilder::new().with("last_name", "Шевченко");
+    let policy = PolicyBuilder::osago(&insurer)
+        .with("holder", holder.as_new())
+        .with(
+            "car",
+            CarBuilder::new().with("plate", "АА 1234 ВВ").as_new(),
+        )
+        .create(&app)
+        .await;
+
+    let found = search(&app, "Шевченко").await;
+    assert_eq!(
+        found["people"][0]["link"],
+        format!("/people/{}", policy["holder_id"])
+    );
+
+    let found = search(&app, policy["number"].as_str().unwrap()).await;
+    assert_eq!(
+        found["policies"][0]["link"],
+        format!("/policies/{}", policy["id"])
+    );
+
+    // Cars have no page, their link lists the policies with the car
+    let found = search(&app, "AA1234BB").await;
+    let link = found["cars"][0]["link"].as_str().unwrap();
+    assert!(link.starts_with("/policies?car="), "{}", link);
+    let page = app
+        .get(
+            &link
+                .replacen("/policies", "/policies?", 1)
+                .replacen("?car", "car", 1),
+        )
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"][0]["id"], policy["id"]);
+}
+
+/// Search reads with the owner's access, so it has to keep to the tenant by
+/// itself.
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn only_the_active_tenant(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let holder = PersonBuilder::new().with("last_name", "Шевченко");
+    PolicyBuilder::osago(&insurer)
+        .with("holder", holder.as_new())
+        .with("number", "7654321")
+        .with("car", CarBuilder::new().with("plate", "KA7777KA").as_new())
+        .create(&app)
+        .await;
+    let partner = sqlx::query_scalar!("insert into tenant (name) values ('Partner') returning id")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    sqlx::query!(
+        "insert into tenant_user (tenant_id, user_email) values ($1, $2)",
+        partner,
+        TEST_USER
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let partner = partner.to_string();
+    for q in ["Шевченко", "KA7777KA", "7654321"] {
+        let uri = format!("/search?q={}", q);
+        let found = app
+            .send(Method::GET, &uri, &[(TENANT_ID, &partner)], None)
+            .await
+            .expect(StatusCode::OK);
+        for group in ["people", "cars", "policies"] {
+            assert_eq!(found[group], Value::Array(vec![]), "{} {}", q, group);
+        }
+        let found = search(&app, q).await;
+        assert_ne!(
+            [&found["people"], &found["cars"], &found["policies"]],
+            [&Value::Array(vec![]); 3],
+            "{}",
+            q
+        );
+    }
+}
+
+/// Fills the tenant with `count` people, cars and policies, with common
+/// surnames and plates so that broad queries match thousands of rows.
+async fn seed(pool: &PgPool, count: i32) {
+    sqlx::query(
+        r#"
+        insert into person (first_name, last_name, patronymic_name, sex, birth_date, tax_number, phone, email, status, tenant_id)
+        select
+            (array['Тарас', 'Олена', 'Андрій', 'Наталія', 'Петро'])[1 + i % 5],
+            (array['Шевченко', 'Коваленко', 'Бондаренко', 'Ткаченко', 'Кравченко',
+                   'Олійник', 'Шевчук', 'Поліщук', 'Лисенко', 'Мельник'])[1 + i % 10] || (i / 10)::text,
+            'Петрович', 'm', '1980-01-01'::date + i % 10000,
+            (2000000000 + i)::text, '+38050' || lpad(i::text, 7, '0'), 'p' || i || '@insure.test',
+            'active', $2
+        from generate_series(1, $1) as i
+        "#,
+    )
+    .bind(count)
+    .bind(DEFAULT_TENANT)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        insert into car (chassis, make, model, registration, plate, year, engine_displacement_litres, mileage_km, unladen_weight, laden_weight, seats, tenant_id)
+        select
+            'WVWZZZ1JZ' || lpad(i::text, 8, '0'), 'Volkswagen', (array['Golf', 'Passat', 'Polo'])[1 + i % 3],
+            'CXI' || i, (array['AA', 'KA', 'BC', 'AI'])[1 + i % 4] || lpad((i % 10000)::text, 4, '0') || 'BB',
+            2015, 2, 100000, 1300, 1800, 5, $2
+        from generate_series(1, $1) as i
+        "#,
+    )
+    .bind(count)
+    .bind(DEFAULT_TENANT)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        insert into insurer (name, tenant_id) values ('ТАС', $1);
+        "#,
+    )
+    .bind(DEFAULT_TENANT)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    sqlx::query(
+        r#"
+        insert into policy (type, holder_id, insurer_id, series, number, start_date, end_date, status, tenant_id)
+        select 'osago', person.id, insurer.id, 'ЕР', lpad(person.id::text, 9, '0'), '2025-01-01', '2026-01-01', 'active', $1
+        from person, insurer
+        "#,
+    )
+    .bind(DEFAULT_TENANT)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    sqlx::query("analyze").execute(pool).await.unwrap();
+}
+
+/// Search has to answer within 100 ms with 100k people, cars and policies.
+/// Timed through the API, taking the best of a few runs per query to leave
+/// out a cold cache. Timings mean nothing next to the other tests, so CI
+/// runs it on its own.
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+#[ignore = "timing, run alone with `cargo test -- --ignored`"]
+async fn fast_on_100k_rows(pool: PgPool) {
+    seed(&pool, 100_000).await;
+    let app = TestApp::new(pool).await;
+
+    for q in [
+        "шевченко",
+        "shevchenko",
+        "шевченко1234",
+        "2000054321",
+        "0501234",
+        "AA1234",
+        "WVWZZZ1JZ0005",
+        "golf",
+        "000012345",
+        "ко",
+    ] {
+        let mut best = Duration::MAX;
+        for _ in 0..3 {
+            let started = Instant::now();
+            search(&app, q).await;
+            best = best.min(started.elapsed());
+        }
+        assert!(best < Duration::from_millis(100), "'{}' took {:?}", q, best);
+    }
+}
@@ -0,0 +1,14 @@
+use axum::{Extension, Json};
+
+use crate::tenant::UserTenants;
+
+/// Tenants the user can switch between with `X-Tenant-Id`.
+#[utoipa::path(
+    get,
+    path = "/tenants",
+    tag = "tenants",
+    responses((status = 200, body = UserTenants))
+)]
+pub async fn get_tenants(Extension(tenants): Extension<UserTenants>) -> Json<UserTenants> {
+    Json(tenants)
+}
@@ -0,0 +1,156 @@
+use axum::http::{HeaderName, Method, StatusCode};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use crate::idempotency::{IDEMPOTENCY_KEY, IDEMPOTENT_REPLAYED};
+use crate::tenant::{self, TENANT_ID};
+use crate::testing::fixtures::{self, PersonBuilder, PolicyBuilder, existing};
+use crate::testing::{DEFAULT_TENANT, TEST_USER, TestApp, TestResponse};
+
+/// Creates a tenant with `TEST_USER` as a member, returning its id.
+async fn partner(app: &TestApp) -> i32 {
+    let id = sqlx::query_scalar!("insert into tenant (name) values ('Partner') returning id")
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    sqlx::query!(
+        "insert into tenant_user (tenant_id, user_email) values ($1, $2)",
+        id,
+        TEST_USER
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    id
+}
+
+fn as_tenant(id: &str) -> [(HeaderName, &str); 1] {
+    [(TENANT_ID, id)]
+}
+
+async fn get_as(app: &TestApp, tenant: &str, uri: &str) -> TestResponse {
+    app.send(Method::GET, uri, &as_tenant(tenant), None).await
+}
+
+async fn post_as(app: &TestApp, tenant: &str, uri: &str, body: Value) -> TestResponse {
+    app.send(Method::POST, uri, &as_tenant(tenant), Some(body))
+        .await
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn tenants_see_only_their_data(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let partner_id = partner(&app).await;
+    let partner = partner_id.to_string();
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    fixtures::agent(&app, "Олена Іваненко").await;
+    let holder = PersonBuilder::new();
+    let person = holder.create(&app).await;
+    PolicyBuilder::osago(&insurer).create(&app).await;
+    let people = app.get("/people").await.expect(StatusCode::OK);
+
+    let uri = format!("/people/{}", person["id"]);
+    get_as(&app, &partner, &uri)
+        .await
+        .expect(StatusCode::NOT_FOUND);
+    for uri in ["/people", "/policies", "/agents"] {
+        let list = get_as(&app, &partner, uri).await.expect(StatusCode::OK);
+        assert_eq!(list["total"], 0, "{}", uri);
+    }
+    let insurers = get_as(&app, &partner, "/insurers").await;
+    assert_eq!(insurers.expect(StatusCode::OK), json!([]));
+
+    // Tax numbers and insurer names only need to be unique within a tenant
+    post_as(&app, &partner, "/people", holder.body())
+        .await
+        .expect(StatusCode::CREATED);
+    let partner_insurer = post_as(&app, &partner, "/insurers", json!({ "name": "ТАС" }))
+        .await
+        .expect(StatusCode::CREATED)["id"]
+        .clone();
+
+    // Rows of the other tenant cannot be linked to by id either
+    let car = app.get("/cars/search?q=").await.expect(StatusCode::OK)["items"][0]["id"].clone();
+    let agent = app.get("/agents").await.expect(StatusCode::OK)["items"][0]["id"].clone();
+    for (field, value) in [
+        ("holder", existing(&person["id"])),
+        ("car", existing(&car)),
+        ("members", json!([existing(&person["id"])])),
+        ("agent_ids", json!([agent])),
+    ] {
+        let policy = match field {
+            "members" => PolicyBuilder::medassistance(&partner_insurer),
+            _ => PolicyBuilder::green_card(&partner_insurer),
+        };
+        let refused = post_as(
+            &app,
+            &partner,
+            "/policies",
+            policy.with(field, value).body(),
+        )
+        .await;
+        refused.expect(StatusCode::NOT_FOUND);
+    }
+
+    let unchanged = app.get("/people").await.expect(StatusCode::OK);
+    assert_eq!(unchanged, people);
+
+    // Enforced by the database, whatever the query
+    let count = tenant::scope(
+        partner_id,
+        sqlx::query_scalar!(r#"select count(*) as "count!" from person"#).fetch_one(&app.pool),
+    )
+    .await
+    .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn active_tenant(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+
+    let tenants = app.get("/tenants").await.expect(StatusCode::OK);
+    assert_eq!(tenants["active"], DEFAULT_TENANT);
+    assert_eq!(tenants["tenants"].as_array().unwrap().len(), 1);
+
+    let partner = partner(&app).await.to_string();
+    let tenants = get_as(&app, &partner, "/tenants")
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(tenants["active"].to_string(), partner);
+    assert_eq!(tenants["tenants"][1]["name"], "Partner");
+
+    get_as(&app, "999", "/people")
+        .await
+        .expect(StatusCode::FORBIDDEN);
+    get_as(&app, "Partner", "/people")
+        .await
+        .expect(StatusCode::BAD_REQUEST);
+
+    sqlx::query!("delete from tenant_user")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    app.get("/people").await.expect(StatusCode::UNAUTHORIZED);
+}
+
+async fn create_insurer(app: &TestApp, tenant: &str) -> TestResponse {
+    let headers = [(IDEMPOTENCY_KEY, "insurer-1"), (TENANT_ID, tenant)];
+    let body = json!({ "name": "ТАС" });
+    app.send(Method::POST, "/insurers", &headers, Some(body))
+        .await
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn idempotency_keys_per_tenant(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let partner = partner(&app).await.to_string();
+
+    let first = create_insurer(&app, "1").await.expect(StatusCode::CREATED);
+    let other = create_insurer(&app, &partner).await;
+    assert!(other.headers.get(IDEMPOTENT_REPLAYED).is_none());
+    assert_ne!(other.expect(StatusCode::CREATED)["id"], first["id"]);
+
+    let retry = create_insurer(&app, &partner).await;
+    assert_eq!(retry.headers[IDEMPOTENT_REPLAYED], "true");
+}
@@ -0,0 +1,168 @@
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::error::AppResult;
+use crate::shared::page::{Page, PageQuery};
+use crate::webhook::{DeliveryStatus, EventType};
+
+#[derive(Serialize, ToSchema)]
+pub struct Delivery {
+    pub id: i32,
+    pub event_id: i32,
+    pub event_type: EventType,
+    pub event_created_at: DateTime<Utc>,
+    pub subscription_id: i32,
+    pub url: String,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub last_response_status: Option<i32>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Attempt {
+    pub id: i32,
+    pub attempted_at: DateTime<Utc>,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct DeliveryQuery {
+    pub subscription_id: Option<i32>,
+    pub event_id: Option<i32>,
+    pub event_type: Option<EventType>,
+    pub status: Option<DeliveryStatus>,
+}
+
+/// The delivery log, newest events first, with the outcome of the last attempt.
+#[utoipa::path(
+    get,
+    path = "/webhooks/deliveries",
+    tag = "webhooks",
+    params(DeliveryQuery, PageQuery),
+    responses((status = 200, body = Page<Delivery>))
+)]
+pub async fn get_deliveries(
+    State(pool): State<PgPool>,
+    Query(query): Query<DeliveryQuery>,
+    Query(page): Query<PageQuery>,
+) -> AppResult<Json<Page<Delivery>>> {
+    let limit = page.limit(50);
+    let offset = page.offset();
+
+    let items = sqlx::query_as!(
+        Delivery,
+        r#"
+        select
+            d.id,
+            d.event_id,
+            e.event_type as "event_type: EventType",
+            e.created_at as event_created_at,
+            d.subscription_id,
+            s.url,
+            d.status as "status: DeliveryStatus",
+            d.attempts,
+            d.next_attempt_at,
+            d.delivered_at,
+            a.response_status as "last_response_status?",
+            a.error as "last_error?"
+        from webhook_delivery d
+        join webhook_event e on e.id = d.event_id
+        join webhook_subscription s on s.id = d.subscription_id
+        left join lateral (
+            select response_status, error
+            from webhook_attempt
+            where delivery_id = d.id
+            order by attempted_at desc, id desc
+            limit 1
+        ) a on true
+        where
+            e.tenant_id = current_tenant()
+            and ($1::int is null or d.subscription_id = $1)
+            and ($2::int is null or d.event_id = $2)
+            and ($3::webhook_event_type is null or e.event_type = $3)
+            and ($4::webhook_delivery_status is null or d.status = $4)
+        order by e.created_at desc, d.id desc
+        limit $5
+        offset $6
+        "#,
+        query.subscription_id,
+        query.event_id,
+        query.event_type as Option<EventType>,
+        query.status as Option<DeliveryStatus>,
+        limit,
+        offset
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"
+        select count(*) as "count!"
+        from webhook_delivery d
+        join webhook_event e on e.id = d.event_id
+        where
+            e.tenant_id = current_tenant()
+            and ($1::int is null or d.subscription_id = $1)
+            and ($2::int is null or d.event_id = $2)
+            and ($3::webhook_event_type is null or e.event_type = $3)
+            and ($4::webhook_delivery_status is null or d.status = $4)
+        "#,
+        query.subscription_id,
+        query.event_id,
+        query.event_type as Option<EventType>,
+        query.status as Option<DeliveryStatus>
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(Page {
+        items,
+        total,
+        limit,
+        offset,
+    }))
+}
+
+/// Every attempt to send a delivery, newest first.
+#[utoipa::path(
+    get,
+    path = "/webhooks/deliveries/{id}/attempts",
+    tag = "webhooks",
+    params(("id" = i32, Path, description = "Delivery id")),
+    responses((status = 200, body = Vec<Attempt>))
+)]
+pub async fn get_attempts(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+) -> AppResult<Json<Vec<Attempt>>> {
+    let attempts = sqlx::query_as!(
+        Attempt,
+        r#"
+        select id, attempted_at, response_status, error, duration_ms
+        from webhook_attempt
+        where
+            delivery_id = $1
+            and delivery_id in (
+                select d.id
+                from webhook_delivery d
+                join webhook_event e on e.id = d.event_id
+                where e.tenant_id = current_tenant()
+            )
+        order by attempted_at desc, id desc
+        "#,
+        id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(attempts))
+}
@@ -0,0 +1,25 @@
+pub mod deliveries;
+pub mod replay;
+pub mod subscriptions;
+#[cfg(test)]
+mod tests;
+
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+pub fn router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(
+            subscriptions::get_subscriptions,
+            subscriptions::create_subscription
+        ))
+        .routes(routes!(
+            subscriptions::update_subscription,
+            subscriptions::delete_subscription
+        ))
+        .routes(routes!(deliveries::get_deliveries))
+        .routes(routes!(deliveries::get_attempts))
+        .routes(routes!(replay::replay_events))
+}
@@ -0,0 +1,74 @@
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::error::{AppError, AppResult};
+
+/// Which events to send again: one event, or everything since a moment,
+/// optionally to a single subscription.
+#[derive(Deserialize, ToSchema)]
+pub struct ReplayRequest {
+    pub event_id: Option<i32>,
+    pub since: Option<DateTime<Utc>>,
+    pub subscription_id: Option<i32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReplayResponse {
+    pub queued: u64,
+}
+
+/// Queues the events again for every active subscription to them, including
+/// ones delivered already or given up on. Subscriptions created after an event
+/// receive it too.
+#[utoipa::path(
+    post,
+    path = "/webhooks/replay",
+    tag = "webhooks",
+    responses(
+        (status = 200, body = ReplayResponse),
+        (status = 400, description = "Neither event_id nor since is given")
+    )
+)]
+pub async fn replay_events(
+    State(pool): State<PgPool>,
+    Json(body): Json<ReplayRequest>,
+) -> AppResult<Json<ReplayResponse>> {
+    if body.event_id.is_none() && body.since.is_none() {
+        return Err(AppError::status(
+            StatusCode::BAD_REQUEST,
+            "Specify event_id or since",
+        ));
+    }
+
+    let result = sqlx::query!(
+        r#"
+        insert into webhook_delivery (event_id, subscription_id)
+        select e.id, s.id
+        from webhook_event e
+        join webhook_subscription s on e.event_type = any(s.events)
+        where
+            s.active
+            and s.tenant_id = current_tenant()
+            and e.tenant_id = current_tenant()
+            and ($1::int is null or e.id = $1)
+            and ($2::timestamptz is null or e.created_at >= $2)
+            and ($3::int is null or s.id = $3)
+        on conflict (event_id, subscription_id) do update
+        set status = 'pending', attempts = 0, next_attempt_at = now(), delivered_at = null
+        "#,
+        body.event_id,
+        body.since,
+        body.subscription_id
+    )
+    .execute(&pool)
+    .await?;
+
+    Ok(Json(ReplayResponse {
+        queued: result.rows_affected(),
+    }))
+}
@@ -0,0 +1,199 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use crate::error::{AppError, AppResult};
+use crate::webhook::EventType;
+
+#[derive(Serialize, ToSchema)]
+pub struct Subscription {
+    pub id: i32,
+    pub url: String,
+    /// Key for the `X-Webhook-Signature` HMAC
+    pub secret: String,
+    pub events: Vec<EventType>,
+    pub active: bool,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SubscriptionBody {
+    pub url: String,
+    /// Generated when missing on create, kept when missing on update
+    pub secret: Option<String>,
+    pub events: Vec<EventType>,
+    #[serde(default = "active_by_default")]
+    pub active: bool,
+    pub description: Option<String>,
+}
+
+fn active_by_default() -> bool {
+    true
+}
+
+impl SubscriptionBody {
+    fn validate(&self) -> AppResult<()> {
+        let url = reqwest::Url::parse(self.url.trim())
+            .map_err(|_| AppError::status(StatusCode::BAD_REQUEST, "Invalid webhook URL"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(AppError::status(
+                StatusCode::BAD_REQUEST,
+                "Webhook URL must be http or https",
+            ));
+        }
+        if self.events.is_empty() {
+            return Err(AppError::status(
+                StatusCode::BAD_REQUEST,
+                "Subscribe to at least one event",
+            ));
+        }
+        Ok(())
+    }
+
+    fn secret(&self) -> Option<String> {
+        self.secret
+            .as_deref()
+            .map(str::trim)
+            .filter(|secret| !secret.is_empty())
+            .map(str::to_owned)
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    responses((status = 200, body = Vec<Subscription>))
+)]
+pub async fn get_subscriptions(State(pool): State<PgPool>) -> AppResult<Json<Vec<Subscription>>> {
+    let subscriptions = sqlx::query_as!(
+        Subscription,
+        r#"
+        select id, url, secret, events as "events: Vec<EventType>", active, description, created_at
+        from webhook_subscription
+        where tenant_id = current_tenant()
+        order by id
+        "#
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(subscriptions))
+}
+
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 201, body = Subscription),
+        (status = 400, description = "Invalid URL or no events")
+    )
+)]
+pub async fn create_subscription(
+    State(pool): State<PgPool>,
+    Json(body): Json<SubscriptionBody>,
+) -> AppResult<(StatusCode, Json<Subscription>)> {
+    body.validate()?;
+    let secret = body.secret().unwrap_or_else(|| {
+        let bytes: [u8; 32] = rand::rng().random();
+        hex::encode(bytes)
+    });
+
+    let subscription = sqlx::query_as!(
+        Subscription,
+        r#"
+        insert into webhook_subscription (url, secret, events, active, description)
+        values ($1, $2, $3, $4, $5)
+        returning id, url, secret, events as "events: Vec<EventType>", active, description, created_at
+        "#,
+        body.url.trim(),
+        secret,
+        &body.events as &[EventType],
+        body.active,
+        body.description
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(subscription)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 200, body = Subscription),
+        (status = 400, description = "Invalid URL or no events"),
+        (status = 404, description = "No such subscription")
+    )
+)]
+pub async fn update_subscription(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+    Json(body): Json<SubscriptionBody>,
+) -> AppResult<Json<Subscription>> {
+    body.validate()?;
+
+    let subscription = sqlx::query_as!(
+        Subscription,
+        r#"
+        update webhook_subscription
+        set
+            url = $2,
+            secret = coalesce($3, secret),
+            events = $4,
+            active = $5,
+            description = $6
+        where id = $1 and tenant_id = current_tenant()
+        returning id, url, secret, events as "events: Vec<EventType>", active, description, created_at
+        "#,
+        id,
+        body.url.trim(),
+        body.secret(),
+        &body.events as &[EventType],
+        body.active,
+        body.description
+    )
+    .fetch_optional(&pool)
+    .await?;
+
+    subscription.map(Json).ok_or(AppError::not_found())
+}
+
+/// Removes the subscription along with its delivery log.
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = i32, Path)),
+    responses(
+        (status = 204),
+        (status = 404, description = "No such subscription")
+    )
+)]
+pub async fn delete_subscription(
+    State(pool): State<PgPool>,
+    Path(id): Path<i32>,
+) -> AppResult<StatusCode> {
+    let result = sqlx::query!(
+        "delete from webhook_subscription where id = $1 and tenant_id = current_tenant()",
+        id
+    )
+    .execute(&pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::not_found());
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
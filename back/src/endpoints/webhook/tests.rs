@@ -0,0 +1,266 @@
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::http::{HeaderMap, Method, StatusCode, header};
+use axum::routing::post;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+
+use crate::testing::TestApp;
+use crate::testing::fixtures::{self, CarBuilder, PolicyBuilder, existing};
+use crate::webhook::delivery::{self, signature};
+
+/// Nothing listens on the discard port, so deliveries to it fail at once.
+const NOWHERE: &str = "http://127.0.0.1:9/hook";
+
+async fn subscribe(app: &TestApp, url: &str, events: &[&str]) -> Value {
+    app.post("/webhooks", json!({ "url": url, "events": events }))
+        .await
+        .expect(StatusCode::CREATED)
+}
+
+async fn deliveries(app: &TestApp, query: &str) -> Vec<Value> {
+    let page = app
+        .get(&format!("/webhooks/deliveries?{}", query))
+        .await
+        .expect(StatusCode::OK);
+    page["items"].as_array().unwrap().clone()
+}
+
+async fn events(app: &TestApp) -> Vec<(String, Value)> {
+    sqlx::query!(
+        r#"
+        select event_type::text as "event_type!", payload
+        from webhook_event
+        order by id
+        "#
+    )
+    .fetch_all(&app.pool)
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|row| (row.event_type, row.payload))
+    .collect()
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn policy_changes_queue_deliveries(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let policies = subscribe(
+        &app,
+        NOWHERE,
+        &["policy.created", "policy.updated", "policy.status_changed"],
+    )
+    .await;
+    let people = subscribe(&app, NOWHERE, &["person.created"]).await;
+    let mut paused = subscribe(&app, NOWHERE, &["policy.created"]).await;
+    paused["active"] = json!(false);
+    app.put(&format!("/webhooks/{}", paused["id"]), paused.clone())
+        .await
+        .expect(StatusCode::OK);
+
+    let policy = PolicyBuilder::osago(&insurer);
+    let created = policy.create(&app).await;
+    let events_after_create = events(&app).await;
+    let types: Vec<&str> = events_after_create
+        .iter()
+        .map(|(event_type, _)| event_type.as_str())
+        .collect();
+    assert_eq!(types, ["person.created", "policy.created"]);
+    assert_eq!(events_after_create[1].1["id"], created["id"]);
+
+    let queued = deliveries(&app, "event_type=policy.created").await;
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0]["subscription_id"], policies["id"]);
+    assert_eq!(queued[0]["status"], "pending");
+    assert_eq!(queued[0]["attempts"], 0);
+    let queued = deliveries(&app, &format!("subscription_id={}", people["id"])).await;
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0]["event_type"], "person.created");
+
+    let uri = format!("/policies/{}", created["id"]);
+    let stored = app.get(&uri).await.expect(StatusCode::OK);
+    let mut body = policy.with("status", "Cancelled").body();
+    body["holder"] = existing(&stored["holder"]["id"]);
+    body["car"] = existing(&stored["car"]["id"]);
+    app.put(&uri, body).await.expect(StatusCode::OK);
+
+    let all = events(&app).await;
+    let (event_type, change) = &all[3];
+    assert_eq!(all[2].0, "policy.updated");
+    assert_eq!(all[2].1["status"], "Cancelled");
+    assert_eq!(event_type, "policy.status_changed");
+    assert_eq!(change["previous_status"], "Active");
+    assert_eq!(change["status"], "Cancelled");
+    assert_eq!(
+        deliveries(&app, &format!("subscription_id={}", policies["id"]))
+            .await
+            .len(),
+        3
+    );
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn refused_updates_publish_nothing(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    subscribe(&app, NOWHERE, &["policy.updated", "policy.status_changed"]).await;
+    let policy = PolicyBuilder::osago(&insurer);
+    let created = policy.create(&app).await;
+    let uri = format!("/policies/{}", created["id"]);
+    let stored = app.get(&uri).await.expect(StatusCode::OK);
+    let before = events(&app).await.len();
+
+    let mut body = policy.with("status", "Stopped").body();
+    body["holder"] = existing(&stored["holder"]["id"]);
+    body["car"] = existing(&stored["car"]["id"]);
+    app.send(
+        Method::PUT,
+        &uri,
+        &[(header::IF_MATCH, "\"7\"")],
+        Some(body.clone()),
+    )
+    .await
+    .expect(StatusCode::CONFLICT);
+
+    // Fails after the policy row was updated, so only the rollback undoes it
+    let mut car = CarBuilder::new().as_new();
+    car["kind"] = json!("ExistingWithUpdates");
+    car["id"] = stored["car"]["id"].clone();
+    car["version"] = json!(0);
+    body["car"] = car;
+    app.put(&uri, body).await.expect(StatusCode::CONFLICT);
+
+    assert_eq!(events(&app).await.len(), before);
+    assert!(deliveries(&app, "").await.is_empty());
+    let after = app.get(&uri).await.expect(StatusCode::OK);
+    assert_eq!(after["status"], "Active");
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn replay(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let first = subscribe(&app, NOWHERE, &["policy.created"]).await;
+    PolicyBuilder::osago(&insurer).create(&app).await;
+    let queued = deliveries(&app, "").await;
+    let event_id = queued[0]["event_id"].clone();
+
+    app.post("/webhooks/replay", json!({}))
+        .await
+        .expect(StatusCode::BAD_REQUEST);
+
+    sqlx::query!("update webhook_delivery set status = 'failed', attempts = 12")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let replayed = app
+        .post("/webhooks/replay", json!({ "event_id": event_id }))
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(replayed["queued"], 1);
+    let queued = deliveries(&app, "").await;
+    assert_eq!(queued[0]["status"], "pending");
+    assert_eq!(queued[0]["attempts"], 0);
+
+    // A subscription made after the event gets it when replayed to it alone
+    let second = subscribe(&app, NOWHERE, &["policy.created"]).await;
+    let replayed = app
+        .post(
+            "/webhooks/replay",
+            json!({ "since": "2000-01-01T00:00:00Z", "subscription_id": second["id"] }),
+        )
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(replayed["queued"], 1);
+    assert_eq!(
+        deliveries(&app, &format!("subscription_id={}", second["id"]))
+            .await
+            .len(),
+        1
+    );
+    assert_eq!(
+        deliveries(&app, &format!("subscription_id={}", first["id"]))
+            .await
+            .len(),
+        1
+    );
+}
+
+#[derive(Clone, Default)]
+struct Received(Arc<Mutex<Vec<(HeaderMap, String)>>>);
+
+/// Accepts webhooks on `/ok` and refuses them on `/down`, keeping what it got.
+async fn receiver() -> (String, Received) {
+    let received = Received::default();
+    let log = received.clone();
+    let router = Router::new()
+        .route(
+            "/ok",
+            post(move |headers: HeaderMap, body: String| async move {
+                log.0.lock().unwrap().push((headers, body));
+                StatusCode::NO_CONTENT
+            }),
+        )
+        .route("/down", post(|| async { StatusCode::SERVICE_UNAVAILABLE }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    (format!("http://{}", address), received)
+}
+
+#[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+async fn deliveries_and_attempts(pool: PgPool) {
+    let app = TestApp::new(pool).await;
+    let insurer = fixtures::insurer(&app, "ТАС").await;
+    let (base, received) = receiver().await;
+    let ok = subscribe(&app, &format!("{}/ok", base), &["policy.created"]).await;
+    let down = subscribe(&app, &format!("{}/down", base), &["policy.created"]).await;
+    let created = PolicyBuilder::osago(&insurer).create(&app).await;
+
+    let attempted = delivery::run(&app.pool, &reqwest::Client::new())
+        .await
+        .unwrap();
+    assert_eq!(attempted, 2);
+
+    let received = received.0.lock().unwrap().clone();
+    assert_eq!(received.len(), 1);
+    let (headers, body) = &received[0];
+    let timestamp: i64 = headers["x-webhook-timestamp"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(
+        headers["x-webhook-signature"],
+        signature(ok["secret"].as_str().unwrap(), timestamp, body)
+    );
+    assert_eq!(headers["x-webhook-event"], "policy.created");
+    let body: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(body["type"], "policy.created");
+    assert_eq!(body["data"]["id"], created["id"]);
+
+    let delivered = deliveries(&app, "status=delivered").await;
+    assert_eq!(delivered.len(), 1);
+    assert_eq!(delivered[0]["subscription_id"], ok["id"]);
+    assert_eq!(delivered[0]["last_response_status"], 204);
+    assert_eq!(delivered[0]["last_error"], Value::Null);
+
+    let retrying = deliveries(&app, "status=pending").await;
+    assert_eq!(retrying.len(), 1);
+    assert_eq!(retrying[0]["subscription_id"], down["id"]);
+    assert_eq!(retrying[0]["attempts"], 1);
+    assert_eq!(retrying[0]["last_response_status"], 503);
+
+    let attempts = app
+        .get(&format!(
+            "/webhooks/deliveries/{}/attempts",
+            retrying[0]["id"]
+        ))
+        .await
+        .expect(StatusCode::OK);
+    assert_eq!(attempts.as_array().unwrap().len(), 1);
+    assert_eq!(attempts[0]["response_status"], 503);
+    assert!(attempts[0]["error"].as_str().unwrap().contains("503"));
+}
@@ -1,11 +1,27 @@
 use axum::{
+    Json,
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::request_id;
 
 pub enum AppError {
     Internal(anyhow::Error),
     Status(StatusCode, Option<String>),
+    Json(StatusCode, serde_json::Value),
+}
+
+/// Body of an error without a structured one of its own.
+#[derive(Serialize)]
+struct ErrorBody {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    /// The `X-Request-Id` to look the error up by in the log
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
 }
 
 impl AppError {
@@ -20,17 +36,43 @@ impl AppError {
     pub fn status(code: StatusCode, message: impl Into<String>) -> Self {
         Self::Status(code, Some(message.into()))
     }
+
+    /// Error with a structured body the client can act on.
+    pub fn json(code: StatusCode, body: impl Serialize) -> Self {
+        match serde_json::to_value(body) {
+            Ok(value) => Self::Json(code, value),
+            Err(err) => Self::Internal(err.into()),
+        }
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let request_id = request_id::current();
         match self {
             AppError::Internal(err) => {
                 tracing::error!("{:?}", err);
-                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                let message = Some("Internal server error".to_owned());
+                let body = ErrorBody {
+                    message,
+                    request_id,
+                };
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response()
+            }
+            AppError::Status(code, message) => (
+                code,
+                Json(ErrorBody {
+                    message,
+                    request_id,
+                }),
+            )
+                .into_response(),
+            AppError::Json(code, mut body) => {
+                if let (Value::Object(fields), Some(request_id)) = (&mut body, request_id) {
+                    fields.insert("request_id".to_owned(), request_id.into());
+                }
+                (code, Json(body)).into_response()
             }
-            AppError::Status(code, None) => code.into_response(),
-            AppError::Status(code, Some(msg)) => (code, msg).into_response(),
         }
     }
 }
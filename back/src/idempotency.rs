@@ -0,0 +1,237 @@
+//! `Idempotency-Key` support for POST requests with a JSON body. The first
+//! response to a key is stored with its status and body and replayed to
+//! retries, so a `POST /policies` retried over a flaky connection does not
+//! create the policy twice. Keys belong to the signed-in user and the tenant
+//! the request acts for; reusing one for a different request is refused with
+//! 422. Stored responses expire after
+//! `Config::idempotency_key_ttl`.
+
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{HeaderName, HeaderValue, Method, StatusCode, Uri, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use crate::auth::middleware::AuthUser;
+use crate::config::Config;
+use crate::error::AppError;
+use crate::tenant::UserTenants;
+
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+/// Set on a response that was stored for an earlier request
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+const MAX_KEY_LENGTH: usize = 255;
+/// Same as axum's default limit for JSON bodies
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+struct StoredResponse {
+    request_hash: String,
+    status: Option<i16>,
+    content_type: Option<String>,
+    body: Option<Vec<u8>>,
+}
+
+fn request_hash(method: &Method, uri: &Uri, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str());
+    hasher.update(b" ");
+    hasher.update(uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/"));
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+fn is_json(request: &Request) -> bool {
+    request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+fn replay(status: i16, content_type: Option<String>, body: Vec<u8>) -> Response {
+    let status = u16::try_from(status)
+        .ok()
+        .and_then(|status| StatusCode::from_u16(status).ok())
+        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let mut response = (status, body).into_response();
+    let headers = response.headers_mut();
+    headers.remove(header::CONTENT_TYPE);
+    if let Some(value) = content_type.and_then(|value| HeaderValue::from_str(&value).ok()) {
+        headers.insert(header::CONTENT_TYPE, value);
+    }
+    headers.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+    response
+}
+
+/// Gives up the key, so the request can be retried with it.
+async fn release(pool: &PgPool, email: &str, tenant_id: i32, key: &str) {
+    let released = sqlx::query!(
+        "delete from idempotency_key where user_email = $1 and tenant_id = $2 and key = $3",
+        email,
+        tenant_id,
+        key
+    )
+    .execute(pool)
+    .await;
+    if let Err(err) = released {
+        tracing::error!("Failed to release Idempotency-Key {}: {:?}", key, err);
+    }
+}
+
+pub async fn idempotency_middleware(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<Config>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if request.method() != Method::POST {
+        return Ok(next.run(request).await);
+    }
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY) else {
+        return Ok(next.run(request).await);
+    };
+    // The auth routes run before there is a user to scope keys to
+    let Some(AuthUser { email }) = request.extensions().get::<AuthUser>().cloned() else {
+        return Ok(next.run(request).await);
+    };
+    let Some(tenant_id) = request
+        .extensions()
+        .get::<UserTenants>()
+        .map(|tenants| tenants.active)
+    else {
+        return Ok(next.run(request).await);
+    };
+
+    let key = key
+        .to_str()
+        .ok()
+        .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LENGTH)
+        .ok_or_else(|| {
+            AppError::status(
+                StatusCode::BAD_REQUEST,
+                "Idempotency-Key must be 1 to 255 printable ASCII characters",
+            )
+        })?
+        .to_owned();
+    if !is_json(&request) {
+        return Err(AppError::status(
+            StatusCode::BAD_REQUEST,
+            "Idempotency-Key is only supported on requests with a JSON body",
+        ));
+    }
+
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::Status(StatusCode::PAYLOAD_TOO_LARGE, None))?;
+    let hash = request_hash(&parts.method, &parts.uri, &body);
+
+    sqlx::query!("delete from idempotency_key where expires_at < now()")
+        .execute(&pool)
+        .await?;
+
+    let expires_at = chrono::Utc::now() + config.idempotency_key_ttl;
+    let claimed = sqlx::query!(
+        r#"
+        insert into idempotency_key (user_email, tenant_id, key, request_hash, expires_at)
+        values ($1, $2, $3, $4, $5)
+        on conflict do nothing
+        "#,
+        email,
+        tenant_id,
+        key,
+        hash,
+        expires_at
+    )
+    .execute(&pool)
+    .await?
+    .rows_affected()
+        == 1;
+
+    if !claimed {
+        let stored = sqlx::query_as!(
+            StoredResponse,
+            r#"
+            select request_hash, status, content_type, body
+            from idempotency_key
+            where user_email = $1 and tenant_id = $2 and key = $3
+            "#,
+            email,
+            tenant_id,
+            key
+        )
+        .fetch_optional(&pool)
+        .await?;
+
+        return match stored {
+            Some(stored) if stored.request_hash != hash => Err(AppError::status(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "This Idempotency-Key was already used for a different request",
+            )),
+            Some(StoredResponse {
+                status: Some(status),
+                content_type,
+                body,
+                ..
+            }) => Ok(replay(status, content_type, body.unwrap_or_default())),
+            _ => Err(AppError::status(
+                StatusCode::CONFLICT,
+                "A request with this Idempotency-Key is still being handled",
+            )),
+        };
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    // Failures are not stored, a retry may well succeed
+    if response.status().is_server_error() {
+        release(&pool, &email, tenant_id, &key).await;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(err) => {
+            release(&pool, &email, tenant_id, &key).await;
+            return Err(AppError::Internal(err.into()));
+        }
+    };
+    let content_type = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+
+    let stored = sqlx::query!(
+        r#"
+        update idempotency_key
+        set status = $4, content_type = $5, body = $6
+        where user_email = $1 and tenant_id = $2 and key = $3
+        "#,
+        email,
+        tenant_id,
+        key,
+        parts.status.as_u16() as i16,
+        content_type,
+        body.as_ref()
+    )
+    .execute(&pool)
+    .await;
+    // The request went through, so its response is returned regardless. Until
+    // the key expires, retries get 409 instead of repeating it.
+    if let Err(err) = stored {
+        tracing::error!(
+            "Failed to store response for Idempotency-Key {}: {:?}",
+            key,
+            err
+        );
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
@@ -0,0 +1,133 @@
+//! Log output, as plain text or, with `LOG_FORMAT=json`, one JSON object per
+//! line that carries the fields of the request span. Either way tax numbers,
+//! tokens and other secrets are masked before a line is written, wherever
+//! they come from: a query string in the logged URI, a field of an event or
+//! the debug output of an error.
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::config::LogFormat;
+
+const REDACTED: &str = "[redacted]";
+
+/// `name=value`, `name: "value"` and `"name":"value"`, also with the quotes
+/// escaped as in the JSON output
+static SECRET_FIELD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)\b(tax_number|token|access_token|refresh_token|id_token|client_secret|secret|password|authorization|cookie)(\\?"?\s*[:=]\s*\\?"?)[^\s"&,;)}\\]+"#,
+    )
+    .unwrap()
+});
+/// The code and state of the OAuth callback
+static SECRET_PARAM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"([?&](?:code|state)=)[^&\s"\\]+"#).unwrap());
+static BEARER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\b(bearer\s+)[^\s"\\]+"#).unwrap());
+/// The values in the detail of a unique violation on a key with the tax number,
+/// `Key (tenant_id, tax_number)=(1, 1234567890)`
+static TAX_NUMBER_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(Key \([^)]*\btax_number\b[^)]*\)=\()[^)]*").unwrap());
+
+pub fn init(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into());
+    let layer = tracing_subscriber::fmt::layer().with_writer(|| Redacting(io::stdout()));
+    let registry = tracing_subscriber::registry().with(filter);
+    match format {
+        LogFormat::Text => registry.with(layer).init(),
+        LogFormat::Json => registry
+            .with(
+                layer
+                    .json()
+                    .flatten_event(true)
+                    .with_current_span(true)
+                    .with_span_list(false),
+            )
+            .init(),
+    }
+}
+
+pub fn redact(line: &str) -> Cow<'_, str> {
+    let mut line = Cow::Borrowed(line);
+    for (regex, replacement) in [
+        (&BEARER, "${1}"),
+        (&SECRET_FIELD, "${1}${2}"),
+        (&SECRET_PARAM, "${1}"),
+        (&TAX_NUMBER_KEY, "${1}"),
+    ] {
+        if let Cow::Owned(replaced) =
+            regex.replace_all(&line, format!("{}{}", replacement, REDACTED).as_str())
+        {
+            line = Cow::Owned(replaced);
+        }
+    }
+    line
+}
+
+/// Masks every line on its way to the inner writer. The formatter writes an
+/// event in a single call, so a secret is never split between two.
+struct Redacting<W>(W);
+
+impl<W: Write> Write for Redacting<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let line = String::from_utf8_lossy(buf);
+        self.0.write_all(redact(&line).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_secrets() {
+        let cases = [
+            (
+                r#"uri=/calendar/feed.ics?token=abc123 status=200"#,
+                r#"uri=/calendar/feed.ics?token=[redacted] status=200"#,
+            ),
+            (
+                r#"uri=/auth/callback?code=4/0Ab&state=xyz"#,
+                r#"uri=/auth/callback?code=[redacted]&state=[redacted]"#,
+            ),
+            (
+                r#"{"tax_number":"1234567890","full_name":"Олена"}"#,
+                r#"{"tax_number":"[redacted]","full_name":"Олена"}"#,
+            ),
+            (
+                r#"{"message":"Session { access_token: \"ya29.a0\", expires_in: 3599 }"}"#,
+                r#"{"message":"Session { access_token: \"[redacted]\", expires_in: 3599 }"}"#,
+            ),
+            (
+                r#"Authorization: Bearer ya29.a0"#,
+                r#"Authorization: [redacted] [redacted]"#,
+            ),
+            (
+                r#"detail: Some("Key (tenant_id, tax_number)=(1, 1234567890) already exists.")"#,
+                r#"detail: Some("Key (tenant_id, tax_number)=([redacted]) already exists.")"#,
+            ),
+        ];
+        for (line, redacted) in cases {
+            assert_eq!(redact(line), redacted);
+        }
+    }
+
+    #[test]
+    fn keeps_the_rest() {
+        let line = r#"{"timestamp":"2026-10-19T09:13:00.123456Z","level":"INFO","message":"finished processing request","latency":"12 ms","status":200,"uri":"/people/42?page=2","request_id":"0b7c4a8e-3a52-4f1e-9d3b-5f2a8c1e7d90"}"#;
+        assert_eq!(redact(line), line);
+
+        // Only tax numbers are secret, not every ten digits
+        let line = r#"policy_number=AP1234567890 phone=0501234567 at=1760000000 detail: Some("Key (tenant_id, upper(chassis::text))=(1, WVWZZZ1KZAW000001) already exists.")"#;
+        assert_eq!(redact(line), line);
+    }
+}
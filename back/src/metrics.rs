@@ -0,0 +1,205 @@
+//! Request counts and latencies kept in memory and written in the Prometheus
+//! text exposition format.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct RequestKey {
+    method: String,
+    route: String,
+    status: String,
+}
+
+#[derive(Default)]
+struct RequestStats {
+    count: u64,
+    seconds: f64,
+    /// Cumulative: requests that took at most the matching bound
+    buckets: [u64; BUCKETS.len()],
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    requests: Mutex<BTreeMap<RequestKey, RequestStats>>,
+}
+
+impl Metrics {
+    pub fn observe(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let key = RequestKey {
+            method: method.to_owned(),
+            route: route.to_owned(),
+            status: status.to_string(),
+        };
+        let seconds = elapsed.as_secs_f64();
+
+        let mut requests = self.requests.lock().expect("metrics lock poisoned");
+        let stats = requests.entry(key).or_default();
+        stats.count += 1;
+        stats.seconds += seconds;
+        for (bucket, bound) in stats.buckets.iter_mut().zip(BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+    }
+
+    pub fn write(&self, out: &mut Exposition) {
+        let requests = self.requests.lock().expect("metrics lock poisoned");
+
+        out.describe(
+            "http_requests_total",
+            "counter",
+            "HTTP requests by route and status",
+        );
+        for (key, stats) in requests.iter() {
+            out.sample("http_requests_total", &key.labels(), stats.count);
+        }
+
+        out.describe(
+            "http_request_duration_seconds",
+            "histogram",
+            "HTTP request latency by route and status",
+        );
+        for (key, stats) in requests.iter() {
+            let labels = key.labels();
+            for (bucket, bound) in stats.buckets.iter().zip(BUCKETS) {
+                let le = bound.to_string();
+                let mut labels = labels.clone();
+                labels.push(("le", &le));
+                out.sample("http_request_duration_seconds_bucket", &labels, bucket);
+            }
+            let mut infinity = labels.clone();
+            infinity.push(("le", "+Inf"));
+            out.sample(
+                "http_request_duration_seconds_bucket",
+                &infinity,
+                stats.count,
+            );
+            out.sample("http_request_duration_seconds_sum", &labels, stats.seconds);
+            out.sample("http_request_duration_seconds_count", &labels, stats.count);
+        }
+    }
+}
+
+impl RequestKey {
+    fn labels(&self) -> Vec<(&str, &str)> {
+        vec![
+            ("method", &self.method),
+            ("route", &self.route),
+            ("status", &self.status),
+        ]
+    }
+}
+
+/// Records every routed request under its route template, so `/policies/{id}`
+/// is one series rather than one per policy.
+pub async fn track_metrics(
+    State(metrics): State<Arc<Metrics>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| "unmatched".to_owned());
+
+    let response = next.run(request).await;
+    metrics.observe(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+    response
+}
+
+/// Writer of the Prometheus text format.
+#[derive(Default)]
+pub struct Exposition {
+    out: String,
+}
+
+impl Exposition {
+    pub fn describe(&mut self, name: &str, kind: &str, help: &str) {
+        self.out.push_str(&format!("# HELP {} {}\n", name, help));
+        self.out.push_str(&format!("# TYPE {} {}\n", name, kind));
+    }
+
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            let labels = labels
+                .iter()
+                .map(|(label, value)| format!("{}=\"{}\"", label, escape(value)))
+                .collect::<Vec<_>>();
+            self.out.push_str(&format!("{{{}}}", labels.join(",")));
+        }
+        self.out.push_str(&format!(" {}\n", value));
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+/// Escapes a label value.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_histograms() {
+        let metrics = Metrics::default();
+        metrics.observe("GET", "/policies/{id}", 200, Duration::from_millis(30));
+        metrics.observe("GET", "/policies/{id}", 200, Duration::from_millis(300));
+
+        let mut out = Exposition::default();
+        metrics.write(&mut out);
+        let text = out.finish();
+
+        let labels = r#"method="GET",route="/policies/{id}",status="200""#;
+        assert!(text.contains(&format!("http_requests_total{{{}}} 2\n", labels)));
+        assert!(text.contains(&format!(
+            "http_request_duration_seconds_bucket{{{},le=\"0.05\"}} 1\n",
+            labels
+        )));
+        assert!(text.contains(&format!(
+            "http_request_duration_seconds_bucket{{{},le=\"0.5\"}} 2\n",
+            labels
+        )));
+        assert!(text.contains(&format!(
+            "http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} 2\n",
+            labels
+        )));
+        assert!(text.contains("# TYPE http_request_duration_seconds histogram\n"));
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        let mut out = Exposition::default();
+        out.sample("m", &[("l", "a\"b\\c\nd")], 1);
+        assert_eq!(out.finish(), "m{l=\"a\\\"b\\\\c\\nd\"} 1\n");
+    }
+}
@@ -0,0 +1,363 @@
+//! Database migrations from `migrations/`, embedded in the binary.
+//!
+//! Databases set up before migrations were tracked have the schema but no
+//! `_sqlx_migrations` table. They need `back migrate baseline <version>` once,
+//! naming the last migration that was applied by hand.
+//!
+//! Data migrated from Access may hold duplicate tax numbers or chassis, which
+//! keeps their unique indexes from being created. Once the duplicates are
+//! merged, `back migrate unique-indexes` creates them.
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use sqlx::migrate::{Migrate, Migration, Migrator};
+
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+const USAGE: &str =
+    "Usage: back migrate <run | status | revert | baseline VERSION | unique-indexes>";
+
+/// Created by `create_unique_person_car_indexes()` once their columns hold no
+/// duplicates
+const UNIQUE_INDEXES: [&str; 2] = ["person_tax_number_key", "car_chassis_key"];
+
+#[derive(sqlx::FromRow)]
+struct AppliedMigration {
+    version: i64,
+    description: String,
+    installed_on: DateTime<Utc>,
+    success: bool,
+    checksum: Vec<u8>,
+}
+
+pub enum MigrationState {
+    Applied(DateTime<Utc>),
+    Pending,
+    /// Applied, but the file has changed since
+    Modified,
+    /// Failed halfway; the database needs fixing by hand
+    Failed,
+    /// Applied by a newer build
+    Unknown,
+}
+
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+}
+
+/// Migrations that move the schema forward, in order.
+fn known() -> impl Iterator<Item = &'static Migration> {
+    MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+}
+
+async fn is_tracked(pool: &PgPool) -> sqlx::Result<bool> {
+    sqlx::query_scalar("select to_regclass('_sqlx_migrations') is not null")
+        .fetch_one(pool)
+        .await
+}
+
+async fn applied(pool: &PgPool) -> sqlx::Result<Vec<AppliedMigration>> {
+    if !is_tracked(pool).await? {
+        return Ok(Vec::new());
+    }
+
+    sqlx::query_as(
+        r#"
+        select version, description, installed_on, success, checksum
+        from _sqlx_migrations
+        order by version
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Fails for a database that has the schema but no migration history.
+async fn ensure_tracked(pool: &PgPool) -> anyhow::Result<()> {
+    let has_schema: bool = sqlx::query_scalar("select to_regclass('person') is not null")
+        .fetch_one(pool)
+        .await?;
+
+    if has_schema && !is_tracked(pool).await? {
+        anyhow::bail!(
+            "The database was migrated by hand. Run `back migrate baseline <version>` with the \
+             last migration it has, see `back migrate status`"
+        );
+    }
+    Ok(())
+}
+
+pub async fn status(pool: &PgPool) -> anyhow::Result<Vec<MigrationStatus>> {
+    let mut applied: HashMap<i64, AppliedMigration> = applied(pool)
+        .await?
+        .into_iter()
+        .map(|migration| (migration.version, migration))
+        .collect();
+
+    let mut statuses: Vec<MigrationStatus> = known()
+        .map(|migration| {
+            let state = match applied.remove(&migration.version) {
+                None => MigrationState::Pending,
+                Some(applied) if !applied.success => MigrationState::Failed,
+                Some(applied) if *applied.checksum != *migration.checksum => {
+                    MigrationState::Modified
+                }
+                Some(applied) => MigrationState::Applied(applied.installed_on),
+            };
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                state,
+            }
+        })
+        .collect();
+
+    statuses.extend(applied.into_values().map(|applied| MigrationStatus {
+        version: applied.version,
+        description: applied.description,
+        state: if applied.success {
+            MigrationState::Unknown
+        } else {
+            MigrationState::Failed
+        },
+    }));
+    statuses.sort_by_key(|status| status.version);
+
+    Ok(statuses)
+}
+
+/// Applies all pending migrations.
+pub async fn run(pool: &PgPool) -> anyhow::Result<()> {
+    ensure_tracked(pool).await?;
+    MIGRATOR.run(pool).await?;
+    Ok(())
+}
+
+/// Reverts the last applied migration. Returns its version.
+pub async fn revert(pool: &PgPool) -> anyhow::Result<i64> {
+    ensure_tracked(pool).await?;
+
+    let applied = applied(pool).await?;
+    let Some(last) = applied.last() else {
+        anyhow::bail!("No migrations are applied");
+    };
+
+    let reversible = MIGRATOR.iter().any(|migration| {
+        migration.version == last.version && migration.migration_type.is_down_migration()
+    });
+    if !reversible {
+        anyhow::bail!(
+            "Migration {} ({}) has no down script and cannot be reverted",
+            last.version,
+            last.description
+        );
+    }
+
+    let target = applied
+        .iter()
+        .rev()
+        .nth(1)
+        .map(|migration| migration.version)
+        .unwrap_or(0);
+    MIGRATOR.undo(pool, target).await?;
+    Ok(last.version)
+}
+
+/// Records every migration up to `version` as applied without running it,
+/// for databases that were migrated by hand.
+pub async fn baseline(pool: &PgPool, version: i64) -> anyhow::Result<usize> {
+    if !known().any(|migration| migration.version == version) {
+        anyhow::bail!("Unknown migration version {}", version);
+    }
+
+    let mut tx = pool.begin().await?;
+    tx.ensure_migrations_table().await?;
+
+    let recorded: i64 = sqlx::query_scalar("select count(*) from _sqlx_migrations")
+        .fetch_one(&mut *tx)
+        .await?;
+    if recorded > 0 {
+        anyhow::bail!("Migrations are already tracked, baseline is only for untracked databases");
+    }
+
+    let mut count = 0;
+    for migration in known().filter(|migration| migration.version <= version) {
+        // execution_time -1 marks migrations that were applied by hand
+        sqlx::query(
+            r#"
+            insert into _sqlx_migrations (version, description, success, checksum, execution_time)
+            values ($1, $2, true, $3, -1)
+            "#,
+        )
+        .bind(migration.version)
+        .bind(&*migration.description)
+        .bind(&*migration.checksum)
+        .execute(&mut *tx)
+        .await?;
+        count += 1;
+    }
+
+    tx.commit().await?;
+    Ok(count)
+}
+
+/// Fails unless the database has exactly the migrations this build knows about.
+pub async fn check(pool: &PgPool) -> anyhow::Result<()> {
+    ensure_tracked(pool).await?;
+
+    let mut problems = Vec::new();
+    for status in status(pool).await? {
+        let problem = match status.state {
+            MigrationState::Applied(_) => continue,
+            MigrationState::Pending => "is not applied",
+            MigrationState::Modified => "was changed after it was applied",
+            MigrationState::Failed => "failed",
+            MigrationState::Unknown => "is applied but unknown to this build",
+        };
+        problems.push(format!(
+            "migration {} ({}) {}",
+            status.version, status.description, problem
+        ));
+    }
+
+    if !problems.is_empty() {
+        anyhow::bail!(
+            "Database schema does not match this build: {}. Run `back migrate run`, or \
+             set MIGRATE_ON_STARTUP=true",
+            problems.join("; ")
+        );
+    }
+    Ok(())
+}
+
+/// Unique indexes that are not created yet because of duplicates.
+pub async fn missing_unique_indexes(pool: &PgPool) -> sqlx::Result<Vec<String>> {
+    sqlx::query_scalar(
+        "select name from unnest($1::text[]) as t(name) where to_regclass(name) is null",
+    )
+    .bind(&UNIQUE_INDEXES[..])
+    .fetch_all(pool)
+    .await
+}
+
+/// Creates the unique indexes whose duplicates have been merged. Returns the
+/// ones still missing.
+pub async fn create_unique_indexes(pool: &PgPool) -> anyhow::Result<Vec<String>> {
+    ensure_tracked(pool).await?;
+    sqlx::query("select create_unique_person_car_indexes()")
+        .execute(pool)
+        .await?;
+    Ok(missing_unique_indexes(pool).await?)
+}
+
+/// `back migrate ...`
+pub async fn command(pool: &PgPool, args: &[String]) -> anyhow::Result<()> {
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["run"] => {
+            run(pool).await?;
+            println!("Migrations are up to date");
+        }
+        ["status"] => {
+            for status in status(pool).await? {
+                let state = match status.state {
+                    MigrationState::Applied(at) => {
+                        format!("applied {}", at.format("%Y-%m-%d %H:%M"))
+                    }
+                    MigrationState::Pending => "pending".to_owned(),
+                    MigrationState::Modified => "modified since applied".to_owned(),
+                    MigrationState::Failed => "failed".to_owned(),
+                    MigrationState::Unknown => "unknown to this build".to_owned(),
+                };
+                println!("{} {:<28} {}", status.version, status.description, state);
+            }
+            if !is_tracked(pool).await? {
+                println!("The database has no migration history");
+            }
+        }
+        ["revert"] => {
+            let version = revert(pool).await?;
+            println!("Reverted migration {}", version);
+        }
+        ["baseline", version] => {
+            let version = version.parse().context(USAGE)?;
+            let count = baseline(pool, version).await?;
+            println!("Recorded {} migrations as applied", count);
+        }
+        ["unique-indexes"] => {
+            let missing = create_unique_indexes(pool).await?;
+            if missing.is_empty() {
+                println!("Unique indexes are in place");
+            } else {
+                println!(
+                    "Not created while duplicates remain, merge them first: {}",
+                    missing.join(", ")
+                );
+            }
+        }
+        _ => anyhow::bail!(USAGE),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+    async fn unique_indexes_after_merge(pool: PgPool) {
+        sqlx::query("drop index person_tax_number_key")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let ids: Vec<i32> = sqlx::query_scalar(
+            r#"
+            insert into person (first_name, last_name, sex, birth_date, tax_number, phone, email, status, tenant_id)
+            select 'Олена', 'Шевченко', 'f', '1990-01-01', '2900000001', '+380500000000', '', 'active', 1
+            from generate_series(1, 2)
+            returning id
+            "#,
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+
+        let missing = create_unique_indexes(&pool).await.unwrap();
+        assert_eq!(missing, ["person_tax_number_key"]);
+
+        sqlx::query("update person set status = 'archived', merged_into_id = $1 where id = $2")
+            .bind(ids[0])
+            .bind(ids[1])
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(create_unique_indexes(&pool).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+    async fn reverts_every_migration(pool: PgPool) {
+        let mut reverted = Vec::new();
+        while !applied(&pool).await.unwrap().is_empty() {
+            reverted.push(revert(&pool).await.unwrap());
+        }
+        assert_eq!(reverted.len(), known().count());
+
+        let tables: i64 = sqlx::query_scalar(
+            "select count(*) from pg_tables where schemaname = 'public' and tablename <> '_sqlx_migrations'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(tables, 0);
+
+        run(&pool).await.unwrap();
+    }
+}
@@ -0,0 +1,357 @@
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::holder::Holder;
+use super::log::{NotificationChannel, NotificationKind};
+use super::sms::OutgoingSms;
+use super::template::{BIRTHDAY_GREETING, BIRTHDAY_GREETING_SMS, RenderedEmail};
+use super::{Notifier, ReminderReport};
+
+#[derive(Clone, Copy, Debug, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "birthday_greeting_status")]
+#[sqlx(rename_all = "snake_case")]
+pub enum GreetingStatus {
+    Sent,
+    Cancelled,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GreetingState {
+    /// Goes out on the day
+    Pending,
+    Sent,
+    /// Cancelled by an agent
+    Cancelled,
+    /// The person does not want greetings
+    OptedOut,
+    /// No mobile number or email to send to
+    Unreachable,
+}
+
+/// A birthday greeting as the agent sees it before and after the day.
+#[derive(Serialize, ToSchema)]
+pub struct UpcomingGreeting {
+    pub person_id: i32,
+    pub full_name: String,
+    pub greeting_date: NaiveDate,
+    pub age: i32,
+    pub state: GreetingState,
+    pub channel: Option<NotificationChannel>,
+    pub recipient: Option<String>,
+    pub text: Option<String>,
+    pub cancelled_by: Option<String>,
+}
+
+/// Whether `day` is the birthday of someone born on `birth_date`. People born
+/// on February 29 are greeted on February 28 in non-leap years.
+pub fn is_birthday(birth_date: NaiveDate, day: NaiveDate) -> bool {
+    if day <= birth_date {
+        return false;
+    }
+    if (birth_date.month(), birth_date.day()) == (day.month(), day.day()) {
+        return true;
+    }
+    (birth_date.month(), birth_date.day()) == (2, 29)
+        && (day.month(), day.day()) == (2, 28)
+        && day.succ_opt().is_some_and(|next| next.month() == 3)
+}
+
+struct Birthday {
+    holder: Holder,
+    greeting_date: NaiveDate,
+    age: i32,
+    birthday_opt_out: bool,
+    status: Option<GreetingStatus>,
+    channel: Option<NotificationChannel>,
+    recipient: Option<String>,
+    text: Option<String>,
+    cancelled_by: Option<String>,
+}
+
+/// Birthdays of non-archived people from `from` for `days` days, with the
+/// greeting already recorded for each, if any.
+async fn birthdays(pool: &PgPool, from: NaiveDate, days: i32) -> sqlx::Result<Vec<Birthday>> {
+    let rows = sqlx::query!(
+        r#"
+        select
+            d.day as "greeting_date!",
+            (extract(year from d.day) - extract(year from pe.birth_date))::int as "age!",
+            pe.id,
+            pe.first_name,
+            pe.last_name,
+            pe.patronymic_name,
+            pe.email,
+            pe.phone,
+            pe.phone2,
+            pe.sms_opt_out,
+            pe.birthday_opt_out,
+            bg.status as "status?: GreetingStatus",
+            bg.channel as "channel?: NotificationChannel",
+            bg.recipient,
+            bg.text,
+            bg.cancelled_by
+        from (select $1::date + i as day from generate_series(0, $2::int - 1) i) d
+        join person pe on
+            (
+                (extract(month from pe.birth_date), extract(day from pe.birth_date))
+                    = (extract(month from d.day), extract(day from d.day))
+                or (
+                    (extract(month from pe.birth_date), extract(day from pe.birth_date)) = (2, 29)
+                    and (extract(month from d.day), extract(day from d.day)) = (2, 28)
+                    and extract(month from d.day + 1) = 3
+                )
+            )
+            and pe.birth_date < d.day
+        left join birthday_greeting bg on bg.person_id = pe.id and bg.greeting_date = d.day
+        where pe.status <> 'archived'
+        order by d.day, pe.last_name, pe.first_name, pe.id
+        "#,
+        from,
+        days
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Birthday {
+            holder: Holder {
+                person_id: row.id,
+                first_name: row.first_name,
+                last_name: row.last_name,
+                patronymic_name: row.patronymic_name,
+                email: row.email,
+                phone: row.phone,
+                phone2: row.phone2,
+                sms_opt_out: row.sms_opt_out,
+            },
+            greeting_date: row.greeting_date,
+            age: row.age,
+            birthday_opt_out: row.birthday_opt_out,
+            status: row.status,
+            channel: row.channel,
+            recipient: row.recipient,
+            text: row.text,
+            cancelled_by: row.cancelled_by,
+        })
+        .collect())
+}
+
+enum Message {
+    Email(RenderedEmail),
+    Sms(String),
+}
+
+/// What would be sent to a person: by SMS when they have a mobile number and
+/// did not opt out, by email otherwise.
+struct Greeting {
+    channel: NotificationChannel,
+    recipient: String,
+    message: Message,
+}
+
+impl Greeting {
+    fn compose(notifier: &Notifier, birthday: &Birthday) -> anyhow::Result<Option<Self>> {
+        let context = minijinja::context! {
+            holder => birthday.holder.context(),
+            age => birthday.age,
+        };
+
+        if notifier.sms.is_some()
+            && let Some(phone) = birthday.holder.sms_phone()
+        {
+            let text = notifier
+                .templates
+                .render_text(BIRTHDAY_GREETING_SMS, context)?;
+            return Ok(Some(Self {
+                channel: NotificationChannel::Sms,
+                recipient: phone,
+                message: Message::Sms(text),
+            }));
+        }
+
+        let email = birthday.holder.email.trim();
+        if notifier.mailer.is_some() && !email.is_empty() {
+            let message = notifier.templates.render(BIRTHDAY_GREETING, context)?;
+            return Ok(Some(Self {
+                channel: NotificationChannel::Email,
+                recipient: email.to_owned(),
+                message: Message::Email(message),
+            }));
+        }
+
+        Ok(None)
+    }
+
+    fn text(&self) -> String {
+        match &self.message {
+            Message::Email(email) => format!("{}\n\n{}", email.subject, email.body),
+            Message::Sms(text) => text.clone(),
+        }
+    }
+
+    async fn send(&self, pool: &PgPool, notifier: &Notifier, person_id: i32) -> anyhow::Result<()> {
+        match (&self.message, &notifier.mailer, &notifier.sms) {
+            (Message::Email(email), Some(mailer), _) => mailer.send(&self.recipient, email).await,
+            (Message::Sms(text), _, Some(sms)) => {
+                let message = OutgoingSms {
+                    kind: NotificationKind::BirthdayGreeting,
+                    person_id,
+                    policy_id: None,
+                    phone: &self.recipient,
+                    text,
+                };
+                sms.send(pool, message).await
+            }
+            _ => anyhow::bail!("Greeting channel is not configured"),
+        }
+    }
+}
+
+/// Greets everyone whose birthday is `today`, unless they opted out, an agent
+/// cancelled the greeting or it was already sent.
+pub async fn send_birthday_greetings(
+    pool: &PgPool,
+    notifier: &Notifier,
+    today: NaiveDate,
+    report: &mut ReminderReport,
+) -> anyhow::Result<()> {
+    for birthday in birthdays(pool, today, 1).await? {
+        if birthday.status.is_some() || birthday.birthday_opt_out {
+            continue;
+        }
+        let Some(greeting) = Greeting::compose(notifier, &birthday)? else {
+            continue;
+        };
+
+        let person_id = birthday.holder.person_id;
+        let claim = sqlx::query_scalar!(
+            r#"
+            insert into birthday_greeting (person_id, greeting_date, status, channel, recipient, text)
+            values ($1, $2, 'sent', $3, $4, $5)
+            on conflict do nothing
+            returning id
+            "#,
+            person_id,
+            birthday.greeting_date,
+            greeting.channel as NotificationChannel,
+            greeting.recipient,
+            greeting.text()
+        )
+        .fetch_optional(pool)
+        .await?;
+        let Some(claim) = claim else {
+            continue;
+        };
+
+        match greeting.send(pool, notifier, person_id).await {
+            Ok(()) => report.greetings_sent += 1,
+            Err(err) => {
+                warn!(
+                    "Birthday greeting for person {} failed: {:?}",
+                    person_id, err
+                );
+                sqlx::query!("delete from birthday_greeting where id = $1", claim)
+                    .execute(pool)
+                    .await?;
+                report.failed += 1;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Greetings for birthdays from `from` for `days` days: what was sent, and
+/// what is going to be sent as things stand now.
+pub async fn upcoming_greetings(
+    pool: &PgPool,
+    notifier: &Notifier,
+    from: NaiveDate,
+    days: i32,
+) -> anyhow::Result<Vec<UpcomingGreeting>> {
+    let mut greetings = Vec::new();
+    for birthday in birthdays(pool, from, days).await? {
+        let holder = &birthday.holder;
+        let full_name = [
+            Some(holder.last_name.as_str()),
+            Some(holder.first_name.as_str()),
+            holder.patronymic_name.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+        let mut greeting = UpcomingGreeting {
+            person_id: holder.person_id,
+            full_name,
+            greeting_date: birthday.greeting_date,
+            age: birthday.age,
+            state: GreetingState::Pending,
+            channel: None,
+            recipient: None,
+            text: None,
+            cancelled_by: None,
+        };
+
+        if birthday.status == Some(GreetingStatus::Sent) {
+            greeting.state = GreetingState::Sent;
+            greeting.channel = birthday.channel;
+            greeting.recipient = birthday.recipient;
+            greeting.text = birthday.text;
+            greetings.push(greeting);
+            continue;
+        }
+
+        if birthday.status == Some(GreetingStatus::Cancelled) {
+            greeting.state = GreetingState::Cancelled;
+            greeting.cancelled_by = birthday.cancelled_by.clone();
+        } else if birthday.birthday_opt_out {
+            greeting.state = GreetingState::OptedOut;
+        }
+
+        match Greeting::compose(notifier, &birthday)? {
+            Some(planned) => {
+                greeting.text = Some(planned.text());
+                greeting.channel = Some(planned.channel);
+                greeting.recipient = Some(planned.recipient);
+            }
+            None if greeting.state == GreetingState::Pending => {
+                greeting.state = GreetingState::Unreachable;
+            }
+            None => {}
+        }
+        greetings.push(greeting);
+    }
+
+    Ok(greetings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn birthdays() {
+        assert!(is_birthday(date(1980, 10, 19), date(2026, 10, 19)));
+        assert!(!is_birthday(date(1980, 10, 19), date(2026, 10, 20)));
+        assert!(!is_birthday(date(2026, 10, 19), date(2026, 10, 19)));
+    }
+
+    #[test]
+    fn leap_day_birthdays() {
+        assert!(is_birthday(date(2000, 2, 29), date(2027, 2, 28)));
+        assert!(!is_birthday(date(2000, 2, 29), date(2028, 2, 28)));
+        assert!(is_birthday(date(2000, 2, 29), date(2028, 2, 29)));
+        assert!(!is_birthday(date(2000, 2, 28), date(2028, 2, 29)));
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Context;
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use super::template::RenderedEmail;
+use crate::config::{SmtpConfig, SmtpTls};
+
+/// SMTP client. Configured with `SMTP_HOST`, `SMTP_PORT`, `SMTP_TLS`
+/// (`starttls`, `tls` or `none`), `SMTP_USERNAME`, `SMTP_PASSWORD` and `SMTP_FROM`.
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn new(config: &SmtpConfig) -> anyhow::Result<Self> {
+        let host = config.host.as_str();
+        let mut builder = match config.tls {
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+            SmtpTls::Plain => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        };
+
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if let Some((username, password)) = &config.credentials {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: config.from.clone(),
+        })
+    }
+
+    pub async fn send(&self, to: &str, email: &RenderedEmail) -> anyhow::Result<()> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to
+                .parse()
+                .with_context(|| format!("Invalid recipient {}", to))?)
+            .subject(&email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body.clone())?;
+
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::sink::SmtpSink;
+
+    fn mailer(sink: &SmtpSink) -> Mailer {
+        Mailer {
+            transport: AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous("127.0.0.1")
+                .port(sink.addr.port())
+                .build(),
+            from: "Insure <noreply@insure.test>".parse().unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn delivers_to_smtp_server() {
+        let mut sink = SmtpSink::start().await;
+        let email = RenderedEmail {
+            subject: "Policy expires".to_owned(),
+            body: "Your policy expires on 20.10.2026.".to_owned(),
+        };
+
+        mailer(&sink)
+            .send("holder@example.com", &email)
+            .await
+            .unwrap();
+
+        let message = sink.next().await;
+        assert_eq!(message.from, "noreply@insure.test");
+        assert_eq!(message.to, ["holder@example.com"]);
+        assert!(message.data.contains("Subject: Policy expires"));
+        assert!(message.data.contains("Your policy expires on 20.10.2026."));
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_recipient() {
+        let sink = SmtpSink::start().await;
+        let email = RenderedEmail {
+            subject: "Policy expires".to_owned(),
+            body: String::new(),
+        };
+
+        assert!(mailer(&sink).send("not an address", &email).await.is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::warn;
+
+use super::holder::{self, Holder, HolderReminder};
+use super::log::{self, LogEntry, NotificationChannel, NotificationKind};
+use super::template::{EXPIRY_DIGEST, EXPIRY_REMINDER, EXPIRY_REMINDER_SMS};
+use super::{Notifier, ReminderReport};
+use crate::shared::policy::labels::type_label;
+use crate::shared::policy::model::PolicyType;
+
+#[derive(Serialize)]
+struct PolicyContext {
+    series: String,
+    number: String,
+    #[serde(rename = "type")]
+    policy_type: &'static str,
+    end_date: String,
+    days_left: i32,
+    holder: String,
+}
+
+#[derive(Serialize)]
+struct AgentContext {
+    full_name: String,
+}
+
+struct DuePolicy {
+    id: i32,
+    series: String,
+    number: String,
+    policy_type: PolicyType,
+    end_date: NaiveDate,
+    days_left: i32,
+    /// The smallest configured threshold the policy has reached
+    days_before: i32,
+    holder_first_name: String,
+    holder_last_name: String,
+}
+
+impl DuePolicy {
+    fn context(&self) -> PolicyContext {
+        PolicyContext {
+            series: self.series.clone(),
+            number: self.number.clone(),
+            policy_type: type_label(self.policy_type),
+            end_date: self.end_date.format("%d.%m.%Y").to_string(),
+            days_left: self.days_left,
+            holder: format!("{} {}", self.holder_last_name, self.holder_first_name),
+        }
+    }
+}
+
+/// Reminds holders of active policies ending within the largest threshold, and
+/// emails each linked agent one digest of their clients' policies. Anything
+/// already in the notification log is skipped.
+pub async fn send_expiry_reminders(
+    pool: &PgPool,
+    notifier: &Notifier,
+    report: &mut ReminderReport,
+) -> anyhow::Result<()> {
+    send_holder_reminders(pool, notifier, report).await?;
+    send_agent_digests(pool, notifier, report).await?;
+    Ok(())
+}
+
+async fn send_holder_reminders(
+    pool: &PgPool,
+    notifier: &Notifier,
+    report: &mut ReminderReport,
+) -> anyhow::Result<()> {
+    let days = &notifier.reminders.expiry_days;
+    let due = sqlx::query!(
+        r#"
+        select
+            p.id,
+            p.series,
+            p.number,
+            p.type as "policy_type: PolicyType",
+            p.end_date as "end_date!",
+            (p.end_date - current_date) as "days_left!",
+            (select min(d) from unnest($1::int[]) d where d >= p.end_date - current_date) as "days_before!",
+            pe.id as person_id,
+            pe.first_name,
+            pe.last_name,
+            pe.patronymic_name,
+            pe.email,
+            pe.phone,
+            pe.phone2,
+            pe.sms_opt_out
+        from policy p
+        join person pe on pe.id = p.holder_id
+        where
+            p.status = 'active'
+            and p.end_date between current_date and current_date + $2::int
+            and pe.status <> 'archived'
+        order by p.end_date, p.id
+        "#,
+        days,
+        days.last().copied().unwrap_or_default()
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for row in due {
+        let policy = DuePolicy {
+            id: row.id,
+            series: row.series,
+            number: row.number,
+            policy_type: row.policy_type,
+            end_date: row.end_date,
+            days_left: row.days_left,
+            days_before: row.days_before,
+            holder_first_name: row.first_name.clone(),
+            holder_last_name: row.last_name.clone(),
+        };
+        let holder = Holder {
+            person_id: row.person_id,
+            first_name: row.first_name,
+            last_name: row.last_name,
+            patronymic_name: row.patronymic_name,
+            email: row.email,
+            phone: row.phone,
+            phone2: row.phone2,
+            sms_opt_out: row.sms_opt_out,
+        };
+        let reminder = HolderReminder {
+            kind: NotificationKind::ExpiryReminder,
+            policy_id: policy.id,
+            due_date: policy.end_date,
+            days_before: policy.days_before,
+            email_template: EXPIRY_REMINDER,
+            sms_template: EXPIRY_REMINDER_SMS,
+            context: minijinja::context! {
+                holder => holder.context(),
+                policy => policy.context(),
+            },
+        };
+
+        holder::remind_holder(pool, notifier, &holder, &reminder, report).await?;
+    }
+
+    Ok(())
+}
+
+async fn send_agent_digests(
+    pool: &PgPool,
+    notifier: &Notifier,
+    report: &mut ReminderReport,
+) -> anyhow::Result<()> {
+    let Some(mailer) = &notifier.mailer else {
+        return Ok(());
+    };
+
+    let days = &notifier.reminders.expiry_days;
+    let rows = sqlx::query!(
+        r#"
+        select
+            a.id as agent_id,
+            a.full_name as agent_full_name,
+            a.email as "agent_email!",
+            p.id,
+            p.series,
+            p.number,
+            p.type as "policy_type: PolicyType",
+            p.end_date as "end_date!",
+            (p.end_date - current_date) as "days_left!",
+            (select min(d) from unnest($1::int[]) d where d >= p.end_date - current_date) as "days_before!",
+            pe.first_name,
+            pe.last_name
+        from agent a
+        join agent_policy ap on ap.agent_id = a.id
+        join policy p on p.id = ap.policy_id
+        join person pe on pe.id = p.holder_id
+        where
+            a.email is not null
+            and a.email <> ''
+            and p.status = 'active'
+            and p.end_date between current_date and current_date + $2::int
+        order by a.id, p.end_date, p.id
+        "#,
+        days,
+        days.last().copied().unwrap_or_default()
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut rows = rows.into_iter().peekable();
+    while let Some(first) = rows.peek() {
+        let agent_id = first.agent_id;
+        let agent = AgentContext {
+            full_name: first.agent_full_name.clone(),
+        };
+        let recipient = first.agent_email.trim().to_owned();
+
+        let mut claims = Vec::new();
+        let mut policies = Vec::new();
+        while let Some(row) = rows.next_if(|row| row.agent_id == agent_id) {
+            let policy = DuePolicy {
+                id: row.id,
+                series: row.series,
+                number: row.number,
+                policy_type: row.policy_type,
+                end_date: row.end_date,
+                days_left: row.days_left,
+                days_before: row.days_before,
+                holder_first_name: row.first_name,
+                holder_last_name: row.last_name,
+            };
+            let entry = LogEntry {
+                kind: NotificationKind::ExpiryDigest,
+                channel: NotificationChannel::Email,
+                policy_id: policy.id,
+                recipient: &recipient,
+                due_date: policy.end_date,
+                days_before: policy.days_before,
+            };
+            if let Some(claim) = log::claim(pool, entry).await? {
+                claims.push(claim);
+                policies.push(policy.context());
+            }
+        }
+
+        if policies.is_empty() {
+            continue;
+        }
+
+        let sent = async {
+            let email = notifier
+                .templates
+                .render(EXPIRY_DIGEST, minijinja::context! { agent, policies })?;
+            mailer.send(&recipient, &email).await
+        }
+        .await;
+
+        match sent {
+            Ok(()) => report.digests_sent += 1,
+            Err(err) => {
+                warn!("Expiry digest for agent {} failed: {:?}", agent_id, err);
+                log::release(pool, &claims).await?;
+                report.failed += 1;
+            }
+        }
+    }
+
+    Ok(())
+}
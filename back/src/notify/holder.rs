@@ -0,0 +1,145 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::warn;
+
+use super::log::{self, LogEntry, NotificationChannel, NotificationKind};
+use super::sms::OutgoingSms;
+use super::{Notifier, ReminderReport};
+use crate::shared::phone::mobile_phone;
+use crate::shared::vocative;
+
+/// A policy holder as far as reminders are concerned.
+pub struct Holder {
+    pub person_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub patronymic_name: Option<String>,
+    pub email: String,
+    pub phone: String,
+    pub phone2: Option<String>,
+    pub sms_opt_out: bool,
+}
+
+#[derive(Serialize)]
+pub struct HolderContext<'a> {
+    first_name: &'a str,
+    last_name: &'a str,
+    patronymic_name: Option<&'a str>,
+    /// First name and patronymic in the vocative, e.g. "Іване Петровичу"
+    address: String,
+}
+
+impl Holder {
+    pub fn context(&self) -> HolderContext<'_> {
+        HolderContext {
+            first_name: &self.first_name,
+            last_name: &self.last_name,
+            patronymic_name: self.patronymic_name.as_deref(),
+            address: vocative::address(&self.first_name, self.patronymic_name.as_deref()),
+        }
+    }
+
+    /// The holder's mobile number, unless they opted out of SMS.
+    pub fn sms_phone(&self) -> Option<String> {
+        if self.sms_opt_out {
+            return None;
+        }
+        mobile_phone(std::iter::once(self.phone.as_str()).chain(self.phone2.as_deref()))
+    }
+}
+
+/// One reminder about a policy, sent to its holder by email and by SMS,
+/// whichever of them is configured and reachable.
+pub struct HolderReminder {
+    pub kind: NotificationKind,
+    pub policy_id: i32,
+    pub due_date: NaiveDate,
+    pub days_before: i32,
+    pub email_template: &'static str,
+    pub sms_template: &'static str,
+    pub context: minijinja::Value,
+}
+
+impl HolderReminder {
+    fn log_entry<'a>(&self, channel: NotificationChannel, recipient: &'a str) -> LogEntry<'a> {
+        LogEntry {
+            kind: self.kind,
+            channel,
+            policy_id: self.policy_id,
+            recipient,
+            due_date: self.due_date,
+            days_before: self.days_before,
+        }
+    }
+}
+
+pub async fn remind_holder(
+    pool: &PgPool,
+    notifier: &Notifier,
+    holder: &Holder,
+    reminder: &HolderReminder,
+    report: &mut ReminderReport,
+) -> anyhow::Result<()> {
+    let email = holder.email.trim();
+    if let Some(mailer) = &notifier.mailer
+        && !email.is_empty()
+        && let Some(claim) =
+            log::claim(pool, reminder.log_entry(NotificationChannel::Email, email)).await?
+    {
+        let sent = async {
+            let message = notifier
+                .templates
+                .render(reminder.email_template, &reminder.context)?;
+            mailer.send(email, &message).await
+        }
+        .await;
+
+        match sent {
+            Ok(()) => report.emails_sent += 1,
+            Err(err) => {
+                warn!(
+                    "Email reminder for policy {} failed: {:?}",
+                    reminder.policy_id, err
+                );
+                log::release(pool, &[claim]).await?;
+                report.failed += 1;
+            }
+        }
+    }
+
+    if let Some(sms) = &notifier.sms
+        && let Some(phone) = holder.sms_phone()
+        && let Some(claim) =
+            log::claim(pool, reminder.log_entry(NotificationChannel::Sms, &phone)).await?
+    {
+        let sent = async {
+            let text = notifier
+                .templates
+                .render_text(reminder.sms_template, &reminder.context)?;
+            let message = OutgoingSms {
+                kind: reminder.kind,
+                person_id: holder.person_id,
+                policy_id: Some(reminder.policy_id),
+                phone: &phone,
+                text: &text,
+            };
+            sms.send(pool, message).await
+        }
+        .await;
+
+        match sent {
+            Ok(()) => report.sms_sent += 1,
+            Err(err) => {
+                warn!(
+                    "SMS reminder for policy {} failed: {:?}",
+                    reminder.policy_id, err
+                );
+                log::release(pool, &[claim]).await?;
+                report.failed += 1;
+            }
+        }
+    }
+
+    Ok(())
+}
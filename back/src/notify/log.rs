@@ -0,0 +1,61 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+#[derive(Clone, Copy, sqlx::Type)]
+#[sqlx(type_name = "notification_kind")]
+#[sqlx(rename_all = "snake_case")]
+pub enum NotificationKind {
+    ExpiryReminder,
+    ExpiryDigest,
+    PaymentReminder,
+    BirthdayGreeting,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "notification_channel")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Email,
+    Sms,
+}
+
+pub struct LogEntry<'a> {
+    pub kind: NotificationKind,
+    pub channel: NotificationChannel,
+    pub policy_id: i32,
+    pub recipient: &'a str,
+    pub due_date: NaiveDate,
+    pub days_before: i32,
+}
+
+/// Records a notification before it is sent. Returns `None` when the same
+/// notification is already logged, so concurrent runs never send it twice.
+pub async fn claim(pool: &PgPool, entry: LogEntry<'_>) -> sqlx::Result<Option<i32>> {
+    sqlx::query_scalar!(
+        r#"
+        insert into notification_log (kind, channel, policy_id, recipient, due_date, days_before)
+        values ($1, $2, $3, $4, $5, $6)
+        on conflict do nothing
+        returning id
+        "#,
+        entry.kind as NotificationKind,
+        entry.channel as NotificationChannel,
+        entry.policy_id,
+        entry.recipient,
+        entry.due_date,
+        entry.days_before
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Forgets claimed notifications that could not be delivered, so the next run retries them.
+pub async fn release(pool: &PgPool, ids: &[i32]) -> sqlx::Result<()> {
+    sqlx::query!("delete from notification_log where id = any($1)", ids)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,122 @@
+pub mod birthday;
+pub mod email;
+pub mod expiry;
+pub mod holder;
+pub mod log;
+pub mod payment;
+#[cfg(test)]
+pub mod sink;
+pub mod sms;
+pub mod template;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Timelike;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::config::Config;
+use email::Mailer;
+use sms::Sms;
+use template::Templates;
+use utoipa::ToSchema;
+
+const RUN_EVERY: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_EXPIRY_DAYS: [i32; 2] = [1, 7];
+const DEFAULT_PAYMENT_DAYS: [i32; 2] = [0, 3];
+const DEFAULT_BIRTHDAY_HOUR: u32 = 9;
+
+/// How many days before the date reminders go out, from the comma-separated
+/// `REMINDER_DAYS` (policy end) and `PAYMENT_REMINDER_DAYS` (payment due).
+/// Every threshold sends its own reminder. Birthday greetings go out from
+/// `BIRTHDAY_SEND_HOUR` (server local time) on the day.
+#[derive(Clone)]
+pub struct ReminderSettings {
+    pub expiry_days: Vec<i32>,
+    pub payment_days: Vec<i32>,
+    pub birthday_hour: u32,
+}
+
+impl Default for ReminderSettings {
+    fn default() -> Self {
+        Self {
+            expiry_days: DEFAULT_EXPIRY_DAYS.to_vec(),
+            payment_days: DEFAULT_PAYMENT_DAYS.to_vec(),
+            birthday_hour: DEFAULT_BIRTHDAY_HOUR,
+        }
+    }
+}
+
+#[derive(Serialize, Default, ToSchema)]
+pub struct ReminderReport {
+    pub emails_sent: usize,
+    pub sms_sent: usize,
+    pub digests_sent: usize,
+    pub greetings_sent: usize,
+    pub failed: usize,
+}
+
+/// Everything needed to send reminders. Absent when neither email nor SMS is configured.
+pub struct Notifier {
+    pub mailer: Option<Mailer>,
+    pub sms: Option<Sms>,
+    pub templates: Templates,
+    pub reminders: ReminderSettings,
+}
+
+impl Notifier {
+    pub fn new(config: &Config) -> anyhow::Result<Option<Self>> {
+        let mailer = config.smtp.as_ref().map(Mailer::new).transpose()?;
+        let sms = config.sms.as_ref().map(Sms::from_config);
+        if mailer.is_none() && sms.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            mailer,
+            sms,
+            templates: Templates::load(config.notify_template_dir.as_deref())?,
+            reminders: config.reminders.clone(),
+        }))
+    }
+}
+
+async fn run(pool: &PgPool, notifier: &Notifier) -> anyhow::Result<ReminderReport> {
+    let mut report = ReminderReport::default();
+    expiry::send_expiry_reminders(pool, notifier, &mut report).await?;
+    payment::send_payment_reminders(pool, notifier, &mut report).await?;
+
+    let now = chrono::Local::now();
+    if now.hour() >= notifier.reminders.birthday_hour {
+        birthday::send_birthday_greetings(pool, notifier, now.date_naive(), &mut report).await?;
+    }
+
+    if let Some(sms) = &notifier.sms {
+        sms.refresh_statuses(pool).await?;
+    }
+    Ok(report)
+}
+
+/// Sends due reminders hourly. Each reminder is logged, so reruns only pick up
+/// what was missed, e.g. while the server was down.
+pub fn spawn(pool: PgPool, notifier: Arc<Notifier>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RUN_EVERY);
+        loop {
+            interval.tick().await;
+            match run(&pool, &notifier).await {
+                Ok(report) => info!(
+                    emails = report.emails_sent,
+                    sms = report.sms_sent,
+                    digests = report.digests_sent,
+                    greetings = report.greetings_sent,
+                    failed = report.failed,
+                    "Reminders sent"
+                ),
+                Err(err) => error!("Reminders failed: {:?}", err),
+            }
+        }
+    });
+}
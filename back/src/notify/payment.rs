@@ -0,0 +1,108 @@
+use serde::Serialize;
+use sqlx::PgPool;
+
+use super::holder::{self, Holder, HolderReminder};
+use super::log::NotificationKind;
+use super::template::{PAYMENT_REMINDER, PAYMENT_REMINDER_SMS};
+use super::{Notifier, ReminderReport};
+use crate::shared::policy::labels::type_label;
+use crate::shared::policy::model::PolicyType;
+
+#[derive(Serialize)]
+struct PolicyContext {
+    series: String,
+    number: String,
+    #[serde(rename = "type")]
+    policy_type: &'static str,
+}
+
+#[derive(Serialize)]
+struct PaymentContext {
+    due_date: String,
+    amount: i32,
+    days_left: i32,
+}
+
+/// Reminds holders of active policies about unpaid payments due within the
+/// largest threshold.
+pub async fn send_payment_reminders(
+    pool: &PgPool,
+    notifier: &Notifier,
+    report: &mut ReminderReport,
+) -> anyhow::Result<()> {
+    let days = &notifier.reminders.payment_days;
+    let due = sqlx::query!(
+        r#"
+        select
+            pp.due_date,
+            pp.amount,
+            (pp.due_date - current_date) as "days_left!",
+            (select min(d) from unnest($1::int[]) d where d >= pp.due_date - current_date) as "days_before!",
+            p.id as policy_id,
+            p.series,
+            p.number,
+            p.type as "policy_type: PolicyType",
+            pe.id as person_id,
+            pe.first_name,
+            pe.last_name,
+            pe.patronymic_name,
+            pe.email,
+            pe.phone,
+            pe.phone2,
+            pe.sms_opt_out
+        from policy_payment pp
+        join policy p on p.id = pp.policy_id
+        join person pe on pe.id = p.holder_id
+        where
+            pp.paid_on is null
+            and pp.due_date between current_date and current_date + $2::int
+            and p.status = 'active'
+            and pe.status <> 'archived'
+        order by pp.due_date, pp.id
+        "#,
+        days,
+        days.last().copied().unwrap_or_default()
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for row in due {
+        let holder = Holder {
+            person_id: row.person_id,
+            first_name: row.first_name,
+            last_name: row.last_name,
+            patronymic_name: row.patronymic_name,
+            email: row.email,
+            phone: row.phone,
+            phone2: row.phone2,
+            sms_opt_out: row.sms_opt_out,
+        };
+        let policy = PolicyContext {
+            series: row.series,
+            number: row.number,
+            policy_type: type_label(row.policy_type),
+        };
+        let payment = PaymentContext {
+            due_date: row.due_date.format("%d.%m.%Y").to_string(),
+            amount: row.amount,
+            days_left: row.days_left,
+        };
+        let reminder = HolderReminder {
+            kind: NotificationKind::PaymentReminder,
+            policy_id: row.policy_id,
+            due_date: row.due_date,
+            days_before: row.days_before,
+            email_template: PAYMENT_REMINDER,
+            sms_template: PAYMENT_REMINDER_SMS,
+            context: minijinja::context! {
+                holder => holder.context(),
+                policy,
+                payment,
+            },
+        };
+
+        holder::remind_holder(pool, notifier, &holder, &reminder, report).await?;
+    }
+
+    Ok(())
+}
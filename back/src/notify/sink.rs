@@ -0,0 +1,91 @@
+//! Minimal SMTP server for tests. Accepts every message and hands it to the test.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+pub struct SinkMessage {
+    pub from: String,
+    pub to: Vec<String>,
+    pub data: String,
+}
+
+pub struct SmtpSink {
+    pub addr: SocketAddr,
+    messages: mpsc::UnboundedReceiver<SinkMessage>,
+}
+
+impl SmtpSink {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, messages) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, tx.clone()));
+            }
+        });
+
+        Self { addr, messages }
+    }
+
+    pub async fn next(&mut self) -> SinkMessage {
+        tokio::time::timeout(Duration::from_secs(5), self.messages.recv())
+            .await
+            .expect("no message delivered")
+            .unwrap()
+    }
+}
+
+fn address(arg: &str) -> String {
+    arg.split(['<', '>']).nth(1).unwrap_or(arg).to_owned()
+}
+
+async fn serve(stream: TcpStream, tx: mpsc::UnboundedSender<SinkMessage>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut from = String::new();
+    let mut to = Vec::new();
+
+    writer.write_all(b"220 sink ready\r\n").await.unwrap();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let command = line.to_ascii_uppercase();
+        let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+            b"250 sink\r\n"
+        } else if command.starts_with("MAIL FROM:") {
+            from = address(&line[10..]);
+            b"250 OK\r\n"
+        } else if command.starts_with("RCPT TO:") {
+            to.push(address(&line[8..]));
+            b"250 OK\r\n"
+        } else if command == "DATA" {
+            writer.write_all(b"354 go ahead\r\n").await.unwrap();
+            let mut data = String::new();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if line == "." {
+                    break;
+                }
+                data.push_str(line.strip_prefix('.').unwrap_or(&line));
+                data.push('\n');
+            }
+            let _ = tx.send(SinkMessage {
+                from: std::mem::take(&mut from),
+                to: std::mem::take(&mut to),
+                data,
+            });
+            b"250 queued\r\n"
+        } else if command == "QUIT" {
+            writer.write_all(b"221 bye\r\n").await.unwrap();
+            break;
+        } else if command == "RSET" || command == "NOOP" {
+            b"250 OK\r\n"
+        } else {
+            b"502 not implemented\r\n"
+        };
+        writer.write_all(reply).await.unwrap();
+    }
+}
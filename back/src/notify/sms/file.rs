@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+
+use futures_util::FutureExt;
+use futures_util::future::BoxFuture;
+use tokio::io::AsyncWriteExt;
+
+use super::{SentSms, SmsProvider, SmsStatus};
+
+/// Development provider: appends each message as a JSON line to `SMS_FILE`,
+/// or only logs it when that is not set. Every message counts as delivered.
+pub struct FileSms {
+    path: Option<PathBuf>,
+}
+
+impl FileSms {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self { path }
+    }
+
+    async fn write(&self, to: &str, text: &str) -> anyhow::Result<SentSms> {
+        let id = format!("file-{:016x}", rand::random::<u64>());
+
+        match &self.path {
+            Some(path) => {
+                let mut line = serde_json::to_string(&serde_json::json!({
+                    "id": id,
+                    "to": to,
+                    "text": text,
+                    "sent_at": chrono::Utc::now(),
+                }))?;
+                line.push('\n');
+
+                let mut file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await?;
+                file.write_all(line.as_bytes()).await?;
+                file.flush().await?;
+            }
+            None => tracing::info!(to, text, "SMS"),
+        }
+
+        Ok(SentSms {
+            provider_message_id: Some(id),
+            status: SmsStatus::Delivered,
+        })
+    }
+}
+
+impl SmsProvider for FileSms {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn send<'a>(&'a self, to: &'a str, text: &'a str) -> BoxFuture<'a, anyhow::Result<SentSms>> {
+        self.write(to, text).boxed()
+    }
+
+    fn status<'a>(&'a self, _: &'a str) -> BoxFuture<'a, anyhow::Result<SmsStatus>> {
+        async { Ok(SmsStatus::Delivered) }.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("sms-{}.jsonl", std::process::id()));
+        let provider = FileSms::new(Some(path.clone()));
+
+        let first = provider.send("+380671234567", "Перше").await.unwrap();
+        provider.send("+380501234567", "Друге").await.unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let lines: Vec<serde_json::Value> = written
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["to"], "+380671234567");
+        assert_eq!(lines[0]["text"], "Перше");
+        assert_eq!(
+            lines[0]["id"].as_str(),
+            first.provider_message_id.as_deref()
+        );
+        assert_eq!(lines[1]["text"], "Друге");
+        assert_eq!(first.status, SmsStatus::Delivered);
+    }
+}
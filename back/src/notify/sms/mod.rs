@@ -0,0 +1,149 @@
+pub mod file;
+pub mod twilio;
+
+use futures_util::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::ToSchema;
+
+use super::log::NotificationKind;
+use crate::config::SmsConfig;
+use file::FileSms;
+use twilio::TwilioSms;
+
+/// Sent messages are checked for delivery for this long, then left as they are.
+const STATUS_CHECK_DAYS: i32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "sms_status")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SmsStatus {
+    /// Accepted by the provider, delivery not confirmed yet
+    Sent,
+    Delivered,
+    Failed,
+}
+
+pub struct SentSms {
+    pub provider_message_id: Option<String>,
+    pub status: SmsStatus,
+}
+
+/// A service that delivers text messages.
+pub trait SmsProvider: Send + Sync {
+    /// Stored with every message, e.g. `twilio`.
+    fn name(&self) -> &'static str;
+
+    fn send<'a>(&'a self, to: &'a str, text: &'a str) -> BoxFuture<'a, anyhow::Result<SentSms>>;
+
+    /// Current delivery status of a message sent earlier.
+    fn status<'a>(
+        &'a self,
+        provider_message_id: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<SmsStatus>>;
+}
+
+pub struct OutgoingSms<'a> {
+    pub kind: NotificationKind,
+    pub person_id: i32,
+    pub policy_id: Option<i32>,
+    /// E.164, see `shared::phone`
+    pub phone: &'a str,
+    pub text: &'a str,
+}
+
+/// Sends SMS through the provider chosen with `SMS_PROVIDER` (`twilio` or `file`)
+/// and records every attempt in `sms_message`.
+pub struct Sms {
+    provider: Box<dyn SmsProvider>,
+}
+
+impl Sms {
+    pub fn from_config(config: &SmsConfig) -> Self {
+        let provider: Box<dyn SmsProvider> = match config {
+            SmsConfig::Twilio(config) => Box::new(TwilioSms::new(config)),
+            SmsConfig::File(path) => Box::new(FileSms::new(path.clone())),
+        };
+        Self::new(provider)
+    }
+
+    pub fn new(provider: Box<dyn SmsProvider>) -> Self {
+        Self { provider }
+    }
+
+    pub async fn send(&self, pool: &PgPool, sms: OutgoingSms<'_>) -> anyhow::Result<()> {
+        let result = self.provider.send(sms.phone, sms.text).await;
+
+        let (provider_message_id, status, error) = match &result {
+            Ok(sent) => (sent.provider_message_id.clone(), sent.status, None),
+            Err(err) => (None, SmsStatus::Failed, Some(format!("{:#}", err))),
+        };
+
+        sqlx::query!(
+            r#"
+            insert into sms_message
+                (kind, person_id, policy_id, phone, text, provider, provider_message_id, status, error)
+            values ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            sms.kind as NotificationKind,
+            sms.person_id,
+            sms.policy_id,
+            sms.phone,
+            sms.text,
+            self.provider.name(),
+            provider_message_id,
+            status as SmsStatus,
+            error
+        )
+        .execute(pool)
+        .await?;
+
+        match result {
+            Ok(sent) if sent.status == SmsStatus::Failed => {
+                anyhow::bail!("SMS to {} was rejected", sms.phone)
+            }
+            Ok(_) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Asks the provider about recent messages that are not delivered yet.
+    /// Returns how many changed status.
+    pub async fn refresh_statuses(&self, pool: &PgPool) -> anyhow::Result<usize> {
+        let pending = sqlx::query!(
+            r#"
+            select id, provider_message_id as "provider_message_id!"
+            from sms_message
+            where
+                status = 'sent'
+                and provider = $1
+                and provider_message_id is not null
+                and created_at > now() - make_interval(days => $2)
+            "#,
+            self.provider.name(),
+            STATUS_CHECK_DAYS
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut changed = 0;
+        for message in pending {
+            let status = self.provider.status(&message.provider_message_id).await?;
+            if status == SmsStatus::Sent {
+                continue;
+            }
+
+            sqlx::query!(
+                "update sms_message set status = $2, updated_at = now() where id = $1",
+                message.id,
+                status as SmsStatus
+            )
+            .execute(pool)
+            .await?;
+            changed += 1;
+        }
+
+        Ok(changed)
+    }
+}
@@ -0,0 +1,114 @@
+use futures_util::FutureExt;
+use futures_util::future::BoxFuture;
+use reqwest::Url;
+use serde::Deserialize;
+
+use super::{SentSms, SmsProvider, SmsStatus};
+use crate::config::TwilioConfig;
+
+/// Twilio Programmable Messaging. Configured with `TWILIO_ACCOUNT_SID`,
+/// `TWILIO_AUTH_TOKEN` and `TWILIO_FROM` (a number or alphanumeric sender ID).
+pub struct TwilioSms {
+    api_url: Url,
+    account_sid: String,
+    auth_token: String,
+    from: String,
+    http_client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct TwilioMessage {
+    sid: String,
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct TwilioError {
+    message: String,
+}
+
+impl TwilioSms {
+    pub fn new(config: &TwilioConfig) -> Self {
+        Self {
+            api_url: config.api_url.clone(),
+            account_sid: config.account_sid.clone(),
+            auth_token: config.auth_token.clone(),
+            from: config.from.clone(),
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    fn messages_url(&self, message_sid: Option<&str>) -> anyhow::Result<Url> {
+        let path = match message_sid {
+            Some(sid) => format!(
+                "2010-04-01/Accounts/{}/Messages/{}.json",
+                self.account_sid, sid
+            ),
+            None => format!("2010-04-01/Accounts/{}/Messages.json", self.account_sid),
+        };
+        Ok(self.api_url.join(&path)?)
+    }
+
+    async fn request(&self, request: reqwest::RequestBuilder) -> anyhow::Result<TwilioMessage> {
+        let response = request
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let message = response
+                .json::<TwilioError>()
+                .await
+                .map(|error| error.message)
+                .unwrap_or_default();
+            anyhow::bail!("Twilio returned {}: {}", status, message);
+        }
+        Ok(response.json().await?)
+    }
+
+    async fn create_message(&self, to: &str, text: &str) -> anyhow::Result<SentSms> {
+        let request = self.http_client.post(self.messages_url(None)?).form(&[
+            ("To", to),
+            ("From", &self.from),
+            ("Body", text),
+        ]);
+        let message = self.request(request).await?;
+
+        Ok(SentSms {
+            provider_message_id: Some(message.sid),
+            status: map_status(&message.status),
+        })
+    }
+
+    async fn fetch_status(&self, sid: &str) -> anyhow::Result<SmsStatus> {
+        let request = self.http_client.get(self.messages_url(Some(sid))?);
+        let message = self.request(request).await?;
+        Ok(map_status(&message.status))
+    }
+}
+
+fn map_status(status: &str) -> SmsStatus {
+    match status {
+        "delivered" | "read" => SmsStatus::Delivered,
+        "failed" | "undelivered" | "canceled" => SmsStatus::Failed,
+        _ => SmsStatus::Sent,
+    }
+}
+
+impl SmsProvider for TwilioSms {
+    fn name(&self) -> &'static str {
+        "twilio"
+    }
+
+    fn send<'a>(&'a self, to: &'a str, text: &'a str) -> BoxFuture<'a, anyhow::Result<SentSms>> {
+        self.create_message(to, text).boxed()
+    }
+
+    fn status<'a>(
+        &'a self,
+        provider_message_id: &'a str,
+    ) -> BoxFuture<'a, anyhow::Result<SmsStatus>> {
+        self.fetch_status(provider_message_id).boxed()
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use anyhow::Context;
+use minijinja::{Environment, UndefinedBehavior};
+use serde::Serialize;
+
+pub const BIRTHDAY_GREETING: &str = "birthday_greeting.txt";
+pub const BIRTHDAY_GREETING_SMS: &str = "birthday_greeting.sms.txt";
+pub const EXPIRY_REMINDER: &str = "expiry_reminder.txt";
+pub const EXPIRY_REMINDER_SMS: &str = "expiry_reminder.sms.txt";
+pub const EXPIRY_DIGEST: &str = "expiry_digest.txt";
+pub const PAYMENT_REMINDER: &str = "payment_reminder.txt";
+pub const PAYMENT_REMINDER_SMS: &str = "payment_reminder.sms.txt";
+
+const DEFAULTS: &[(&str, &str)] = &[
+    (
+        BIRTHDAY_GREETING,
+        include_str!("../../assets/templates/birthday_greeting.txt"),
+    ),
+    (
+        BIRTHDAY_GREETING_SMS,
+        include_str!("../../assets/templates/birthday_greeting.sms.txt"),
+    ),
+    (
+        EXPIRY_REMINDER,
+        include_str!("../../assets/templates/expiry_reminder.txt"),
+    ),
+    (
+        EXPIRY_REMINDER_SMS,
+        include_str!("../../assets/templates/expiry_reminder.sms.txt"),
+    ),
+    (
+        EXPIRY_DIGEST,
+        include_str!("../../assets/templates/expiry_digest.txt"),
+    ),
+    (
+        PAYMENT_REMINDER,
+        include_str!("../../assets/templates/payment_reminder.txt"),
+    ),
+    (
+        PAYMENT_REMINDER_SMS,
+        include_str!("../../assets/templates/payment_reminder.sms.txt"),
+    ),
+];
+
+pub struct RenderedEmail {
+    pub subject: String,
+    pub body: String,
+}
+
+/// Jinja templates for outgoing messages. Email templates start with a `Subject:`
+/// line, followed by a blank line and the body; SMS templates (`*.sms.txt`) are
+/// plain text. Built-in templates can be replaced by files of the same name in
+/// `NOTIFY_TEMPLATE_DIR`.
+pub struct Templates {
+    env: Environment<'static>,
+}
+
+impl Templates {
+    pub fn load(dir: Option<&Path>) -> anyhow::Result<Self> {
+        let mut env = Environment::new();
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+
+        for (name, default) in DEFAULTS {
+            let source = match dir.map(|dir| dir.join(name)).filter(|path| path.exists()) {
+                Some(path) => std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read template {}", path.display()))?,
+                None => default.to_string(),
+            };
+            env.add_template_owned(*name, source)
+                .with_context(|| format!("Invalid template {}", name))?;
+        }
+
+        Ok(Self { env })
+    }
+
+    pub fn render_text(&self, name: &str, context: impl Serialize) -> anyhow::Result<String> {
+        let text = self
+            .env
+            .get_template(name)?
+            .render(context)
+            .with_context(|| format!("Failed to render template {}", name))?;
+        Ok(text.trim().to_owned())
+    }
+
+    pub fn render(&self, name: &str, context: impl Serialize) -> anyhow::Result<RenderedEmail> {
+        let text = self.render_text(name, context)?;
+
+        let (first_line, body) = text.split_once('\n').unwrap_or((&text, ""));
+        let subject = first_line
+            .strip_prefix("Subject:")
+            .with_context(|| format!("Template {} must start with a Subject: line", name))?;
+
+        Ok(RenderedEmail {
+            subject: subject.trim().to_owned(),
+            body: body.trim_start_matches(['\r', '\n']).to_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_builtin_digest() {
+        let templates = Templates::load(None).unwrap();
+        let email = templates
+            .render(
+                EXPIRY_DIGEST,
+                json!({
+                    "agent": { "full_name": "Ткаченко Олена" },
+                    "policies": [
+                        { "series": "ЕР", "number": "123", "type": "ОСЦПВ", "end_date": "20.10.2026", "days_left": 1, "holder": "Яременко Іван" },
+                        { "series": "АМ", "number": "456", "type": "ОСЦПВ", "end_date": "25.10.2026", "days_left": 6, "holder": "Мельник Петро" },
+                    ],
+                }),
+            )
+            .unwrap();
+
+        assert_eq!(email.subject, "Поліси клієнтів, що закінчуються: 2");
+        assert!(email.body.starts_with("Ткаченко Олена, "));
+        assert!(
+            email
+                .body
+                .ends_with("- 25.10.2026 (днів: 6) · ОСЦПВ АМ 456 · Мельник Петро")
+        );
+    }
+
+    #[test]
+    fn directory_overrides_builtin() {
+        let dir = std::env::temp_dir().join(format!("templates-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(EXPIRY_REMINDER),
+            "Subject: {{ policy.number }}\n\nHi {{ holder.first_name }}",
+        )
+        .unwrap();
+
+        let templates = Templates::load(Some(&dir)).unwrap();
+        let email = templates
+            .render(
+                EXPIRY_REMINDER,
+                json!({ "holder": { "first_name": "Іван" }, "policy": { "number": "7" } }),
+            )
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(email.subject, "7");
+        assert_eq!(email.body, "Hi Іван");
+    }
+
+    #[test]
+    fn rejects_unknown_variables() {
+        let templates = Templates::load(None).unwrap();
+        assert!(templates.render(EXPIRY_REMINDER, json!({})).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+//! The OpenAPI document, generated from the `#[utoipa::path]` attributes on
+//! the handlers and the schemas of their request and response types. Routes
+//! are registered through the same attributes, so they cannot drift apart.
+//!
+//! `openapi.json` next to `Cargo.toml` is the committed copy the front end is
+//! generated from. A test fails when it is out of date; regenerate it with
+//! `UPDATE_OPENAPI=1 cargo test openapi`.
+
+use axum::Router;
+use utoipa::openapi::path::{ParameterBuilder, ParameterIn};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::openapi::{Object, Required, ResponseBuilder, Type};
+use utoipa::{Modify, OpenApi};
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::auth::cookie::AUTH_COOKIE_NAME;
+use crate::state::AppState;
+use crate::{auth, endpoints};
+
+pub const SPEC_PATH: &str = "/openapi.json";
+pub const DOCS_PATH: &str = "/docs";
+
+/// Security scheme name, also used as a literal in `#[openapi(security)]`
+const SESSION: &str = "session";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Insure Agent API",
+        description = "Errors answer with a JSON body `{\"message\", \"request_id\"}`, \
+            both optional, unless the operation describes its own. These bodies used to be \
+            plain text. `/auth` errors answer with `{\"error\"}`."
+    ),
+    modifiers(&SessionCookie),
+    security(("session" = []))
+)]
+struct ApiDoc;
+
+/// Every route requires the session cookie set by `/auth/callback`, unless
+/// its operation says otherwise.
+struct SessionCookie;
+
+impl Modify for SessionCookie {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi
+            .components
+            .get_or_insert_default()
+            .add_security_scheme(
+                SESSION,
+                SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(AUTH_COOKIE_NAME))),
+            );
+    }
+}
+
+/// `Idempotency-Key` on every POST with a JSON body, see `crate::idempotency`.
+/// Applied after the routes are merged in, unlike the modifiers of `ApiDoc`.
+struct IdempotencyKey;
+
+impl Modify for IdempotencyKey {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let operations = openapi
+            .paths
+            .paths
+            .iter_mut()
+            .filter(|(path, _)| !path.starts_with("/auth/"))
+            .filter_map(|(_, item)| item.post.as_mut())
+            .filter(|operation| {
+                operation
+                    .request_body
+                    .as_ref()
+                    .is_some_and(|body| body.content.contains_key("application/json"))
+            });
+
+        for operation in operations {
+            let parameter = ParameterBuilder::new()
+                .name("Idempotency-Key")
+                .parameter_in(ParameterIn::Header)
+                .required(Required::False)
+                .description(Some(
+                    "Replays the stored response to retries of this request",
+                ))
+                .schema(Some(Object::with_type(Type::String)))
+                .build();
+            operation.parameters.get_or_insert_default().push(parameter);
+            operation.responses.responses.insert(
+                "422".to_owned(),
+                ResponseBuilder::new()
+                    .description("Idempotency-Key was used for a different request")
+                    .build()
+                    .into(),
+            );
+        }
+    }
+}
+
+fn api() -> (Router<AppState>, utoipa::openapi::OpenApi) {
+    let (router, mut openapi) = OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .merge(endpoints::router())
+        .merge(auth::router())
+        .split_for_parts();
+    IdempotencyKey.modify(&mut openapi);
+    (router, openapi)
+}
+
+/// All API routes, plus the document at `/openapi.json` and Swagger UI at `/docs`.
+pub fn router() -> Router<AppState> {
+    let (router, openapi) = api();
+    router.merge(SwaggerUi::new(DOCS_PATH).url(SPEC_PATH, openapi))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openapi_json_is_up_to_date() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+        let generated = api().1.to_pretty_json().unwrap() + "\n";
+
+        if std::env::var("UPDATE_OPENAPI").is_ok() {
+            std::fs::write(path, &generated).unwrap();
+            return;
+        }
+
+        let committed = std::fs::read_to_string(path).unwrap_or_default();
+        assert!(
+            committed == generated,
+            "openapi.json does not match the handlers, regenerate it with \
+             `UPDATE_OPENAPI=1 cargo test openapi` and commit the result"
+        );
+    }
+}
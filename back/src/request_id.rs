@@ -0,0 +1,87 @@
+//! Ties a request to its log lines. Each request gets an id, the one sent in
+//! `X-Request-Id` when it looks like one, or else a new UUID. It is recorded
+//! on the request span, returned in the `X-Request-Id` response header and
+//! included in error bodies, so a reported error can be found in the log.
+
+use axum::extract::Request;
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::Span;
+use uuid::Uuid;
+
+pub const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+const MAX_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: String;
+}
+
+/// Id of the request being handled, if any.
+pub fn current() -> Option<String> {
+    CURRENT.try_with(String::clone).ok()
+}
+
+/// A client's id is kept if it is short and plain enough to log as is.
+fn accepted(value: &HeaderValue) -> Option<String> {
+    let value = value.to_str().ok()?.trim();
+    let valid = !value.is_empty()
+        && value.len() <= MAX_LENGTH
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    valid.then(|| value.to_owned())
+}
+
+pub async fn request_id_middleware(request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID)
+        .and_then(accepted)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    Span::current().record("request_id", &id);
+
+    let mut response = CURRENT.scope(id.clone(), next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Method, StatusCode};
+    use sqlx::PgPool;
+
+    use super::REQUEST_ID;
+    use crate::testing::TestApp;
+
+    #[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+    async fn returned_with_errors(pool: PgPool) {
+        let app = TestApp::new(pool).await;
+
+        let response = app.get("/people/0").await;
+        let id = response.headers[&REQUEST_ID].to_str().unwrap().to_owned();
+        assert!(uuid::Uuid::parse_str(&id).is_ok(), "{}", id);
+        assert_eq!(response.expect(StatusCode::NOT_FOUND)["request_id"], id);
+
+        let response = app.get("/people").await;
+        assert_ne!(response.headers[&REQUEST_ID], id.as_str());
+    }
+
+    #[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+    async fn accepted_from_client(pool: PgPool) {
+        let app = TestApp::new(pool).await;
+
+        let sent = [(REQUEST_ID, "front-1f3a.7")];
+        let response = app.send(Method::GET, "/people/0", &sent, None).await;
+        assert_eq!(response.headers[&REQUEST_ID], "front-1f3a.7");
+        assert_eq!(response.body["request_id"], "front-1f3a.7");
+
+        let sent = [(REQUEST_ID, "drop table person;")];
+        let response = app.send(Method::GET, "/people", &sent, None).await;
+        let id = response.headers[&REQUEST_ID].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(id).is_ok(), "{}", id);
+    }
+}
@@ -0,0 +1,59 @@
+pub mod model;
+
+use model::{Attachment, AttachmentOwner};
+
+/// Whether the record attachments are added to or listed for exists.
+pub async fn owner_exists(
+    executor: impl sqlx::PgExecutor<'_>,
+    owner: AttachmentOwner,
+    owner_id: i32,
+) -> sqlx::Result<bool> {
+    let exists = match owner {
+        AttachmentOwner::Policy => {
+            sqlx::query_scalar!(
+                r#"select exists(select 1 from policy where id = $1 and tenant_id = current_tenant()) as "e!""#,
+                owner_id
+            )
+            .fetch_one(executor)
+            .await?
+        }
+        AttachmentOwner::Person => {
+            sqlx::query_scalar!(
+                r#"select exists(select 1 from person where id = $1 and tenant_id = current_tenant()) as "e!""#,
+                owner_id
+            )
+            .fetch_one(executor)
+            .await?
+        }
+        AttachmentOwner::Car => {
+            sqlx::query_scalar!(
+                r#"select exists(select 1 from car where id = $1 and tenant_id = current_tenant()) as "e!""#,
+                owner_id
+            )
+            .fetch_one(executor)
+            .await?
+        }
+    };
+    Ok(exists)
+}
+
+/// Attachments of a record, newest first.
+pub async fn for_owner(
+    executor: impl sqlx::PgExecutor<'_>,
+    owner: AttachmentOwner,
+    owner_id: i32,
+) -> sqlx::Result<Vec<Attachment>> {
+    sqlx::query_as!(
+        Attachment,
+        r#"
+        select id, file_name, content_type, size_bytes, sha256, uploaded_by, uploaded_at
+        from attachment
+        where owner_type = $1 and owner_id = $2 and tenant_id = current_tenant()
+        order by uploaded_at desc
+        "#,
+        owner as AttachmentOwner,
+        owner_id
+    )
+    .fetch_all(executor)
+    .await
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Clone, Copy, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "attachment_owner")]
+#[sqlx(rename_all = "snake_case")]
+pub enum AttachmentOwner {
+    Policy,
+    Person,
+    Car,
+}
+
+impl AttachmentOwner {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Policy => "policy",
+            Self::Person => "person",
+            Self::Car => "car",
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Attachment {
+    pub id: i32,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub sha256: String,
+    pub uploaded_by: Option<String>,
+    pub uploaded_at: chrono::DateTime<chrono::Utc>,
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct CarNew {
     pub chassis: String,
     pub make: String,
@@ -16,19 +17,21 @@ pub struct CarNew {
     pub seats: i32,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 #[serde(tag = "kind")]
 pub enum CarRef {
     Existing { id: i32 },
     New(Box<CarNew>),
     ExistingWithUpdates {
         id: i32,
+        /// Refuse the update if the car has changed since this version
+        version: Option<i32>,
         #[serde(flatten)]
         data: Box<CarNew>,
     },
 }
 
-#[derive(FromRow, Serialize)]
+#[derive(FromRow, Serialize, ToSchema)]
 pub struct CarFull {
     pub id: i32,
     pub chassis: String,
@@ -42,4 +45,6 @@ pub struct CarFull {
     pub unladen_weight: i32,
     pub laden_weight: i32,
     pub seats: i32,
+    /// Send back in `If-Match` when updating, see `shared::version`
+    pub version: i32,
 }
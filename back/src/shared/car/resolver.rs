@@ -1,18 +1,143 @@
-use super::model::CarRef;
+use anyhow::Context;
+use axum::http::StatusCode;
+
+use super::model::{CarFull, CarRef};
+use crate::error::{AppError, AppResult};
 use crate::shared::IdResult;
+use crate::shared::existing::{ExistingConflict, OnExisting};
+use crate::shared::version;
+
+/// A stored car with the same chassis or, when no chassis is given, the same
+/// plate. Plates get reissued, so they do not override a differing chassis.
+pub async fn find_car(
+    executor: impl sqlx::PgExecutor<'_>,
+    chassis: &str,
+    plate: &str,
+) -> sqlx::Result<Option<(i32, &'static str)>> {
+    let chassis = chassis.trim();
+    if !chassis.is_empty() {
+        let id = sqlx::query_scalar!(
+            r#"
+            select id from car
+            where tenant_id = current_tenant() and chassis <> '' and upper(chassis) = upper($1)
+            order by id
+            limit 1
+            "#,
+            chassis
+        )
+        .fetch_optional(executor)
+        .await?;
+        return Ok(id.map(|id| (id, "chassis")));
+    }
+
+    let plate = normalize_plate(plate);
+    if plate.is_empty() {
+        return Ok(None);
+    }
+    let id = sqlx::query_scalar!(
+        r#"
+        select id from car
+        where tenant_id = current_tenant()
+            and upper(regexp_replace(plate, '[\s-]', '', 'g')) = $1
+        order by id
+        limit 1
+        "#,
+        plate
+    )
+    .fetch_optional(executor)
+    .await?;
+    Ok(id.map(|id| (id, "plate")))
+}
+
+pub fn normalize_plate(plate: &str) -> String {
+    plate
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_uppercase()
+}
+
+fn car_conflict(existing_id: i32, matched_on: &'static str) -> ExistingConflict {
+    ExistingConflict {
+        entity: "car",
+        existing_id,
+        matched_on,
+        message: format!("Car with the same {} already exists", matched_on),
+    }
+}
+
+/// What a car submitted as new resolves to when it matches a stored one.
+fn on_taken(
+    (existing_id, matched_on): (i32, &'static str),
+    on_existing: OnExisting,
+) -> AppResult<i32> {
+    match on_existing {
+        OnExisting::Reuse => Ok(existing_id),
+        OnExisting::Conflict => Err(car_conflict(existing_id, matched_on).into()),
+    }
+}
+
+/// The car with its row locked until the transaction ends.
+async fn lock_car(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: i32,
+) -> sqlx::Result<Option<CarFull>> {
+    sqlx::query_as!(
+        CarFull,
+        r#"
+        select
+            id,
+            chassis,
+            make,
+            model,
+            registration,
+            plate,
+            year,
+            engine_displacement_litres,
+            mileage_km,
+            unladen_weight,
+            laden_weight,
+            seats,
+            version
+        from car
+        where id = $1 and tenant_id = current_tenant()
+        for update
+        "#,
+        id
+    )
+    .fetch_optional(&mut **tx)
+    .await
+}
 
 pub async fn resolve_car(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     car_ref: CarRef,
-) -> Result<i32, sqlx::Error> {
+    on_existing: OnExisting,
+) -> AppResult<i32> {
     match car_ref {
-        CarRef::Existing { id } => Ok(id),
+        CarRef::Existing { id } => {
+            // Another tenant's car counts as missing
+            sqlx::query_scalar!(
+                "select id from car where id = $1 and tenant_id = current_tenant()",
+                id
+            )
+            .fetch_optional(&mut **tx)
+            .await?
+            .ok_or_else(|| {
+                AppError::status(StatusCode::NOT_FOUND, format!("No car with id {}", id))
+            })
+        }
         CarRef::New(car) => {
+            if let Some(existing) = find_car(&mut **tx, &car.chassis, &car.plate).await? {
+                return on_taken(existing, on_existing);
+            }
+
             let result = sqlx::query_as!(
                 IdResult,
                 r#"
                 insert into car (chassis, make, model, registration, plate, year, engine_displacement_litres, mileage_km, unladen_weight, laden_weight, seats)
                 values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                on conflict do nothing
                 returning id
                 "#,
                 car.chassis,
@@ -27,11 +152,32 @@ pub async fn resolve_car(
                 car.laden_weight,
                 car.seats
             )
-            .fetch_one(&mut **tx)
+            .fetch_optional(&mut **tx)
             .await?;
-            Ok(result.id)
+            match result {
+                Some(result) => Ok(result.id),
+                // Taken by a concurrent request since the check above
+                None => {
+                    let existing = find_car(&mut **tx, &car.chassis, "")
+                        .await?
+                        .context("Car insert conflicted without a match")?;
+                    on_taken(existing, on_existing)
+                }
+            }
         }
-        CarRef::ExistingWithUpdates { id, data } => {
+        CarRef::ExistingWithUpdates { id, version, data } => {
+            let current = lock_car(tx, id).await?.ok_or(AppError::not_found())?;
+            if version::is_stale(version, current.version) {
+                return Err(version::conflict("car", current));
+            }
+
+            // Only the chassis is unique, a plate may have moved to this car
+            if let Some((existing_id, "chassis")) = find_car(&mut **tx, &data.chassis, "").await?
+                && existing_id != id
+            {
+                return Err(car_conflict(existing_id, "chassis").into());
+            }
+
             sqlx::query!(
                 r#"
                 update car set
@@ -45,8 +191,9 @@ pub async fn resolve_car(
                     mileage_km = $9,
                     unladen_weight = $10,
                     laden_weight = $11,
-                    seats = $12
-                where id = $1
+                    seats = $12,
+                    version = version + 1
+                where id = $1 and tenant_id = current_tenant()
                 "#,
                 id,
                 data.chassis,
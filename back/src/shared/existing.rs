@@ -0,0 +1,30 @@
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+
+/// What to do when a person or car submitted as new matches a stored one.
+#[derive(Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OnExisting {
+    /// Fail with 409 and the id of the stored record
+    #[default]
+    Conflict,
+    /// Use the stored record instead of creating another one
+    Reuse,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ExistingConflict {
+    pub entity: &'static str,
+    pub existing_id: i32,
+    pub matched_on: &'static str,
+    pub message: String,
+}
+
+impl From<ExistingConflict> for AppError {
+    fn from(conflict: ExistingConflict) -> Self {
+        AppError::json(StatusCode::CONFLICT, conflict)
+    }
+}
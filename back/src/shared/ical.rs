@@ -0,0 +1,171 @@
+//! A minimal RFC 5545 (iCalendar) writer for all-day events, enough for
+//! calendar apps to subscribe to a feed.
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Content lines are limited to 75 octets, longer ones continue on lines starting with a space.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// How often subscribed calendar apps should refetch the feed.
+const REFRESH_INTERVAL: &str = "PT1H";
+
+pub enum Recurrence {
+    None,
+    Yearly,
+    /// Yearly on the last day of February, for dates on February 29
+    YearlyLastDayOfFebruary,
+}
+
+pub struct Event {
+    /// Stable across fetches, so apps update events instead of duplicating them
+    pub uid: String,
+    pub date: NaiveDate,
+    pub summary: String,
+    pub description: String,
+    pub category: &'static str,
+    pub recurrence: Recurrence,
+}
+
+pub struct Calendar {
+    out: String,
+    stamp: String,
+}
+
+impl Calendar {
+    pub fn new(name: &str, stamp: DateTime<Utc>) -> Self {
+        let mut calendar = Self {
+            out: String::new(),
+            stamp: stamp.format("%Y%m%dT%H%M%SZ").to_string(),
+        };
+        calendar.line("BEGIN:VCALENDAR");
+        calendar.line("VERSION:2.0");
+        calendar.line("PRODID:-//insure-agent//Calendar//UK");
+        calendar.line("CALSCALE:GREGORIAN");
+        calendar.line("METHOD:PUBLISH");
+        calendar.line(&format!("X-WR-CALNAME:{}", escape(name)));
+        calendar.line(&format!(
+            "REFRESH-INTERVAL;VALUE=DURATION:{}",
+            REFRESH_INTERVAL
+        ));
+        calendar.line(&format!("X-PUBLISHED-TTL:{}", REFRESH_INTERVAL));
+        calendar
+    }
+
+    pub fn event(&mut self, event: &Event) {
+        let end = event.date.succ_opt().unwrap_or(event.date);
+
+        self.line("BEGIN:VEVENT");
+        self.line(&format!("UID:{}", escape(&event.uid)));
+        self.line(&format!("DTSTAMP:{}", self.stamp));
+        self.line(&format!(
+            "DTSTART;VALUE=DATE:{}",
+            event.date.format("%Y%m%d")
+        ));
+        self.line(&format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+        match event.recurrence {
+            Recurrence::None => {}
+            Recurrence::Yearly => self.line("RRULE:FREQ=YEARLY"),
+            Recurrence::YearlyLastDayOfFebruary => {
+                self.line("RRULE:FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=-1")
+            }
+        }
+        self.line(&format!("SUMMARY:{}", escape(&event.summary)));
+        if !event.description.is_empty() {
+            self.line(&format!("DESCRIPTION:{}", escape(&event.description)));
+        }
+        self.line(&format!("CATEGORIES:{}", escape(event.category)));
+        self.line("TRANSP:TRANSPARENT");
+        self.line("END:VEVENT");
+    }
+
+    pub fn finish(mut self) -> String {
+        self.line("END:VCALENDAR");
+        self.out
+    }
+
+    /// Writes a content line, folded at 75 octets without splitting characters.
+    fn line(&mut self, line: &str) {
+        let mut octets = 0;
+        for c in line.chars() {
+            if octets + c.len_utf8() > MAX_LINE_OCTETS {
+                self.out.push_str("\r\n ");
+                octets = 1;
+            }
+            self.out.push(c);
+            octets += c.len_utf8();
+        }
+        self.out.push_str("\r\n");
+    }
+}
+
+/// Escapes a TEXT value.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stamp() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_792_396_800, 0).unwrap()
+    }
+
+    #[test]
+    fn escapes_text() {
+        assert_eq!(escape("a,b;c\\d\r\ne"), "a\\,b\\;c\\\\d\\ne");
+    }
+
+    #[test]
+    fn folds_long_lines_on_character_boundaries() {
+        let mut calendar = Calendar::new("Test", stamp());
+        calendar.event(&Event {
+            uid: "policy-end-1@insure-agent".to_owned(),
+            date: NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+            summary: "Закінчується поліс ОСЦПВ АА 1234567, Шевченко Тарас Григорович".to_owned(),
+            description: String::new(),
+            category: "Поліси",
+            recurrence: Recurrence::None,
+        });
+        let ics = calendar.finish();
+
+        for line in ics.split("\r\n") {
+            assert!(line.len() <= MAX_LINE_OCTETS, "{}", line);
+        }
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded.contains(
+            "SUMMARY:Закінчується поліс ОСЦПВ АА 1234567\\, Шевченко Тарас Григорович\r\n"
+        ));
+    }
+
+    #[test]
+    fn writes_all_day_events() {
+        let mut calendar = Calendar::new("Test", stamp());
+        calendar.event(&Event {
+            uid: "birthday-7@insure-agent".to_owned(),
+            date: NaiveDate::from_ymd_opt(2000, 2, 29).unwrap(),
+            summary: "День народження".to_owned(),
+            description: "Телефон: 067".to_owned(),
+            category: "Дні народження",
+            recurrence: Recurrence::YearlyLastDayOfFebruary,
+        });
+        let ics = calendar.finish();
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTAMP:20261019T080000Z\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20000229\r\nDTEND;VALUE=DATE:20000301\r\n"));
+        assert!(ics.contains("RRULE:FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=-1\r\n"));
+    }
+}
@@ -0,0 +1,18 @@
+pub mod model;
+
+use axum::http::StatusCode;
+
+use crate::error::AppError;
+
+/// Another insurer of the tenant already has the name.
+pub fn name_taken(err: sqlx::Error, name: &str) -> AppError {
+    match &err {
+        sqlx::Error::Database(db) if db.constraint() == Some("insurer_name_key") => {
+            AppError::status(
+                StatusCode::CONFLICT,
+                format!("Insurer {} already exists", name),
+            )
+        }
+        _ => err.into(),
+    }
+}
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Insurer {
+    pub id: i32,
+    pub name: String,
+}
@@ -1,7 +1,17 @@
+pub mod attachment;
 pub mod car;
+pub mod existing;
+pub mod ical;
 pub mod id_result;
+pub mod insurer;
+pub mod page;
+pub mod payment;
 pub mod person;
+pub mod phone;
 pub mod policy;
+pub mod translit;
+pub mod version;
+pub mod vocative;
 
 pub use id_result::IdResult;
 
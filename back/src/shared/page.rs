@@ -0,0 +1,58 @@
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::error::{AppError, AppResult};
+
+const MAX_LIMIT: i64 = 500;
+
+#[derive(Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Offset pagination and sorting parameters shared by the list endpoints.
+/// Extracted as a separate `Query` next to the endpoint's own filters.
+#[derive(Deserialize, IntoParams)]
+pub struct PageQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub sort: Option<String>,
+    pub order: Option<SortOrder>,
+}
+
+impl PageQuery {
+    pub fn limit(&self, default: i64) -> i64 {
+        self.limit.unwrap_or(default).clamp(1, MAX_LIMIT)
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+
+    /// Returns the requested sort column, rejecting anything not in `allowed`.
+    pub fn sort(&self, allowed: &[&str]) -> AppResult<Option<String>> {
+        match self.sort.as_deref().filter(|s| !s.is_empty()) {
+            None => Ok(None),
+            Some(sort) if allowed.contains(&sort) => Ok(Some(sort.to_owned())),
+            Some(sort) => Err(AppError::status(
+                StatusCode::BAD_REQUEST,
+                format!("Cannot sort by '{}'", sort),
+            )),
+        }
+    }
+
+    pub fn descending(&self) -> bool {
+        matches!(self.order, Some(SortOrder::Desc))
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
@@ -0,0 +1,20 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A scheduled payment for a policy, e.g. one instalment of the premium.
+#[derive(Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Payment {
+    pub id: i32,
+    pub policy_id: i32,
+    pub due_date: NaiveDate,
+    pub amount: i32,
+    pub paid_on: Option<NaiveDate>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PaymentNew {
+    pub due_date: NaiveDate,
+    pub amount: i32,
+    pub paid_on: Option<NaiveDate>,
+}
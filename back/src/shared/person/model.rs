@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Clone, Copy, Serialize, Deserialize, sqlx::Type)]
+use crate::shared::translit::latin_or;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "person_status")]
 #[sqlx(rename_all = "snake_case")]
 pub enum PersonStatus {
@@ -9,7 +12,7 @@ pub enum PersonStatus {
     Archived,
 }
 
-#[derive(Serialize, Deserialize, sqlx::Type)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "sex")]
 #[sqlx(rename_all = "snake_case")]
 pub enum Sex {
@@ -18,7 +21,7 @@ pub enum Sex {
     Unknown,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct PersonNew {
     pub first_name: String,
     pub first_name_lat: Option<String>,
@@ -35,19 +38,33 @@ pub struct PersonNew {
     pub status: PersonStatus,
 }
 
-#[derive(Deserialize)]
+impl PersonNew {
+    /// Transliterates the names whose Latin spelling was left empty.
+    pub fn fill_latin_names(&mut self) {
+        self.first_name_lat = latin_or(self.first_name_lat.take(), Some(&self.first_name));
+        self.last_name_lat = latin_or(self.last_name_lat.take(), Some(&self.last_name));
+        self.patronymic_name_lat = latin_or(
+            self.patronymic_name_lat.take(),
+            self.patronymic_name.as_deref(),
+        );
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
 #[serde(tag = "kind")]
 pub enum PersonRef {
     Existing { id: i32 },
     New(Box<PersonNew>),
     ExistingWithUpdates {
         id: i32,
+        /// Refuse the update if the person has changed since this version
+        version: Option<i32>,
         #[serde(flatten)]
         data: Box<PersonNew>,
     },
 }
 
-#[derive(Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct PersonFull {
     pub id: i32,
     pub first_name: String,
@@ -63,4 +80,6 @@ pub struct PersonFull {
     pub phone2: Option<String>,
     pub email: String,
     pub status: PersonStatus,
+    /// Send back in `If-Match` when updating, see `shared::version`
+    pub version: i32,
 }
@@ -1,19 +1,143 @@
-use super::model::{PersonRef, PersonStatus, Sex};
-use crate::shared::IdResult;
+use anyhow::Context;
+use axum::http::StatusCode;
+
+use super::model::{PersonFull, PersonRef, PersonStatus, Sex};
+use crate::error::{AppError, AppResult};
+use crate::shared::existing::{ExistingConflict, OnExisting};
+use crate::shared::version;
+use crate::webhook::{self, EventType};
+
+/// Non-archived person holding the tax number, if any.
+pub async fn find_person(
+    executor: impl sqlx::PgExecutor<'_>,
+    tax_number: &str,
+) -> sqlx::Result<Option<i32>> {
+    sqlx::query_scalar!(
+        r#"
+        select id from person
+        where tenant_id = current_tenant()
+            and tax_number = $1 and tax_number <> '' and status <> 'archived'
+        order by id
+        limit 1
+        "#,
+        tax_number.trim()
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+fn tax_number_conflict(existing_id: i32, tax_number: &str) -> ExistingConflict {
+    ExistingConflict {
+        entity: "person",
+        existing_id,
+        matched_on: "tax_number",
+        message: format!("Person with tax number {} already exists", tax_number),
+    }
+}
+
+/// What a person submitted as new resolves to when its tax number is taken.
+fn on_taken(existing_id: i32, tax_number: &str, on_existing: OnExisting) -> AppResult<i32> {
+    match on_existing {
+        OnExisting::Reuse => Ok(existing_id),
+        OnExisting::Conflict => Err(tax_number_conflict(existing_id, tax_number).into()),
+    }
+}
+
+/// The person with its row locked until the transaction ends.
+pub async fn lock_person(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    id: i32,
+) -> sqlx::Result<Option<PersonFull>> {
+    sqlx::query_as!(
+        PersonFull,
+        r#"
+        select
+            id,
+            first_name,
+            first_name_lat,
+            last_name,
+            last_name_lat,
+            patronymic_name,
+            patronymic_name_lat,
+            sex as "sex: Sex",
+            birth_date,
+            tax_number,
+            phone,
+            phone2,
+            email,
+            status as "status: PersonStatus",
+            version
+        from person
+        where id = $1 and tenant_id = current_tenant()
+        for update
+        "#,
+        id
+    )
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+/// Checks that no other person holds the tax number.
+pub async fn ensure_tax_number_free(
+    executor: impl sqlx::PgExecutor<'_>,
+    tax_number: &str,
+    id: Option<i32>,
+) -> AppResult<()> {
+    match find_person(executor, tax_number).await? {
+        Some(existing_id) if Some(existing_id) != id => {
+            Err(tax_number_conflict(existing_id, tax_number).into())
+        }
+        _ => Ok(()),
+    }
+}
 
 pub async fn resolve_person(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     person_ref: PersonRef,
-) -> Result<i32, sqlx::Error> {
+    on_existing: OnExisting,
+) -> AppResult<i32> {
     match person_ref {
-        PersonRef::Existing { id } => Ok(id),
-        PersonRef::New(person) => {
-            let result = sqlx::query_as!(
-                IdResult,
+        PersonRef::Existing { id } => {
+            // Another tenant's person counts as missing
+            sqlx::query_scalar!(
+                "select id from person where id = $1 and tenant_id = current_tenant()",
+                id
+            )
+            .fetch_optional(&mut **tx)
+            .await?
+            .ok_or_else(|| {
+                AppError::status(StatusCode::NOT_FOUND, format!("No person with id {}", id))
+            })
+        }
+        PersonRef::New(mut person) => {
+            if let Some(existing_id) = find_person(&mut **tx, &person.tax_number).await? {
+                return on_taken(existing_id, &person.tax_number, on_existing);
+            }
+
+            person.fill_latin_names();
+
+            let created = sqlx::query_as!(
+                PersonFull,
                 r#"
                 insert into person (first_name, first_name_lat, last_name, last_name_lat, patronymic_name, patronymic_name_lat, sex, birth_date, tax_number, phone, phone2, email, status)
                 values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
-                returning id
+                on conflict do nothing
+                returning
+                    id,
+                    first_name,
+                    first_name_lat,
+                    last_name,
+                    last_name_lat,
+                    patronymic_name,
+                    patronymic_name_lat,
+                    sex as "sex: Sex",
+                    birth_date,
+                    tax_number,
+                    phone,
+                    phone2,
+                    email,
+                    status as "status: PersonStatus",
+                    version
                 "#,
                 person.first_name,
                 person.first_name_lat,
@@ -29,11 +153,32 @@ pub async fn resolve_person(
                 person.email,
                 person.status as PersonStatus
             )
-            .fetch_one(&mut **tx)
+            .fetch_optional(&mut **tx)
             .await?;
-            Ok(result.id)
+            let Some(created) = created else {
+                // Taken by a concurrent request since the check above
+                let existing_id = find_person(&mut **tx, &person.tax_number)
+                    .await?
+                    .context("Person insert conflicted without a match")?;
+                return on_taken(existing_id, &person.tax_number, on_existing);
+            };
+
+            webhook::publish(tx, EventType::PersonCreated, &created).await?;
+            Ok(created.id)
         }
-        PersonRef::ExistingWithUpdates { id, data } => {
+        PersonRef::ExistingWithUpdates {
+            id,
+            version,
+            mut data,
+        } => {
+            let current = lock_person(tx, id).await?.ok_or(AppError::not_found())?;
+            if version::is_stale(version, current.version) {
+                return Err(version::conflict("person", current));
+            }
+
+            data.fill_latin_names();
+            ensure_tax_number_free(&mut **tx, &data.tax_number, Some(id)).await?;
+
             sqlx::query!(
                 r#"
                 update person set
@@ -49,8 +194,9 @@ pub async fn resolve_person(
                     phone = $11,
                     phone2 = $12,
                     email = $13,
-                    status = $14
-                where id = $1
+                    status = $14,
+                    version = version + 1
+                where id = $1 and tenant_id = current_tenant()
                 "#,
                 id,
                 data.first_name,
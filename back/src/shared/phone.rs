@@ -0,0 +1,99 @@
+//! Phone numbers as typed by agents ("067 123 45 67", "80671234567", "+38 (067) 123-45-67")
+//! turned into E.164 for sending SMS.
+
+/// Ukrainian mobile operator codes, the part after +380.
+const UA_MOBILE_CODES: &[&str] = &[
+    "39", "50", "63", "66", "67", "68", "73", "91", "92", "93", "94", "95", "96", "97", "98", "99",
+];
+
+/// Normalises a phone number to E.164. Numbers without a country code are read
+/// as Ukrainian. Returns `None` for anything that cannot be a full number.
+pub fn normalize_phone(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let digits: String = raw.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    let national = match digits.len() {
+        12 if digits.starts_with("380") => &digits[3..],
+        11 if digits.starts_with("80") => &digits[2..],
+        10 if digits.starts_with('0') => &digits[1..],
+        9 if !raw.starts_with('+') => &digits[..],
+        8..=15 if raw.starts_with('+') || raw.starts_with("00") => {
+            let international = digits.strip_prefix("00").unwrap_or(&digits);
+            return Some(format!("+{}", international));
+        }
+        _ => return None,
+    };
+
+    Some(format!("+380{}", national))
+}
+
+/// Whether an E.164 number can receive SMS. Foreign numbers are assumed to.
+pub fn is_mobile(e164: &str) -> bool {
+    match e164.strip_prefix("+380") {
+        Some(national) => UA_MOBILE_CODES
+            .iter()
+            .any(|code| national.starts_with(code)),
+        None => true,
+    }
+}
+
+/// The first of the given numbers that normalises to a mobile number.
+pub fn mobile_phone<'a>(phones: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    phones
+        .into_iter()
+        .filter_map(normalize_phone)
+        .find(|phone| is_mobile(phone))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_ukrainian_formats() {
+        for raw in [
+            "+380671234567",
+            "+38 (067) 123-45-67",
+            "380671234567",
+            "80671234567",
+            "0671234567",
+            "067 123 45 67",
+            "671234567",
+        ] {
+            assert_eq!(
+                normalize_phone(raw).as_deref(),
+                Some("+380671234567"),
+                "{}",
+                raw
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_foreign_numbers() {
+        assert_eq!(
+            normalize_phone("+48 601 234 567").as_deref(),
+            Some("+48601234567")
+        );
+        assert_eq!(
+            normalize_phone("0048601234567").as_deref(),
+            Some("+48601234567")
+        );
+    }
+
+    #[test]
+    fn rejects_partial_numbers() {
+        assert_eq!(normalize_phone(""), None);
+        assert_eq!(normalize_phone("12-34-56"), None);
+        assert_eq!(normalize_phone("06712345"), None);
+    }
+
+    #[test]
+    fn picks_first_mobile() {
+        assert_eq!(
+            mobile_phone(["044 123 45 67", "", "050-765-43-21"]).as_deref(),
+            Some("+380507654321")
+        );
+        assert_eq!(mobile_phone(["044 123 45 67"]), None);
+    }
+}
@@ -0,0 +1,45 @@
+//! Ukrainian display names for policy enums, used in printed and exported documents.
+
+use super::model::{CarInsurancePeriodUnit, OsagoZone, PolicyStatus, PolicyType};
+
+pub fn type_label(policy_type: PolicyType) -> &'static str {
+    match policy_type {
+        PolicyType::GreenCard => "Зелена картка",
+        PolicyType::Medassistance => "Медичний асистанс",
+        PolicyType::Osago => "ОСЦПВ",
+    }
+}
+
+pub fn period_label(units: i32, unit: CarInsurancePeriodUnit) -> String {
+    let unit = match unit {
+        CarInsurancePeriodUnit::Day => "дн.",
+        CarInsurancePeriodUnit::Month => "міс.",
+        CarInsurancePeriodUnit::Year => "р.",
+    };
+    format!("{} {}", units, unit)
+}
+
+pub fn status_label(status: PolicyStatus) -> &'static str {
+    match status {
+        PolicyStatus::Active => "Діючий",
+        PolicyStatus::Prolonged => "Пролонгований",
+        PolicyStatus::Rejected => "Відхилений",
+        PolicyStatus::Stopped => "Призупинений",
+        PolicyStatus::Postponed => "Відкладений",
+        PolicyStatus::Cancelled => "Скасований",
+        PolicyStatus::Project => "Проєкт",
+        PolicyStatus::Replaced => "Замінений",
+        PolicyStatus::Expired => "Закінчився",
+    }
+}
+
+pub fn zone_label(zone: OsagoZone) -> &'static str {
+    match zone {
+        OsagoZone::Zone1 => "Зона 1",
+        OsagoZone::Zone2 => "Зона 2",
+        OsagoZone::Zone3 => "Зона 3",
+        OsagoZone::Zone4 => "Зона 4",
+        OsagoZone::Zone5 => "Зона 5",
+        OsagoZone::Outside => "Поза зонами",
+    }
+}
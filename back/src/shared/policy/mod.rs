@@ -1 +1,3 @@
+pub mod labels;
 pub mod model;
+pub mod resolver;
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Clone, Copy, Serialize, Deserialize, sqlx::Type)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "policy_status")]
 #[sqlx(rename_all = "snake_case")]
 pub enum PolicyStatus {
@@ -15,7 +16,7 @@ pub enum PolicyStatus {
     Expired,
 }
 
-#[derive(Clone, Copy, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "policy_type")]
 #[sqlx(rename_all = "snake_case")]
 pub enum PolicyType {
@@ -24,7 +25,7 @@ pub enum PolicyType {
     Osago,
 }
 
-#[derive(Clone, Copy, Serialize, Deserialize, sqlx::Type)]
+#[derive(Clone, Copy, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "car_insurance_period_unit")]
 #[sqlx(rename_all = "snake_case")]
 pub enum CarInsurancePeriodUnit {
@@ -33,7 +34,7 @@ pub enum CarInsurancePeriodUnit {
     Year,
 }
 
-#[derive(Clone, Copy, Serialize, Deserialize, sqlx::Type)]
+#[derive(Clone, Copy, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "osago_zone")]
 #[sqlx(rename_all = "snake_case")]
 pub enum OsagoZone {
@@ -45,11 +46,12 @@ pub enum OsagoZone {
     Outside,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct PolicyShort {
     pub id: i32,
     pub policy_type: PolicyType,
     pub holder_name: String,
+    pub insurer_name: String,
     pub series: String,
     pub number: String,
     pub start_date: chrono::NaiveDate,
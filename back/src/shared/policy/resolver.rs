@@ -0,0 +1,40 @@
+use axum::http::StatusCode;
+
+use crate::error::{AppError, AppResult};
+
+/// Checks that the insurer exists for the current tenant.
+pub async fn ensure_insurer(executor: impl sqlx::PgExecutor<'_>, id: i32) -> AppResult<()> {
+    sqlx::query_scalar!(
+        "select id from insurer where id = $1 and tenant_id = current_tenant()",
+        id
+    )
+    .fetch_optional(executor)
+    .await?
+    .map(|_| ())
+    .ok_or_else(|| AppError::status(StatusCode::NOT_FOUND, format!("No insurer with id {}", id)))
+}
+
+/// Checks that every agent linked to a policy exists for the current tenant.
+pub async fn ensure_agents(executor: impl sqlx::PgExecutor<'_>, ids: &[i32]) -> AppResult<()> {
+    let missing = sqlx::query_scalar!(
+        r#"
+        select t.agent_id as "agent_id!"
+        from unnest($1::int[]) as t(agent_id)
+        where not exists (
+            select 1 from agent a where a.id = t.agent_id and a.tenant_id = current_tenant()
+        )
+        limit 1
+        "#,
+        ids
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    match missing {
+        Some(id) => Err(AppError::status(
+            StatusCode::NOT_FOUND,
+            format!("No agent with id {}", id),
+        )),
+        None => Ok(()),
+    }
+}
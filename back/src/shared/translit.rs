@@ -0,0 +1,257 @@
+//! Ukrainian to Latin transliteration per Cabinet of Ministers resolution
+//! No. 55 of 27 January 2010, the system used in Ukrainian passports.
+//! `translit_uk` in the person search migration implements the same rules in
+//! SQL, a test checks both against the examples of the resolution.
+
+fn is_apostrophe(c: char) -> bool {
+    matches!(c, '\'' | '’' | 'ʼ' | '`')
+}
+
+/// Letters and apostrophes continue a word, anything else starts a new one.
+fn is_word_char(c: char) -> bool {
+    c.is_alphabetic() || is_apostrophe(c)
+}
+
+/// Latin spelling of a lowercase Ukrainian letter.
+fn latin(c: char, word_start: bool, prev: Option<char>) -> Option<&'static str> {
+    let s = match c {
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        // "зг" is spelled "zgh" to keep it apart from "ж"
+        'г' if matches!(prev, Some('з')) => "gh",
+        'г' => "h",
+        'ґ' => "g",
+        'д' => "d",
+        'е' => "e",
+        'є' if word_start => "ye",
+        'є' => "ie",
+        'ж' => "zh",
+        'з' => "z",
+        'и' => "y",
+        'і' => "i",
+        'ї' if word_start => "yi",
+        'ї' => "i",
+        'й' if word_start => "y",
+        'й' => "i",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ь' => "",
+        'ю' if word_start => "yu",
+        'ю' => "iu",
+        'я' if word_start => "ya",
+        'я' => "ia",
+        _ => return None,
+    };
+    Some(s)
+}
+
+pub fn transliterate(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+
+    for (i, &c) in chars.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|j| chars[j]);
+        let next = chars.get(i + 1).copied();
+
+        if is_apostrophe(c) && prev.is_some_and(char::is_alphabetic) {
+            continue;
+        }
+
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        let word_start = !prev.is_some_and(is_word_char);
+        let Some(latin) = latin(
+            lower,
+            word_start,
+            prev.map(|p| p.to_lowercase().next().unwrap_or(p)),
+        ) else {
+            out.push(c);
+            continue;
+        };
+
+        if !c.is_uppercase() {
+            out.push_str(latin);
+        } else if next.is_some_and(char::is_uppercase) || prev.is_some_and(char::is_uppercase) {
+            // Part of an all-caps word: ЩУР -> SHCHUR
+            out.push_str(&latin.to_uppercase());
+        } else {
+            let mut letters = latin.chars();
+            if let Some(first) = letters.next() {
+                out.extend(first.to_uppercase());
+                out.push_str(letters.as_str());
+            }
+        }
+    }
+
+    out
+}
+
+/// Keeps a Latin spelling that was entered by hand, otherwise derives it
+/// from the Cyrillic one.
+pub fn latin_or(lat: Option<String>, name: Option<&str>) -> Option<String> {
+    match lat {
+        Some(lat) if !lat.trim().is_empty() => Some(lat),
+        _ => name
+            .filter(|name| !name.trim().is_empty())
+            .map(transliterate),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+
+    /// Examples from the table in the resolution.
+    const OFFICIAL_EXAMPLES: [(&str, &str); 76] = [
+        ("Алушта", "Alushta"),
+        ("Андрій", "Andrii"),
+        ("Борщагівка", "Borshchahivka"),
+        ("Борисенко", "Borysenko"),
+        ("Вінниця", "Vinnytsia"),
+        ("Володимир", "Volodymyr"),
+        ("Гадяч", "Hadiach"),
+        ("Богдан", "Bohdan"),
+        ("Згурський", "Zghurskyi"),
+        ("Ґалаґан", "Galagan"),
+        ("Ґорґани", "Gorgany"),
+        ("Донецьк", "Donetsk"),
+        ("Дмитро", "Dmytro"),
+        ("Рівне", "Rivne"),
+        ("Олег", "Oleh"),
+        ("Есмань", "Esman"),
+        ("Єнакієве", "Yenakiieve"),
+        ("Гаєвич", "Haievych"),
+        ("Короп'є", "Koropie"),
+        ("Житомир", "Zhytomyr"),
+        ("Жанна", "Zhanna"),
+        ("Жежелів", "Zhezheliv"),
+        ("Закарпаття", "Zakarpattia"),
+        ("Казимирчук", "Kazymyrchuk"),
+        ("Медвин", "Medvyn"),
+        ("Михайленко", "Mykhailenko"),
+        ("Іванків", "Ivankiv"),
+        ("Іващенко", "Ivashchenko"),
+        ("Їжакевич", "Yizhakevych"),
+        ("Кадиївка", "Kadyivka"),
+        ("Мар'їне", "Marine"),
+        ("Йосипівка", "Yosypivka"),
+        ("Стрий", "Stryi"),
+        ("Олексій", "Oleksii"),
+        ("Київ", "Kyiv"),
+        ("Коваленко", "Kovalenko"),
+        ("Лебедин", "Lebedyn"),
+        ("Леонід", "Leonid"),
+        ("Миколаїв", "Mykolaiv"),
+        ("Маринич", "Marynych"),
+        ("Ніжин", "Nizhyn"),
+        ("Наталія", "Nataliia"),
+        ("Одеса", "Odesa"),
+        ("Онищенко", "Onyshchenko"),
+        ("Полтава", "Poltava"),
+        ("Петро", "Petro"),
+        ("Решетилівка", "Reshetylivka"),
+        ("Рибчинський", "Rybchynskyi"),
+        ("Суми", "Sumy"),
+        ("Соломія", "Solomiia"),
+        ("Тернопіль", "Ternopil"),
+        ("Троць", "Trots"),
+        ("Ужгород", "Uzhhorod"),
+        ("Уляна", "Uliana"),
+        ("Фастів", "Fastiv"),
+        ("Філіпчук", "Filipchuk"),
+        ("Харків", "Kharkiv"),
+        ("Христина", "Khrystyna"),
+        ("Біла Церква", "Bila Tserkva"),
+        ("Стеценко", "Stetsenko"),
+        ("Чернівці", "Chernivtsi"),
+        ("Шевченко", "Shevchenko"),
+        ("Шостка", "Shostka"),
+        ("Кишеньки", "Kyshenky"),
+        ("Щербухи", "Shcherbukhy"),
+        ("Гоща", "Hoshcha"),
+        ("Гаращенко", "Harashchenko"),
+        ("Юрій", "Yurii"),
+        ("Корюківка", "Koriukivka"),
+        ("Яготин", "Yahotyn"),
+        ("Ярошенко", "Yaroshenko"),
+        ("Костянтин", "Kostiantyn"),
+        ("Знам'янка", "Znamianka"),
+        ("Феодосія", "Feodosiia"),
+        ("Згорани", "Zghorany"),
+        ("Розгон", "Rozghon"),
+    ];
+
+    #[test]
+    fn official_examples() {
+        for (cyrillic, latin) in OFFICIAL_EXAMPLES {
+            assert_eq!(transliterate(cyrillic), latin, "{}", cyrillic);
+        }
+    }
+
+    /// `translit_uk` has to agree, it finds people by the Latin spelling of
+    /// names stored without one.
+    #[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+    async fn sql_matches(pool: PgPool) {
+        for (cyrillic, _) in OFFICIAL_EXAMPLES {
+            let sql: String = sqlx::query_scalar("select translit_uk($1)")
+                .bind(cyrillic)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(sql, transliterate(cyrillic).to_lowercase(), "{}", cyrillic);
+        }
+    }
+
+    #[test]
+    fn position_dependent_letters_after_hyphen_and_space() {
+        assert_eq!(transliterate("Ігор-Ярослав"), "Ihor-Yaroslav");
+        assert_eq!(transliterate("Олег Юрійович"), "Oleh Yuriiovych");
+    }
+
+    #[test]
+    fn typographic_apostrophes() {
+        assert_eq!(transliterate("Знам’янка"), "Znamianka");
+        assert_eq!(transliterate("Знамʼянка"), "Znamianka");
+    }
+
+    #[test]
+    fn all_caps() {
+        assert_eq!(transliterate("ЩЕРБУХИ"), "SHCHERBUKHY");
+        assert_eq!(transliterate("ЮРІЙ"), "YURII");
+    }
+
+    #[test]
+    fn keeps_latin_and_digits() {
+        assert_eq!(transliterate("Smith 2"), "Smith 2");
+    }
+
+    #[test]
+    fn latin_or_keeps_manual_spelling() {
+        assert_eq!(
+            latin_or(Some("Alexey".into()), Some("Олексій")),
+            Some("Alexey".into())
+        );
+        assert_eq!(
+            latin_or(Some(" ".into()), Some("Олексій")),
+            Some("Oleksii".into())
+        );
+        assert_eq!(latin_or(None, None), None);
+        assert_eq!(latin_or(None, Some("")), None);
+    }
+}
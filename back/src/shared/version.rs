@@ -0,0 +1,89 @@
+//! Optimistic concurrency for people, cars and policies. Their rows carry a
+//! `version` that every update bumps. Reads return it in the body and as an
+//! `ETag`; an update sends it back in `If-Match` or a `version` field and is
+//! refused with 409 and the current state when the row has changed since.
+//! Updates that send neither overwrite unconditionally.
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{HeaderName, StatusCode, header};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error::AppError;
+use crate::shared::existing::ExistingConflict;
+
+/// Version from the `If-Match` header, if any.
+pub struct IfMatch(Option<i32>);
+
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(header::IF_MATCH) else {
+            return Ok(Self(None));
+        };
+        let value = value.to_str().unwrap_or_default().trim();
+        if value == "*" {
+            return Ok(Self(None));
+        }
+
+        value
+            .strip_prefix("W/")
+            .unwrap_or(value)
+            .trim_matches('"')
+            .parse()
+            .map(|version| Self(Some(version)))
+            .map_err(|_| {
+                AppError::status(
+                    StatusCode::BAD_REQUEST,
+                    "If-Match must be an ETag returned by this API",
+                )
+            })
+    }
+}
+
+impl IfMatch {
+    /// The expected version; the header wins over one sent in the body.
+    pub fn or(self, body: Option<i32>) -> Option<i32> {
+        self.0.or(body)
+    }
+}
+
+pub fn etag(version: i32) -> [(HeaderName, String); 1] {
+    [(header::ETAG, format!("\"{}\"", version))]
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VersionConflict<T> {
+    pub message: String,
+    /// What the update would have overwritten
+    pub current: T,
+}
+
+/// 409 of an update that may also create people or cars, described for the
+/// OpenAPI document only.
+#[derive(Serialize, ToSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+pub enum UpdateConflict<T> {
+    Existing(ExistingConflict),
+    Version(VersionConflict<T>),
+}
+
+pub fn is_stale(expected: Option<i32>, version: i32) -> bool {
+    expected.is_some_and(|expected| expected != version)
+}
+
+pub fn conflict(entity: &str, current: impl Serialize) -> AppError {
+    AppError::json(
+        StatusCode::CONFLICT,
+        VersionConflict {
+            message: format!(
+                "The {} was changed by someone else, reload it and try again",
+                entity
+            ),
+            current,
+        },
+    )
+}
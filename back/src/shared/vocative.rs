@@ -0,0 +1,149 @@
+//! Ukrainian vocative case for addressing people by name, e.g. "Іване Петровичу",
+//! "Маріє Іванівно".
+
+/// Names whose vocative does not follow the ending rules below.
+const EXCEPTIONS: &[(&str, &str)] = &[
+    ("ілля", "ілле"),
+    ("ігор", "ігорю"),
+    ("федір", "федоре"),
+    ("антін", "антоне"),
+    ("сидір", "сидоре"),
+    ("лев", "леве"),
+];
+
+const VOWELS: &str = "аеєиіїоуюя";
+
+/// Replaces the last `cut` characters of `word` with `suffix`, keeping the
+/// capitalisation of names written in capitals.
+fn replace_end(word: &str, cut: usize, suffix: &str) -> String {
+    let keep = word.chars().count() - cut;
+    let mut result: String = word.chars().take(keep).collect();
+
+    let upper = word.chars().any(char::is_alphabetic)
+        && word.chars().filter(|c| c.is_alphabetic()).all(char::is_uppercase);
+    if upper {
+        result.push_str(&suffix.to_uppercase());
+    } else {
+        result.push_str(suffix);
+    }
+    result
+}
+
+pub fn first_name_vocative(name: &str) -> String {
+    let name = name.trim();
+    let lower = name.to_lowercase();
+    let chars: Vec<char> = lower.chars().collect();
+
+    if let Some((_, vocative)) = EXCEPTIONS.iter().find(|(nominative, _)| *nominative == lower) {
+        let common = chars
+            .iter()
+            .zip(vocative.chars())
+            .take_while(|(a, b)| *a == b)
+            .count();
+        let suffix: String = vocative.chars().skip(common).collect();
+        return replace_end(name, chars.len() - common, &suffix);
+    }
+
+    let (Some(&last), prev) = (chars.last(), chars.len().checked_sub(2).map(|i| chars[i])) else {
+        return name.to_owned();
+    };
+    let after_vowel = prev.is_some_and(|c| VOWELS.contains(c));
+
+    match last {
+        // Олена → Олено, Микола → Миколо
+        'а' => replace_end(name, 1, "о"),
+        // Марія → Маріє, Оля → Олю
+        'я' if after_vowel => replace_end(name, 1, "є"),
+        'я' => replace_end(name, 1, "ю"),
+        // Марко → Марку, Петро → Петре
+        'о' if prev == Some('к') => replace_end(name, 1, "у"),
+        'о' => replace_end(name, 1, "е"),
+        // Андрій → Андрію, Василь → Василю
+        'й' | 'ь' => replace_end(name, 1, "ю"),
+        // Марк → Марку, Олег → Олегу
+        'к' | 'г' | 'х' => replace_end(name, 0, "у"),
+        // Іван → Іване, Любов → Любове
+        c if c.is_alphabetic() && !VOWELS.contains(c) => replace_end(name, 0, "е"),
+        _ => name.to_owned(),
+    }
+}
+
+pub fn patronymic_vocative(patronymic: &str) -> String {
+    let patronymic = patronymic.trim();
+    let lower = patronymic.to_lowercase();
+
+    if lower.ends_with("ич") || lower.ends_with("іч") {
+        // Петрович → Петровичу
+        replace_end(patronymic, 0, "у")
+    } else if lower.ends_with("на") {
+        // Петрівна → Петрівно
+        replace_end(patronymic, 1, "о")
+    } else {
+        patronymic.to_owned()
+    }
+}
+
+/// How to address a person politely: first name and patronymic, both in the vocative.
+pub fn address(first_name: &str, patronymic: Option<&str>) -> String {
+    let first_name = first_name_vocative(first_name);
+    match patronymic.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(patronymic) => format!("{} {}", first_name, patronymic_vocative(patronymic)),
+        None => first_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_names() {
+        for (nominative, vocative) in [
+            ("Іван", "Іване"),
+            ("Олександр", "Олександре"),
+            ("Петро", "Петре"),
+            ("Дмитро", "Дмитре"),
+            ("Марко", "Марку"),
+            ("Андрій", "Андрію"),
+            ("Юрій", "Юрію"),
+            ("Василь", "Василю"),
+            ("Олег", "Олегу"),
+            ("Микола", "Миколо"),
+            ("Ілля", "Ілле"),
+            ("Ігор", "Ігорю"),
+            ("Федір", "Федоре"),
+            ("Олена", "Олено"),
+            ("Ольга", "Ольго"),
+            ("Марія", "Маріє"),
+            ("Наталія", "Наталіє"),
+            ("Надія", "Надіє"),
+            ("Оля", "Олю"),
+            ("Любов", "Любове"),
+        ] {
+            assert_eq!(first_name_vocative(nominative), vocative);
+        }
+    }
+
+    #[test]
+    fn patronymics() {
+        assert_eq!(patronymic_vocative("Петрович"), "Петровичу");
+        assert_eq!(patronymic_vocative("Андрійович"), "Андрійовичу");
+        assert_eq!(patronymic_vocative("Ілліч"), "Іллічу");
+        assert_eq!(patronymic_vocative("Петрівна"), "Петрівно");
+        assert_eq!(patronymic_vocative("Сергіївна"), "Сергіївно");
+        assert_eq!(patronymic_vocative("Іллівна"), "Іллівно");
+    }
+
+    #[test]
+    fn full_address() {
+        assert_eq!(address("Іван", Some("Петрович")), "Іване Петровичу");
+        assert_eq!(address("Марія", Some(" Іванівна ")), "Маріє Іванівно");
+        assert_eq!(address("Олена", None), "Олено");
+        assert_eq!(address("Олена", Some("")), "Олено");
+    }
+
+    #[test]
+    fn keeps_capitals() {
+        assert_eq!(address("ІВАН", Some("ПЕТРОВИЧ")), "ІВАНЕ ПЕТРОВИЧУ");
+    }
+}
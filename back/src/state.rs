@@ -0,0 +1,15 @@
+use std::sync::Arc;
+
+use axum::extract::FromRef;
+use sqlx::PgPool;
+
+use crate::config::Config;
+use crate::metrics::Metrics;
+
+/// Shared by all handlers; they extract the part they need, e.g. `State<PgPool>`.
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub pool: PgPool,
+    pub config: Arc<Config>,
+    pub metrics: Arc<Metrics>,
+}
@@ -0,0 +1,129 @@
+use axum::body::Bytes;
+use hmac::{Hmac, Mac};
+use reqwest::{Method, Url};
+use sha2::{Digest, Sha256};
+
+use crate::config::S3Config;
+
+/// Minimal S3 client using path-style addressing and AWS Signature V4,
+/// so it works with AWS as well as MinIO and other S3-compatible stores.
+pub struct S3Storage {
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    http_client: reqwest::Client,
+}
+
+impl S3Storage {
+    pub fn new(config: &S3Config) -> Self {
+        Self {
+            endpoint: config.endpoint.clone(),
+            bucket: config.bucket.clone(),
+            region: config.region.clone(),
+            access_key_id: config.access_key_id.clone(),
+            secret_access_key: config.secret_access_key.clone(),
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn put(&self, key: &str, content: Bytes, content_type: &str) -> anyhow::Result<()> {
+        let response = self
+            .request(Method::PUT, key, content)
+            .header("content-type", content_type)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_body = response.text().await.unwrap_or_default();
+            anyhow::bail!("S3 upload of {} failed: {}", key, error_body);
+        }
+        Ok(())
+    }
+
+    pub async fn get(&self, key: &str) -> anyhow::Result<Bytes> {
+        let response = self.request(Method::GET, key, Bytes::new()).send().await?;
+
+        if !response.status().is_success() {
+            let error_body = response.text().await.unwrap_or_default();
+            anyhow::bail!("S3 download of {} failed: {}", key, error_body);
+        }
+        Ok(response.bytes().await?)
+    }
+
+    pub async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let response = self
+            .request(Method::DELETE, key, Bytes::new())
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_body = response.text().await.unwrap_or_default();
+            anyhow::bail!("S3 delete of {} failed: {}", key, error_body);
+        }
+        Ok(())
+    }
+
+    /// Builds a signed request. Keys are generated by us and only contain
+    /// URL-safe characters, so the path needs no extra encoding.
+    fn request(&self, method: Method, key: &str, body: Bytes) -> reqwest::RequestBuilder {
+        let path = format!(
+            "{}/{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            self.bucket,
+            key
+        );
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_owned(),
+        };
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+            method, path, host, payload_hash, amz_date, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let signing_key = [self.region.as_str(), "s3", "aws4_request"].iter().fold(
+            hmac_sha256(
+                format!("AWS4{}", self.secret_access_key).as_bytes(),
+                date.as_bytes(),
+            ),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+            self.access_key_id, scope, signature
+        );
+
+        self.http_client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", authorization)
+            .body(body)
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
@@ -0,0 +1,168 @@
+//! Agencies sharing the instance. Each request acts for one tenant of the
+//! signed-in user: the one named by `X-Tenant-Id`, or else the first they
+//! belong to.
+//!
+//! Separation is enforced by row-level security rather than by filters in
+//! the queries. The tenant is kept in a task-local while the request is
+//! handled, and every connection taken from the pool in that time is switched
+//! to the `insure_tenant` role with `app.tenant_id` set, so it only sees and
+//! writes that tenant's rows. Public routes get no tenant and see nothing,
+//! apart from the health checks and metrics, which read across tenants.
+//! Work outside of a request, such as reminders and webhook deliveries, keeps
+//! the owner's access to every tenant.
+
+use std::future::Future;
+
+use axum::extract::{Request, State};
+use axum::http::{HeaderName, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use serde::Serialize;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgConnection, PgPool};
+use utoipa::ToSchema;
+
+use crate::auth::middleware::{AuthUser, is_public};
+use crate::error::AppError;
+
+pub const TENANT_ID: HeaderName = HeaderName::from_static("x-tenant-id");
+
+/// Role the row-level security policies apply to, see the tenant migration.
+const ROLE: &str = "insure_tenant";
+
+#[derive(Clone, Copy)]
+enum Access {
+    Tenant(i32),
+    /// Public routes
+    NoTenant,
+    /// Lookups made before a tenant is known
+    Unrestricted,
+}
+
+tokio::task_local! {
+    static ACCESS: Access;
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct Tenant {
+    pub id: i32,
+    pub name: String,
+}
+
+/// Tenants of the signed-in user, and the one the request acts for.
+#[derive(Clone, Serialize, ToSchema)]
+pub struct UserTenants {
+    pub active: i32,
+    pub tenants: Vec<Tenant>,
+}
+
+/// Pool whose connections follow the tenant of the request using them.
+pub fn pool_options() -> PgPoolOptions {
+    PgPoolOptions::new()
+        .after_connect(|conn, _| Box::pin(switch(conn)))
+        .before_acquire(|conn, _| Box::pin(async move { switch(conn).await.map(|()| true) }))
+}
+
+async fn switch(conn: &mut PgConnection) -> sqlx::Result<()> {
+    // Outside of a request, e.g. in a background job
+    let access = ACCESS
+        .try_with(|access| *access)
+        .unwrap_or(Access::Unrestricted);
+    let (role, tenant_id) = match access {
+        Access::Tenant(id) => (ROLE, id.to_string()),
+        Access::NoTenant => (ROLE, String::new()),
+        Access::Unrestricted => ("none", String::new()),
+    };
+    // The same as `set role`, which takes no parameters
+    sqlx::query("select set_config('role', $1, false), set_config('app.tenant_id', $2, false)")
+        .bind(role)
+        .bind(tenant_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Runs `future` with the database access of the given tenant.
+pub async fn scope<F: Future>(tenant_id: i32, future: F) -> F::Output {
+    ACCESS.scope(Access::Tenant(tenant_id), future).await
+}
+
+/// Runs `future` with access to every tenant, for the lookups that decide
+/// which tenant a public route acts for.
+pub async fn unrestricted<F: Future>(future: F) -> F::Output {
+    ACCESS.scope(Access::Unrestricted, future).await
+}
+
+/// Carries the current access into a future run elsewhere, e.g. by
+/// `tokio::spawn`, which does not inherit it.
+pub fn propagate<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let access = ACCESS.try_with(|access| *access).ok();
+    async move {
+        match access {
+            Some(access) => ACCESS.scope(access, future).await,
+            None => future.await,
+        }
+    }
+}
+
+pub async fn user_tenants(pool: &PgPool, email: &str) -> sqlx::Result<Vec<Tenant>> {
+    unrestricted(
+        sqlx::query_as!(
+            Tenant,
+            r#"
+            select t.id, t.name
+            from tenant t
+            join tenant_user tu on tu.tenant_id = t.id
+            where tu.user_email = $1
+            order by t.id
+            "#,
+            email
+        )
+        .fetch_all(pool),
+    )
+    .await
+}
+
+/// Resolves the tenant of the request. Users who belong to none are refused.
+pub async fn tenant_middleware(
+    State(pool): State<PgPool>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let user = request.extensions().get::<AuthUser>().cloned();
+    let user = match user {
+        Some(user) if !is_public(request.uri().path()) => user,
+        _ => return Ok(ACCESS.scope(Access::NoTenant, next.run(request)).await),
+    };
+
+    let tenants = user_tenants(&pool, &user.email).await?;
+    let requested = match request.headers().get(TENANT_ID) {
+        Some(value) => Some(
+            value
+                .to_str()
+                .ok()
+                .and_then(|value| value.trim().parse::<i32>().ok())
+                .ok_or_else(|| {
+                    AppError::status(StatusCode::BAD_REQUEST, "X-Tenant-Id must be a tenant id")
+                })?,
+        ),
+        None => None,
+    };
+
+    let active = match requested {
+        Some(id) => tenants.iter().find(|tenant| tenant.id == id),
+        None => tenants.first(),
+    };
+    let Some(active) = active else {
+        return Err(match requested {
+            Some(_) => AppError::status(StatusCode::FORBIDDEN, "Not a member of this tenant"),
+            None => AppError::Status(StatusCode::UNAUTHORIZED, None),
+        });
+    };
+
+    let active = active.id;
+    request
+        .extensions_mut()
+        .insert(UserTenants { active, tenants });
+    Ok(scope(active, next.run(request)).await)
+}
@@ -0,0 +1,217 @@
+//! Request bodies with valid defaults for people, cars and policies. Tax
+//! numbers, phones, chassis and plates are unique within a test run, so
+//! fixtures never collide with each other; `with` overrides any field.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+
+use super::{DEFAULT_TENANT, TestApp};
+
+static NEXT: AtomicU32 = AtomicU32::new(1);
+
+fn next() -> u32 {
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+fn set(body: &mut Value, field: &str, value: Value) {
+    body.as_object_mut()
+        .expect("fixture body is an object")
+        .insert(field.to_owned(), value);
+}
+
+/// `PersonRef` or `CarRef` to a stored record.
+pub fn existing(id: &Value) -> Value {
+    json!({ "kind": "Existing", "id": id })
+}
+
+pub async fn insurer(app: &TestApp, name: &str) -> Value {
+    app.post("/insurers", json!({ "name": name }))
+        .await
+        .expect(StatusCode::CREATED)["id"]
+        .clone()
+}
+
+/// Agents are not created through the API.
+pub async fn agent(app: &TestApp, full_name: &str) -> Value {
+    let id = sqlx::query_scalar!(
+        "insert into agent (full_name, tenant_id) values ($1, $2) returning id",
+        full_name,
+        DEFAULT_TENANT
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    json!(id)
+}
+
+pub struct PersonBuilder {
+    body: Value,
+}
+
+impl PersonBuilder {
+    pub fn new() -> Self {
+        let n = next();
+        Self {
+            body: json!({
+                "first_name": "Тарас",
+                "last_name": format!("Коваленко{}", n),
+                "patronymic_name": "Петрович",
+                "sex": "M",
+                "birth_date": "1985-03-14",
+                "tax_number": format!("30{:08}", n),
+                "phone": format!("+38050{:07}", n),
+                "email": format!("person{}@insure.test", n),
+                "status": "Active",
+            }),
+        }
+    }
+
+    pub fn with(mut self, field: &str, value: impl Into<Value>) -> Self {
+        set(&mut self.body, field, value.into());
+        self
+    }
+
+    /// Body of `POST /people`.
+    pub fn body(&self) -> Value {
+        self.body.clone()
+    }
+
+    /// `PersonRef` creating this person along with a policy.
+    pub fn as_new(&self) -> Value {
+        let mut body = self.body();
+        set(&mut body, "kind", json!("New"));
+        body
+    }
+
+    /// Stores the person, returning it as `PersonFull`.
+    pub async fn create(&self, app: &TestApp) -> Value {
+        app.post("/people", self.body())
+            .await
+            .expect(StatusCode::CREATED)
+    }
+}
+
+pub struct CarBuilder {
+    body: Value,
+}
+
+impl CarBuilder {
+    pub fn new() -> Self {
+        let n = next();
+        Self {
+            body: json!({
+                "chassis": format!("WVWZZZ1JZ{:08}", n),
+                "make": "Volkswagen",
+                "model": "Golf",
+                "registration": format!("CXI{:06}", n),
+                "plate": format!("AA{:04}BB", n % 10_000),
+                "year": 2015,
+                "engine_displacement_litres": 2,
+                "mileage_km": 120_000,
+                "unladen_weight": 1300,
+                "laden_weight": 1800,
+                "seats": 5,
+            }),
+        }
+    }
+
+    pub fn with(mut self, field: &str, value: impl Into<Value>) -> Self {
+        set(&mut self.body, field, value.into());
+        self
+    }
+
+    /// `CarRef` creating this car along with a policy.
+    pub fn as_new(&self) -> Value {
+        let mut body = self.body.clone();
+        set(&mut body, "kind", json!("New"));
+        body
+    }
+}
+
+/// A policy of a new holder, and for car policies a new car, with the
+/// insurer it is issued by.
+pub struct PolicyBuilder {
+    body: Value,
+}
+
+impl PolicyBuilder {
+    fn new(insurer_id: &Value, data: Value) -> Self {
+        let mut body = json!({
+            "holder": PersonBuilder::new().as_new(),
+            "insurer_id": insurer_id,
+            "series": "AM",
+            "number": format!("{:07}", next()),
+            "start_date": "2026-01-01",
+            "end_date": "2026-12-31",
+            "status": "Active",
+            "agent_ids": [],
+        });
+        for (field, value) in data.as_object().expect("policy data is an object") {
+            set(&mut body, field, value.clone());
+        }
+        Self { body }
+    }
+
+    pub fn green_card(insurer_id: &Value) -> Self {
+        Self::new(
+            insurer_id,
+            json!({
+                "policy_type": "GreenCard",
+                "territory": "Європа",
+                "period_in_units": 15,
+                "period_unit": "Day",
+                "premium": 1500,
+                "car": CarBuilder::new().as_new(),
+            }),
+        )
+    }
+
+    pub fn medassistance(insurer_id: &Value) -> Self {
+        Self::new(
+            insurer_id,
+            json!({
+                "policy_type": "Medassistance",
+                "territory": "Шенген",
+                "period_days": 30,
+                "premium": 900,
+                "payout": 30_000,
+                "program": "Стандарт",
+                "members": [PersonBuilder::new().as_new()],
+            }),
+        )
+    }
+
+    pub fn osago(insurer_id: &Value) -> Self {
+        Self::new(
+            insurer_id,
+            json!({
+                "policy_type": "Osago",
+                "period_in_units": 12,
+                "period_unit": "Month",
+                "zone": "Zone1",
+                "exempt": "",
+                "premium": 2400,
+                "car": CarBuilder::new().as_new(),
+            }),
+        )
+    }
+
+    pub fn with(mut self, field: &str, value: impl Into<Value>) -> Self {
+        set(&mut self.body, field, value.into());
+        self
+    }
+
+    /// Body of `POST /policies`.
+    pub fn body(&self) -> Value {
+        self.body.clone()
+    }
+
+    /// Stores the policy, returning the `POST /policies` response.
+    pub async fn create(&self, app: &TestApp) -> Value {
+        app.post("/policies", self.body())
+            .await
+            .expect(StatusCode::CREATED)
+    }
+}
@@ -0,0 +1,220 @@
+//! Harness for end-to-end tests of the HTTP API.
+//!
+//! Tests take their database from `#[sqlx::test]`, which creates a throwaway
+//! one on the server named by `DATABASE_URL`, applies the migrations and drops
+//! it afterwards:
+//!
+//! ```ignore
+//! #[sqlx::test(migrator = "crate::migrate::MIGRATOR")]
+//! async fn lists_people(pool: PgPool) {
+//!     let app = TestApp::new(pool).await;
+//!     app.get("/people").await.expect(StatusCode::OK);
+//! }
+//! ```
+//!
+//! Requests skip the Google sign-in and run as `TEST_USER`, a member of the
+//! `Default` tenant that every migrated database starts with.
+
+pub mod fixtures;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::http::{HeaderMap, HeaderName, Method, Request, StatusCode, header};
+use axum::{Extension, Router, middleware};
+use serde_json::Value;
+use sqlx::PgPool;
+use tower::ServiceExt;
+
+use crate::auth::middleware::AuthUser;
+use crate::config::{Config, GoogleConfig, LogFormat, StorageConfig};
+use crate::endpoints;
+use crate::idempotency;
+use crate::metrics::Metrics;
+use crate::notify::{Notifier, ReminderSettings};
+use crate::request_id;
+use crate::state::AppState;
+use crate::storage::{AttachmentLimits, FileStorage};
+use crate::tenant;
+
+pub const TEST_USER: &str = "test@insure.test";
+/// Created by the tenant migration
+pub const DEFAULT_TENANT: i32 = 1;
+
+pub struct TestApp {
+    router: Router,
+    /// Connections outside of a request see every tenant
+    pub pool: PgPool,
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// `Null` for an empty or non-JSON body
+    pub body: Value,
+    /// The body as it came, for responses that are not JSON
+    pub text: String,
+}
+
+fn config() -> Config {
+    Config {
+        database_url: String::new(),
+        bind_address: ([127, 0, 0, 1], 0).into(),
+        tls: None,
+        cors_origins: Vec::new(),
+        secure_cookies: false,
+        migrate_on_startup: false,
+        idempotency_key_ttl: Duration::from_secs(3600),
+        log_format: LogFormat::Text,
+        google: GoogleConfig {
+            client_id: String::new(),
+            client_secret: String::new(),
+        },
+        storage: StorageConfig::Local(std::env::temp_dir().join("insure-test-attachments")),
+        attachment_limits: AttachmentLimits::default(),
+        smtp: None,
+        sms: None,
+        reminders: ReminderSettings::default(),
+        notify_template_dir: None,
+    }
+}
+
+impl TestApp {
+    pub async fn new(pool: PgPool) -> Self {
+        // The same database, with connections that follow the request's tenant
+        let pool = tenant::pool_options()
+            .connect_with(pool.connect_options().as_ref().clone())
+            .await
+            .unwrap();
+        sqlx::query!(
+            "insert into tenant_user (tenant_id, user_email) values ($1, $2)",
+            DEFAULT_TENANT,
+            TEST_USER
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let config = Arc::new(config());
+        let storage = FileStorage::new(&config.storage);
+        let attachment_limits = config.attachment_limits.clone();
+
+        let state = AppState {
+            pool: pool.clone(),
+            config,
+            metrics: Arc::new(Metrics::default()),
+        };
+
+        let router = Router::from(endpoints::router())
+            .layer(Extension(Arc::new(storage)))
+            .layer(Extension(Arc::new(attachment_limits)))
+            .layer(Extension(None::<Arc<Notifier>>))
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                idempotency::idempotency_middleware,
+            ))
+            .layer(middleware::from_fn_with_state(
+                state.clone(),
+                tenant::tenant_middleware,
+            ))
+            // Stands in for `auth_middleware`
+            .layer(Extension(AuthUser {
+                email: TEST_USER.to_owned(),
+            }))
+            .layer(middleware::from_fn(request_id::request_id_middleware))
+            .with_state(state);
+
+        Self { router, pool }
+    }
+
+    pub async fn get(&self, uri: &str) -> TestResponse {
+        self.send(Method::GET, uri, &[], None).await
+    }
+
+    pub async fn post(&self, uri: &str, body: Value) -> TestResponse {
+        self.send(Method::POST, uri, &[], Some(body)).await
+    }
+
+    pub async fn put(&self, uri: &str, body: Value) -> TestResponse {
+        self.send(Method::PUT, uri, &[], Some(body)).await
+    }
+
+    pub async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        headers: &[(HeaderName, &str)],
+        body: Option<Value>,
+    ) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+        self.call(request).await
+    }
+
+    /// Posts `content` as the `file` field of a multipart form, the way the
+    /// front end uploads files.
+    pub async fn upload(
+        &self,
+        uri: &str,
+        file_name: &str,
+        content_type: &str,
+        content: &[u8],
+    ) -> TestResponse {
+        const BOUNDARY: &str = "insure-test-boundary";
+        let mut body = format!(
+            "--{}\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n\
+             Content-Type: {}\r\n\r\n",
+            BOUNDARY, file_name, content_type
+        )
+        .into_bytes();
+        body.extend_from_slice(content);
+        body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            )
+            .body(Body::from(body))
+            .unwrap();
+        self.call(request).await
+    }
+
+    async fn call(&self, request: Request<Body>) -> TestResponse {
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        TestResponse {
+            status,
+            headers,
+            body: serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+            text: String::from_utf8_lossy(&bytes).into_owned(),
+        }
+    }
+}
+
+impl TestResponse {
+    /// Returns the body, failing the test with it when the status differs.
+    #[track_caller]
+    pub fn expect(self, status: StatusCode) -> Value {
+        assert_eq!(self.status, status, "unexpected response: {}", self.body);
+        self.body
+    }
+}
@@ -0,0 +1,252 @@
+use std::time::{Duration, Instant};
+
+use futures_util::future::join_all;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::PgPool;
+use tracing::{error, warn};
+
+const POLL_EVERY: Duration = Duration::from_secs(5);
+const BATCH_SIZE: i64 = 20;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a claimed delivery stays hidden from other workers; longer than a request can take.
+const LEASE_SECS: f64 = 60.0;
+const FIRST_RETRY: Duration = Duration::from_secs(30);
+const MAX_RETRY: Duration = Duration::from_secs(6 * 60 * 60);
+/// After this many failed attempts a delivery is marked failed and only a replay sends it again.
+const MAX_ATTEMPTS: i32 = 12;
+
+const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+const EVENT_HEADER: &str = "X-Webhook-Event";
+const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+/// `sha256=` and the hex HMAC-SHA256 of `{timestamp}.{body}` keyed with the
+/// subscription secret. Receivers recompute it and reject stale timestamps.
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Delay before the next attempt after `attempts` failed ones, doubling from
+/// 30 seconds up to 6 hours. `None` once the delivery should be given up.
+pub fn retry_delay(attempts: i32) -> Option<Duration> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+    let doublings = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    Some(FIRST_RETRY.saturating_mul(1 << doublings).min(MAX_RETRY))
+}
+
+struct Due {
+    id: i32,
+    attempts: i32,
+    url: String,
+    secret: String,
+    event_type: String,
+    body: String,
+}
+
+/// Takes due deliveries of active subscriptions and leases them to this worker.
+async fn claim(pool: &PgPool) -> sqlx::Result<Vec<Due>> {
+    sqlx::query_as!(
+        Due,
+        r#"
+        update webhook_delivery d
+        set next_attempt_at = now() + make_interval(secs => $2)
+        from webhook_event e, webhook_subscription s
+        where
+            d.id in (
+                select wd.id
+                from webhook_delivery wd
+                join webhook_subscription ws on ws.id = wd.subscription_id
+                where wd.status = 'pending' and wd.next_attempt_at <= now() and ws.active
+                order by wd.next_attempt_at
+                limit $1
+                for update of wd skip locked
+            )
+            and e.id = d.event_id
+            and s.id = d.subscription_id
+        returning
+            d.id,
+            d.attempts,
+            s.url,
+            s.secret,
+            e.event_type::text as "event_type!",
+            jsonb_build_object(
+                'id', e.id,
+                'type', e.event_type,
+                'created_at', e.created_at,
+                'data', e.payload
+            )::text as "body!"
+        "#,
+        BATCH_SIZE,
+        LEASE_SECS
+    )
+    .fetch_all(pool)
+    .await
+}
+
+struct Outcome {
+    response_status: Option<i32>,
+    error: Option<String>,
+}
+
+async fn post(client: &reqwest::Client, due: &Due) -> Outcome {
+    let timestamp = chrono::Utc::now().timestamp();
+
+    let result = client
+        .post(&due.url)
+        .header("Content-Type", "application/json")
+        .header(EVENT_HEADER, &due.event_type)
+        .header(DELIVERY_HEADER, due.id.to_string())
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(
+            SIGNATURE_HEADER,
+            signature(&due.secret, timestamp, &due.body),
+        )
+        .body(due.body.clone())
+        .send()
+        .await;
+
+    match result {
+        Ok(response) if response.status().is_success() => Outcome {
+            response_status: Some(response.status().as_u16().into()),
+            error: None,
+        },
+        Ok(response) => Outcome {
+            response_status: Some(response.status().as_u16().into()),
+            error: Some(format!("Receiver responded with {}", response.status())),
+        },
+        Err(err) => Outcome {
+            response_status: None,
+            error: Some(format!("{:#}", anyhow::Error::from(err))),
+        },
+    }
+}
+
+/// Sends one delivery and records the attempt, scheduling a retry on failure.
+async fn deliver(pool: &PgPool, client: &reqwest::Client, due: Due) -> sqlx::Result<()> {
+    let started = Instant::now();
+    let outcome = post(client, &due).await;
+    let duration_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        r#"
+        insert into webhook_attempt (delivery_id, response_status, error, duration_ms)
+        values ($1, $2, $3, $4)
+        "#,
+        due.id,
+        outcome.response_status,
+        outcome.error,
+        duration_ms
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let attempts = due.attempts + 1;
+    match (&outcome.error, retry_delay(attempts)) {
+        (None, _) => {
+            sqlx::query!(
+                r#"
+                update webhook_delivery
+                set status = 'delivered', attempts = $2, delivered_at = now()
+                where id = $1
+                "#,
+                due.id,
+                attempts
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        (Some(error), Some(delay)) => {
+            warn!("Webhook delivery {} failed, retrying: {}", due.id, error);
+            sqlx::query!(
+                r#"
+                update webhook_delivery
+                set attempts = $2, next_attempt_at = now() + make_interval(secs => $3)
+                where id = $1
+                "#,
+                due.id,
+                attempts,
+                delay.as_secs_f64()
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        (Some(error), None) => {
+            warn!("Webhook delivery {} failed, giving up: {}", due.id, error);
+            sqlx::query!(
+                "update webhook_delivery set status = 'failed', attempts = $2 where id = $1",
+                due.id,
+                attempts
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    tx.commit().await
+}
+
+/// Sends everything due right now. Returns how many deliveries were attempted.
+pub async fn run(pool: &PgPool, client: &reqwest::Client) -> sqlx::Result<usize> {
+    let mut attempted = 0;
+    loop {
+        let due = claim(pool).await?;
+        if due.is_empty() {
+            return Ok(attempted);
+        }
+        attempted += due.len();
+
+        for result in join_all(due.into_iter().map(|due| deliver(pool, client, due))).await {
+            result?;
+        }
+    }
+}
+
+/// Delivers queued webhook events every few seconds.
+pub fn spawn(pool: PgPool) {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("Failed to build the webhook HTTP client");
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_EVERY);
+        loop {
+            interval.tick().await;
+            if let Err(err) = run(&pool, &client).await {
+                error!("Webhook delivery failed: {:?}", err);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_timestamp_and_body() {
+        // echo -n '1700000000.{"id":1}' | openssl dgst -sha256 -hmac secret
+        assert_eq!(
+            signature("secret", 1_700_000_000, r#"{"id":1}"#),
+            "sha256=3dd1b9aef568d75f6790a84bd2e5dfa1f44409eef3cbdbd3f10b837376100c11"
+        );
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        assert_eq!(retry_delay(1), Some(Duration::from_secs(30)));
+        assert_eq!(retry_delay(2), Some(Duration::from_secs(60)));
+        assert_eq!(retry_delay(5), Some(Duration::from_secs(480)));
+        assert_eq!(retry_delay(11), Some(MAX_RETRY));
+        assert_eq!(retry_delay(MAX_ATTEMPTS), None);
+    }
+}
@@ -0,0 +1,68 @@
+pub mod delivery;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use utoipa::ToSchema;
+
+pub use delivery::spawn;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "webhook_event_type")]
+pub enum EventType {
+    #[sqlx(rename = "policy.created")]
+    #[serde(rename = "policy.created")]
+    PolicyCreated,
+    #[sqlx(rename = "policy.updated")]
+    #[serde(rename = "policy.updated")]
+    PolicyUpdated,
+    /// Also covers renewals (`prolonged`) and cancellations
+    #[sqlx(rename = "policy.status_changed")]
+    #[serde(rename = "policy.status_changed")]
+    PolicyStatusChanged,
+    #[sqlx(rename = "person.created")]
+    #[serde(rename = "person.created")]
+    PersonCreated,
+}
+
+/// Writes an event to the outbox and queues a delivery for every active
+/// subscription to it. Runs in the caller's transaction, so the event exists
+/// exactly when the change it describes was committed.
+pub async fn publish(
+    tx: &mut Transaction<'_, Postgres>,
+    event_type: EventType,
+    payload: &impl Serialize,
+) -> anyhow::Result<()> {
+    let payload = serde_json::to_string(payload)?;
+
+    sqlx::query!(
+        r#"
+        with event as (
+            insert into webhook_event (event_type, payload)
+            values ($1, $2::text::jsonb)
+            returning id
+        )
+        insert into webhook_delivery (event_id, subscription_id)
+        select event.id, s.id
+        from event, webhook_subscription s
+        where s.active and $1 = any(s.events)
+        "#,
+        event_type as EventType,
+        payload
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "webhook_delivery_status")]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Not sent yet, or waiting for a retry
+    Pending,
+    Delivered,
+    /// Gave up after the last retry
+    Failed,
+}
@@ -7,7 +7,9 @@ import localeRu from '@angular/common/locales/ru';
 import { routes } from './app.routes';
 import { providePrimeNG } from 'primeng/config';
 import Aura from '@primeuix/themes/aura';
+import { MessageService } from 'primeng/api';
 import { authInterceptor } from './auth/auth.interceptor';
+import { errorInterceptor } from './error.interceptor';
 
 registerLocaleData(localeRu);
 
@@ -15,7 +17,8 @@ export const appConfig: ApplicationConfig = {
   providers: [
     provideBrowserGlobalErrorListeners(),
     provideRouter(routes),
-    provideHttpClient(withInterceptors([authInterceptor])),
+    provideHttpClient(withInterceptors([authInterceptor, errorInterceptor])),
+    MessageService,
     providePrimeNG({
       theme: {
         preset: Aura,
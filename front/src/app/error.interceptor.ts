@@ -0,0 +1,30 @@
+import { HttpErrorResponse, HttpInterceptorFn } from '@angular/common/http';
+import { inject } from '@angular/core';
+import { MessageService } from 'primeng/api';
+import { catchError, throwError } from 'rxjs';
+
+/** Body of API errors, `request_id` finds the error in the server log. */
+interface ApiError {
+  message?: string;
+  request_id?: string;
+}
+
+export const errorInterceptor: HttpInterceptorFn = (req, next) => {
+  const messageService = inject(MessageService);
+
+  return next(req).pipe(
+    catchError((error: HttpErrorResponse) => {
+      // 401 is handled by the auth interceptor, sign-in shows its own errors
+      if (error.status !== 401 && !req.url.includes('/auth/')) {
+        const body: ApiError | null = typeof error.error === 'object' ? error.error : null;
+        const message = body?.message ?? 'Не вдалося виконати запит';
+        messageService.add({
+          severity: 'error',
+          summary: `Помилка ${error.status || ''}`.trim(),
+          detail: body?.request_id ? `${message} (запит ${body.request_id})` : message,
+        });
+      }
+      return throwError(() => error);
+    }),
+  );
+};
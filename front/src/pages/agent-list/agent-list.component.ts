@@ -4,8 +4,10 @@ import { FormControl } from '@angular/forms';
 import { ActivatedRoute, Router } from '@angular/router';
 import { sharedImports } from '../../shared/shared-imports';
 import { AgentService } from './agent.service';
-import { debounceTime, delay, startWith, switchMap, tap } from 'rxjs';
+import { BehaviorSubject, debounceTime, delay, startWith, switchMap, tap } from 'rxjs';
+import { TableLazyLoadEvent } from 'primeng/table';
 import { fakeLoadingDelay } from '../../shared/shared-delay';
+import { PageRequest, lazyPage, samePage } from '../../shared/models/page.model';
 
 @Component({
   selector: 'app-agent-list',
@@ -21,6 +23,8 @@ export class AgentListComponent {
 
   public searchControl = new FormControl(this.initialSearch);
   public loading = true;
+  public pageSize = 50;
+  private page$ = new BehaviorSubject<PageRequest>({ offset: 0, limit: this.pageSize });
 
   public agents = toSignal(
     this.searchControl.valueChanges.pipe(
@@ -32,15 +36,32 @@ export class AgentListComponent {
           queryParamsHandling: 'merge',
           replaceUrl: true,
         });
+        // A new search starts from the first page, keeping the sort
+        this.page$.next({ ...this.page$.value, offset: 0 });
       }),
-      switchMap((search) => {
-        this.loading = true;
-        return this.agentService.getAll(search ?? '').pipe(
-          tap(() => (this.loading = false)),
-          delay(fakeLoadingDelay),
-        );
-      }),
+      switchMap((search) =>
+        this.page$.pipe(
+          switchMap((page) => {
+            this.loading = true;
+            return this.agentService.getAll(page, search ?? '').pipe(
+              tap(() => (this.loading = false)),
+              delay(fakeLoadingDelay),
+            );
+          }),
+        ),
+      ),
     ),
   );
   public skeletonRows = Array(13).fill({});
+
+  public get offset(): number {
+    return this.page$.value.offset;
+  }
+
+  public onLazyLoad(event: TableLazyLoadEvent) {
+    const page = lazyPage(event, this.pageSize);
+    if (!samePage(page, this.page$.value)) {
+      this.page$.next(page);
+    }
+  }
 }
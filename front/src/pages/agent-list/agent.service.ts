@@ -2,6 +2,7 @@ import { Injectable, inject } from '@angular/core';
 import { HttpClient, HttpParams } from '@angular/common/http';
 import { Observable } from 'rxjs';
 import { environment } from '../../environments/environment';
+import { Page, PageRequest, pageParams } from '../../shared/models/page.model';
 
 export interface AgentListItem {
   id: number;
@@ -14,11 +15,11 @@ export class AgentService {
   private http = inject(HttpClient);
   private url = `${environment.apiUrl}/agents`;
 
-  getAll(search?: string): Observable<AgentListItem[]> {
-    let params = new HttpParams();
+  getAll(page: PageRequest, search?: string): Observable<Page<AgentListItem>> {
+    let params = pageParams(new HttpParams(), page);
     if (search) {
       params = params.set('search', search);
     }
-    return this.http.get<AgentListItem[]>(this.url, { params });
+    return this.http.get<Page<AgentListItem>>(this.url, { params });
   }
 }
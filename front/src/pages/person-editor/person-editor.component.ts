@@ -20,6 +20,7 @@ export class PersonEditorComponent {
 
   public editMode = signal(false);
   public personId = signal<number | null>(null);
+  private version: number | null = null;
   public loading = signal(false);
 
   public personControl = new FormControl<PersonEditorValue>(null);
@@ -37,6 +38,7 @@ export class PersonEditorComponent {
   private loadPerson(id: number) {
     this.personService.getById(id).subscribe({
       next: (person) => {
+        this.version = person.version;
         this.personControl.setValue({
           kind: 'New',
           first_name: person.first_name,
@@ -82,7 +84,7 @@ export class PersonEditorComponent {
     };
 
     if (this.editMode() && this.personId()) {
-      this.personService.update(this.personId()!, personData).subscribe(() => {
+      this.personService.update(this.personId()!, personData, this.version!).subscribe(() => {
         this.router.navigate(['/people', this.personId()]);
       });
     } else {
@@ -4,10 +4,12 @@ import { FormControl } from '@angular/forms';
 import { ActivatedRoute, Router } from '@angular/router';
 import { sharedImports } from '../../shared/shared-imports';
 import { PersonService } from './person.service';
-import { combineLatest, debounceTime, delay, startWith, switchMap, tap } from 'rxjs';
+import { BehaviorSubject, combineLatest, debounceTime, delay, startWith, switchMap, tap } from 'rxjs';
+import { TableLazyLoadEvent } from 'primeng/table';
 import { fakeLoadingDelay } from '../../shared/shared-delay';
 import { PersonStatus, personStatusValues } from '../../shared/models/person.model';
 import { getPersonStatusLocalizedName } from '../../shared/pipes/person-localization.pipe';
+import { PageRequest, lazyPage, samePage } from '../../shared/models/page.model';
 
 @Component({
   selector: 'app-person-list',
@@ -31,6 +33,8 @@ export class PersonListComponent {
     value: x,
   }));
   public loading = true;
+  public pageSize = 30;
+  private page$ = new BehaviorSubject<PageRequest>({ offset: 0, limit: this.pageSize });
 
   public people = toSignal(
     combineLatest([
@@ -47,15 +51,32 @@ export class PersonListComponent {
           queryParamsHandling: 'merge',
           replaceUrl: true,
         });
+        // A new search starts from the first page, keeping the sort
+        this.page$.next({ ...this.page$.value, offset: 0 });
       }),
-      switchMap(([search, statuses]) => {
-        this.loading = true;
-        return this.personService.getAll(search ?? '', statuses ?? []).pipe(
-          tap(() => (this.loading = false)),
-          delay(fakeLoadingDelay),
-        );
-      }),
+      switchMap(([search, statuses]) =>
+        this.page$.pipe(
+          switchMap((page) => {
+            this.loading = true;
+            return this.personService.getAll(page, search ?? '', statuses ?? []).pipe(
+              tap(() => (this.loading = false)),
+              delay(fakeLoadingDelay),
+            );
+          }),
+        ),
+      ),
     ),
   );
   public skeletonRows = Array(13).fill({});
+
+  public get offset(): number {
+    return this.page$.value.offset;
+  }
+
+  public onLazyLoad(event: TableLazyLoadEvent) {
+    const page = lazyPage(event, this.pageSize);
+    if (!samePage(page, this.page$.value)) {
+      this.page$.next(page);
+    }
+  }
 }
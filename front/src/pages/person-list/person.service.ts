@@ -3,21 +3,22 @@ import { HttpClient, HttpParams } from '@angular/common/http';
 import { Observable } from 'rxjs';
 import { environment } from '../../environments/environment';
 import { CreatePersonDto, PersonDto, PersonWithPolicies } from '../../shared/person-editor-control/person.model';
+import { Page, PageRequest, pageParams } from '../../shared/models/page.model';
 
 @Injectable({ providedIn: 'root' })
 export class PersonService {
   private http = inject(HttpClient);
   private url = `${environment.apiUrl}/people`;
 
-  getAll(search?: string, statuses?: string[]): Observable<PersonDto[]> {
-    let params = new HttpParams();
+  getAll(page: PageRequest, search?: string, statuses?: string[]): Observable<Page<PersonDto>> {
+    let params = pageParams(new HttpParams(), page);
     if (search) {
       params = params.set('search', search);
     }
     if (statuses?.length) {
       params = params.set('statuses', statuses.join(','));
     }
-    return this.http.get<PersonDto[]>(this.url, { params });
+    return this.http.get<Page<PersonDto>>(this.url, { params });
   }
 
   getById(id: number): Observable<PersonWithPolicies> {
@@ -28,7 +29,10 @@ export class PersonService {
     return this.http.post<PersonDto>(this.url, person);
   }
 
-  update(id: number, person: CreatePersonDto): Observable<PersonDto> {
-    return this.http.put<PersonDto>(`${this.url}/${id}`, person);
+  /** Refused with 409 when the person changed since `version` was read. */
+  update(id: number, person: CreatePersonDto, version: number): Observable<PersonDto> {
+    return this.http.put<PersonDto>(`${this.url}/${id}`, person, {
+      headers: { 'If-Match': `"${version}"` },
+    });
   }
 }
@@ -1,7 +1,8 @@
-import { Observable } from 'rxjs';
+import { Observable, map } from 'rxjs';
 import { Injectable, inject } from '@angular/core';
 import { HttpClient } from '@angular/common/http';
 import { Agent } from '../../shared/models/agent.model';
+import { Page, maxPageLimit } from '../../shared/models/page.model';
 import { environment } from '../../environments/environment';
 
 @Injectable({ providedIn: 'root' })
@@ -10,6 +11,8 @@ export class AgentService {
   private baseUrl = `${environment.apiUrl}/agents`;
 
   public getAll(): Observable<Agent[]> {
-    return this.http.get<Agent[]>(this.baseUrl);
+    return this.http
+      .get<Page<Agent>>(this.baseUrl, { params: { limit: maxPageLimit } })
+      .pipe(map((page) => page.items));
   }
 }
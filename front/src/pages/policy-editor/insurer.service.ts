@@ -0,0 +1,15 @@
+import { Observable } from 'rxjs';
+import { Injectable, inject } from '@angular/core';
+import { HttpClient } from '@angular/common/http';
+import { Insurer } from '../../shared/models/insurer.model';
+import { environment } from '../../environments/environment';
+
+@Injectable({ providedIn: 'root' })
+export class InsurerService {
+  private http = inject(HttpClient);
+  private baseUrl = `${environment.apiUrl}/insurers`;
+
+  public getAll(): Observable<Insurer[]> {
+    return this.http.get<Insurer[]>(this.baseUrl);
+  }
+}
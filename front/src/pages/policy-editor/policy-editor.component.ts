@@ -28,7 +28,9 @@ import {
 import { CreatePolicyRequest, PolicyFull, UpdatePolicyRequest } from './policy.model';
 import { PolicyEditorService } from './policy.service';
 import { AgentService } from './agent.service';
+import { InsurerService } from './insurer.service';
 import { Agent } from '../../shared/models/agent.model';
+import { Insurer } from '../../shared/models/insurer.model';
 import { ActivatedRoute, Router } from '@angular/router';
 
 @Component({
@@ -39,16 +41,20 @@ import { ActivatedRoute, Router } from '@angular/router';
 export class PolicyEditorComponent {
   private policyService = inject(PolicyEditorService);
   private agentService = inject(AgentService);
+  private insurerService = inject(InsurerService);
   private router = inject(Router);
   private route = inject(ActivatedRoute);
 
   public editMode = signal(false);
   public policyId = signal<number | null>(null);
+  private version: number | null = null;
   public loading = signal(false);
 
   public agents = signal<Agent[]>([]);
   public agentsControl = new FormControl<Agent[]>([]);
 
+  public insurers = signal<Insurer[]>([]);
+
   public holderControl = new FormControl<PersonEditorValue>(null);
   public carControl = new FormControl<CarEditorValue>(null);
 
@@ -58,6 +64,7 @@ export class PolicyEditorComponent {
     startDate: new FormControl<string | null>(null, [Validators.required]),
     endDate: new FormControl<string | null>(null),
     type: new FormControl<PolicyType | null>(null, [Validators.required]),
+    insurerId: new FormControl<number | null>(null, [Validators.required]),
     status: new FormControl<PolicyStatus>('Active', [Validators.required]),
   });
 
@@ -92,6 +99,9 @@ export class PolicyEditorComponent {
     this.agentService.getAll().subscribe((agents) => {
       this.agents.set(agents);
     });
+    this.insurerService.getAll().subscribe((insurers) => {
+      this.insurers.set(insurers);
+    });
 
     const id = this.route.snapshot.paramMap.get('id');
     if (id) {
@@ -121,11 +131,13 @@ export class PolicyEditorComponent {
   }
 
   private populateForm(policy: PolicyFull) {
+    this.version = policy.version;
     this.generalGroup.controls.series.setValue(policy.series);
     this.generalGroup.controls.number.setValue(policy.number);
     this.generalGroup.controls.startDate.setValue(policy.start_date);
     this.generalGroup.controls.endDate.setValue(policy.end_date);
     this.generalGroup.controls.type.setValue(policy.policy_type);
+    this.generalGroup.controls.insurerId.setValue(policy.insurer.id);
     this.generalGroup.controls.status.setValue(policy.status);
 
     // Disable type change in edit mode
@@ -200,6 +212,7 @@ export class PolicyEditorComponent {
       series: this.generalGroup.controls.series.value!,
       number: this.generalGroup.controls.number.value!,
       holder,
+      insurer_id: this.generalGroup.controls.insurerId.value!,
       start_date: this.generalGroup.controls.startDate.value!,
       end_date: this.generalGroup.controls.endDate.value,
       status: this.generalGroup.controls.status.value!,
@@ -275,9 +288,11 @@ export class PolicyEditorComponent {
     const request = this.getPolicyRequest();
 
     if (this.editMode() && this.policyId()) {
-      this.policyService.update(this.policyId()!, request as UpdatePolicyRequest).subscribe(() => {
-        this.router.navigate(['/policies', this.policyId()]);
-      });
+      this.policyService
+        .update(this.policyId()!, request as UpdatePolicyRequest, this.version!)
+        .subscribe(() => {
+          this.router.navigate(['/policies', this.policyId()]);
+        });
     } else {
       this.policyService.create(request as CreatePolicyRequest).subscribe(() => {
         this.router.navigate(['/policies']);
@@ -7,6 +7,7 @@ import {
   OsagoZone,
 } from '../../shared/models/policy.model';
 import { Agent } from '../../shared/models/agent.model';
+import { Insurer } from '../../shared/models/insurer.model';
 
 // === Policy Type Specific Data (for requests) ===
 
@@ -45,6 +46,7 @@ export type PolicyData = GreenCardData | MedassistanceData | OsagoData;
 
 export type CreatePolicyRequest = {
   holder: PersonRef;
+  insurer_id: number;
   series: string;
   number: string;
   start_date: string;
@@ -106,10 +108,12 @@ export type PolicyDetails = GreenCardDetails | MedassistanceDetails | OsagoDetai
 export type PolicyFull = {
   id: number;
   holder: PersonDto;
+  insurer: Insurer;
   series: string;
   number: string;
   start_date: string;
   end_date: string | null;
   status: PolicyStatus;
   agents: Agent[];
+  version: number;
 } & PolicyDetails;
@@ -22,7 +22,10 @@ export class PolicyEditorService {
     return this.http.post<CreatePolicyResponse>(this.url, request);
   }
 
-  update(id: number, request: UpdatePolicyRequest): Observable<PolicyFull> {
-    return this.http.put<PolicyFull>(`${this.url}/${id}`, request);
+  /** Refused with 409 when the policy changed since `version` was read. */
+  update(id: number, request: UpdatePolicyRequest, version: number): Observable<PolicyFull> {
+    return this.http.put<PolicyFull>(`${this.url}/${id}`, request, {
+      headers: { 'If-Match': `"${version}"` },
+    });
   }
 }
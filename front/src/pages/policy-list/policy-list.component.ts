@@ -2,12 +2,15 @@ import { Component, inject, signal } from '@angular/core';
 import { ActivatedRoute, Params, Router } from '@angular/router';
 import { sharedImports } from '../../shared/shared-imports';
 import { PolicyService } from './policy.service';
-import { finalize, map, switchMap, tap } from 'rxjs';
+import { finalize, switchMap, tap } from 'rxjs';
 import {
   PolicyTableComponent,
   PolicyTableFilter,
 } from '../../shared/policy-table/policy-table.component';
 import { PolicyShort, PolicyStatus, PolicyType } from '../../shared/models/policy.model';
+import { Page, PageRequest } from '../../shared/models/page.model';
+
+const pageSize = 50;
 
 @Component({
   selector: 'app-policy-list',
@@ -21,26 +24,46 @@ export class PolicyListComponent {
 
   public loading = true;
   public filter: PolicyTableFilter = null!;
-  public policies = signal<PolicyShort[] | null>(null);
+  public page: PageRequest = { offset: 0, limit: pageSize };
+  public policies = signal<Page<PolicyShort> | null>(null);
 
   constructor() {
     this.route.queryParams
       .pipe(
-        map(deserializeFilter),
-        tap((newFilter) => {
-          this.filter = newFilter;
+        tap((queryParams) => {
+          this.filter = deserializeFilter(queryParams);
+          this.page = {
+            offset: Math.max(+queryParams['offset'] || 0, 0),
+            limit: pageSize,
+            sort: queryParams['sort'] || undefined,
+            order: queryParams['order'] === 'desc' ? 'desc' : undefined,
+          };
           this.loading = true;
         }),
-        switchMap((filter) =>
-          this.policyService.getAll(filter).pipe(finalize(() => (this.loading = false))),
+        switchMap(() =>
+          this.policyService
+            .getAll(this.filter, this.page)
+            .pipe(finalize(() => (this.loading = false))),
         ),
       )
       .subscribe((policies) => this.policies.set(policies));
   }
 
   public onFilterChange(filter: PolicyTableFilter) {
-    const newQueryParams = serializeFilter(filter);
-    this.router.navigate(['policies'], { queryParams: newQueryParams });
+    // A new filter starts from the first page, keeping the sort
+    this.router.navigate(['policies'], {
+      queryParams: { ...serializeFilter(filter), ...serializeSort(this.page) },
+    });
+  }
+
+  public onPageChange(page: PageRequest) {
+    this.router.navigate(['policies'], {
+      queryParams: {
+        ...serializeFilter(this.filter),
+        ...serializeSort(page),
+        offset: page.offset || null,
+      },
+    });
   }
 }
 
@@ -69,6 +92,13 @@ function deserializeFilter(queryParams: Params): PolicyTableFilter {
   };
 }
 
+function serializeSort(page: PageRequest): Params {
+  return {
+    sort: page.sort || null,
+    order: page.sort && page.order === 'desc' ? 'desc' : null,
+  };
+}
+
 function serializeFilter(filter: PolicyTableFilter): Params {
   return {
     number: filter.number || null,
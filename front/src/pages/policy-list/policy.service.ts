@@ -3,6 +3,7 @@ import { HttpClient, HttpParams } from '@angular/common/http';
 import { Observable } from 'rxjs';
 import { environment } from '../../environments/environment';
 import { PolicyShort } from '../../shared/models/policy.model';
+import { Page, PageRequest, pageParams } from '../../shared/models/page.model';
 import { PolicyTableFilter } from '../../shared/policy-table/policy-table.component';
 
 @Injectable({ providedIn: 'root' })
@@ -10,8 +11,8 @@ export class PolicyService {
   private http = inject(HttpClient);
   private url = `${environment.apiUrl}/policies`;
 
-  getAll(filter: PolicyTableFilter): Observable<PolicyShort[]> {
-    let params = new HttpParams();
+  getAll(filter: PolicyTableFilter, page: PageRequest): Observable<Page<PolicyShort>> {
+    let params = pageParams(new HttpParams(), page);
     if (filter.number) {
       params = params.set('number', filter.number);
     }
@@ -42,6 +43,6 @@ export class PolicyService {
     if (filter.agentIds.length) {
       params = params.set('agent_ids', filter.agentIds.join(','));
     }
-    return this.http.get<PolicyShort[]>(this.url, { params });
+    return this.http.get<Page<PolicyShort>>(this.url, { params });
   }
 }
@@ -14,7 +14,7 @@ import {
 } from '@angular/forms';
 import { AutoCompleteCompleteEvent } from 'primeng/autocomplete';
 import { CarSearchService } from './car-search.service';
-import { CarRef, CreateCarDto } from '../models/car.model';
+import { CarDto, CarRef } from '../models/car.model';
 
 export type CarEditorValue = CarRef | null;
 
@@ -43,7 +43,7 @@ export class CarEditorControlComponent implements ControlValueAccessor, Validato
 
   public existingCarIdControl = new FormControl<number | null>(null);
 
-  private originalCarData: CreateCarDto | null = null;
+  private originalCarData: Omit<CarDto, 'id'> | null = null;
 
   public carGroup = new FormGroup({
     chassis: new FormControl('', [Validators.required]),
@@ -111,6 +111,7 @@ export class CarEditorControlComponent implements ControlValueAccessor, Validato
           unladen_weight: car.unladen_weight,
           laden_weight: car.laden_weight,
           seats: car.seats,
+          version: car.version,
         };
 
         this.emitValue();
@@ -193,6 +194,7 @@ export class CarEditorControlComponent implements ControlValueAccessor, Validato
         this.onChange({
           kind: 'ExistingWithUpdates',
           id: existingId,
+          version: this.originalCarData.version,
           chassis: v.chassis!,
           make: v.make!,
           model: v.model!,
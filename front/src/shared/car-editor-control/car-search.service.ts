@@ -3,6 +3,7 @@ import { Injectable, inject } from '@angular/core';
 import { HttpClient } from '@angular/common/http';
 import { CarDto } from '../models/car.model';
 import { environment } from '../../environments/environment';
+import { Page } from '../models/page.model';
 
 interface CarSearchResult {
   id: number;
@@ -16,10 +17,10 @@ export class CarSearchService {
 
   public search(query: string): Observable<SelectOption<number>[]> {
     return this.http
-      .get<CarSearchResult[]>(`${this.baseUrl}/search`, {
+      .get<Page<CarSearchResult>>(`${this.baseUrl}/search`, {
         params: { q: query },
       })
-      .pipe(map((results) => results.map((r) => ({ value: r.id, label: r.label }))));
+      .pipe(map((results) => results.items.map((r) => ({ value: r.id, label: r.label }))));
   }
 
   public getCar(id: number): Observable<CarDto | null> {
@@ -11,9 +11,11 @@ export interface CarDto {
   unladen_weight: number;
   laden_weight: number;
   seats: number;
+  /** Sent back with updates, see `If-Match` */
+  version: number;
 }
 
-export type CreateCarDto = Omit<CarDto, 'id'>;
+export type CreateCarDto = Omit<CarDto, 'id' | 'version'>;
 
 // === Car Reference (matches backend CarRef) ===
 
@@ -29,6 +31,7 @@ export type CarRefNew = {
 export type CarRefExistingWithUpdates = {
   kind: 'ExistingWithUpdates';
   id: number;
+  version: number;
 } & CreateCarDto;
 
 export type CarRef = CarRefExisting | CarRefNew | CarRefExistingWithUpdates;
@@ -0,0 +1,4 @@
+export interface Insurer {
+  id: number;
+  name: string;
+}
@@ -0,0 +1,46 @@
+import { HttpParams } from '@angular/common/http';
+import { TableLazyLoadEvent } from 'primeng/table';
+
+// === Pagination (matches backend Page and PageQuery) ===
+
+export interface Page<T> {
+  items: T[];
+  /** Matches of the whole query, not just this page */
+  total: number;
+  limit: number;
+  offset: number;
+}
+
+export interface PageRequest {
+  offset: number;
+  limit: number;
+  /** A column the endpoint allows sorting by, its default order when absent */
+  sort?: string;
+  order?: 'asc' | 'desc';
+}
+
+/** The most one request returns, e.g. to fill a dropdown with every agent. */
+export const maxPageLimit = 500;
+
+export function pageParams(params: HttpParams, page: PageRequest): HttpParams {
+  params = params.set('offset', page.offset).set('limit', page.limit);
+  if (page.sort) {
+    params = params.set('sort', page.sort).set('order', page.order ?? 'asc');
+  }
+  return params;
+}
+
+/** The page and sort a lazy table asks for. */
+export function lazyPage(event: TableLazyLoadEvent, limit: number): PageRequest {
+  const sort = typeof event.sortField === 'string' ? event.sortField : undefined;
+  return {
+    offset: event.first ?? 0,
+    limit,
+    sort,
+    order: sort ? (event.sortOrder === -1 ? 'desc' : 'asc') : undefined,
+  };
+}
+
+export function samePage(a: PageRequest, b: PageRequest): boolean {
+  return a.offset === b.offset && a.limit === b.limit && a.sort === b.sort && a.order === b.order;
+}
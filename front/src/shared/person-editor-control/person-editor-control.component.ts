@@ -23,7 +23,7 @@ import {
 } from '@angular/forms';
 import { AutoCompleteCompleteEvent } from 'primeng/autocomplete';
 import { PersonSearchService } from './person-search.service';
-import { CreatePersonDto, PersonRef } from './person.model';
+import { PersonDto, PersonRef } from './person.model';
 import { PersonStatus, Sex } from '../models/person.model';
 import { map } from 'rxjs';
 import { toSignal } from '@angular/core/rxjs-interop';
@@ -58,7 +58,7 @@ export class PersonEditorControlComponent implements ControlValueAccessor, Valid
 
   public existingPersonIdControl = new FormControl<number | null>(null);
 
-  private originalPersonData: CreatePersonDto | null = null;
+  private originalPersonData: Omit<PersonDto, 'id'> | null = null;
 
   public sexOptions: { label: string; value: Sex }[] = [
     { label: 'Чоловіча', value: 'M' },
@@ -154,6 +154,7 @@ export class PersonEditorControlComponent implements ControlValueAccessor, Valid
           phone2: person.phone2,
           email: person.email,
           status: person.status,
+          version: person.version,
         };
 
         this.emitValue();
@@ -240,6 +241,7 @@ export class PersonEditorControlComponent implements ControlValueAccessor, Valid
         this.onChange({
           kind: 'ExistingWithUpdates',
           id: existingId,
+          version: this.originalPersonData.version,
           first_name: v.first_name!,
           first_name_lat: v.first_name_lat || null,
           last_name: v.last_name!,
@@ -16,13 +16,15 @@ export interface PersonDto {
   phone2: string | null;
   email: string;
   status: PersonStatus;
+  /** Sent back with updates, see `If-Match` */
+  version: number;
 }
 
 export interface PersonWithPolicies extends PersonDto {
   policies: PolicyShort[];
 }
 
-export type CreatePersonDto = Omit<PersonDto, 'id'>;
+export type CreatePersonDto = Omit<PersonDto, 'id' | 'version'>;
 
 // === Person Reference (matches backend PersonRef) ===
 
@@ -38,6 +40,7 @@ export type PersonRefNew = {
 export type PersonRefExistingWithUpdates = {
   kind: 'ExistingWithUpdates';
   id: number;
+  version: number;
 } & CreatePersonDto;
 
 export type PersonRef = PersonRefExisting | PersonRefNew | PersonRefExistingWithUpdates;
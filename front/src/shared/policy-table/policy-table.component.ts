@@ -13,7 +13,9 @@ import {
   getPolicyStatusLocalizedName,
   getPolicyTypeLocalizedName,
 } from '../pipes/policy-localization.pipe';
-import { AgentService } from '../../pages/agent-list/agent.service';
+import { AgentService } from '../../pages/policy-editor/agent.service';
+import { PageRequest, lazyPage, samePage } from '../models/page.model';
+import { TableLazyLoadEvent } from 'primeng/table';
 
 export interface PolicyTableFilter {
   number: string | null;
@@ -35,6 +37,10 @@ export class PolicyTableComponent {
   public policies = input.required<PolicyShort[]>();
   public loading = input(false, { transform: booleanAttribute });
   public showHolderColumn = input(true, { transform: booleanAttribute });
+  /** Matches of the filter on every page; the paginator shows when set */
+  public total = input<number | null>(null);
+  public page = input<PageRequest>({ offset: 0, limit: 50 });
+  public pageChange = output<PageRequest>();
 
   public filter = input<PolicyTableFilter>({
     number: null,
@@ -93,6 +99,13 @@ export class PolicyTableComponent {
     });
   }
 
+  public onLazyLoad(event: TableLazyLoadEvent) {
+    const page = lazyPage(event, event.rows ?? this.page().limit);
+    if (!samePage(page, this.page())) {
+      this.pageChange.emit(page);
+    }
+  }
+
   ngOnInit() {
     this.filterForm.controls.number.setValue(this.filter().number);
     this.filterForm.controls.holder.setValue(this.filter().holder);
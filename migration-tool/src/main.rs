@@ -4,6 +4,13 @@ use jetdb::{PageReader, Value, read_catalog, read_table_def, read_table_rows};
 use tokio_postgres::NoTls;
 
 type IdMap = HashMap<i32, i32>;
+type InsurerMap = HashMap<String, i32>;
+
+/// The tenant every migrated database starts with
+const DEFAULT_TENANT: i32 = 1;
+const USAGE: &str = "usage: migration-tool <file.accdb> <pg_conn_string> [tenant_id]";
+const DEFAULT_INSURER: &str = "Невідомий страховик";
+const INSURER_COLUMNS: &[&str] = &["Страхова компанія", "Страховик", "СК"];
 
 fn text_val(val: &Value) -> String {
     match val {
@@ -182,6 +189,12 @@ impl Table {
             .position(|c| c == name)
             .unwrap_or_else(|| panic!("column '{name}' not found in {:?}", self.columns))
     }
+
+    fn idx_any(&self, names: &[&str]) -> Option<usize> {
+        names
+            .iter()
+            .find_map(|name| self.columns.iter().position(|c| c == name))
+    }
 }
 
 async fn query_returning_id(
@@ -202,6 +215,31 @@ async fn exec(client: &tokio_postgres::Client, q: &str) -> bool {
     }
 }
 
+async fn resolve_insurer(
+    client: &tokio_postgres::Client,
+    insurers: &mut InsurerMap,
+    row: &[Value],
+    i_insurer: Option<usize>,
+) -> Result<i32, tokio_postgres::Error> {
+    let name = i_insurer
+        .map(|i| text_val(&row[i]))
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_INSURER.to_string());
+    if let Some(id) = insurers.get(&name) {
+        return Ok(*id);
+    }
+    let id = query_returning_id(
+        client,
+        &format!(
+            "INSERT INTO insurer (name) VALUES ({}) ON CONFLICT (tenant_id, name) DO UPDATE SET name = excluded.name RETURNING id",
+            qs(&name)
+        ),
+    )
+    .await?;
+    insurers.insert(name, id);
+    Ok(id)
+}
+
 async fn link_agent(
     client: &tokio_postgres::Client,
     agent_ids: &IdMap,
@@ -224,8 +262,12 @@ async fn link_agent(
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = std::env::args().collect();
-    let path = args.get(1).ok_or("usage: migration-tool <file.accdb> <pg_conn_string>")?;
-    let pg_conn = args.get(2).ok_or("usage: migration-tool <file.accdb> <pg_conn_string>")?;
+    let path = args.get(1).ok_or(USAGE)?;
+    let pg_conn = args.get(2).ok_or(USAGE)?;
+    let tenant_id = match args.get(3) {
+        Some(id) => id.parse::<i32>().map_err(|_| USAGE)?,
+        None => DEFAULT_TENANT,
+    };
 
     let (client, connection) = tokio_postgres::connect(pg_conn, NoTls).await?;
     tokio::spawn(async move {
@@ -234,14 +276,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
+    let rows = client
+        .query("SELECT name FROM tenant WHERE id = $1", &[&tenant_id])
+        .await?;
+    let tenant: String = rows.first().ok_or("no such tenant")?.get(0);
+    println!("importing into tenant {tenant_id} ({tenant})");
+
+    // The same access as the API has for the tenant: new rows default to it,
+    // and row-level security keeps the pruning below to its data.
+    client
+        .execute(
+            "SELECT set_config('role', 'insure_tenant', false), set_config('app.tenant_id', $1, false)",
+            &[&tenant_id.to_string()],
+        )
+        .await?;
+
     println!("pruning existing data...");
     for table in [
+        "webhook_attempt",
+        "webhook_delivery",
+        "webhook_event",
+        "attachment",
+        "policy_payment",
+        "notification_log",
+        "sms_message",
+        "birthday_greeting",
         "agent_policy",
         "osago_policy",
         "medassistance_policy_member",
         "medassistance_policy",
         "green_card_policy",
         "policy",
+        "insurer",
         "car",
         "agent",
         "person",
@@ -254,10 +320,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let person_ids = migrate_people(&client, &mut reader).await?;
     let agent_ids = migrate_agents(&client, &mut reader).await?;
     let car_ids = migrate_cars(&client, &mut reader).await?;
-    migrate_green_card(&client, &mut reader, &person_ids, &car_ids, &agent_ids).await?;
-    let ma_policy_ids = migrate_medassistance(&client, &mut reader, &person_ids, &agent_ids).await?;
+    let mut insurers = InsurerMap::new();
+    migrate_green_card(
+        &client,
+        &mut reader,
+        &person_ids,
+        &car_ids,
+        &agent_ids,
+        &mut insurers,
+    )
+    .await?;
+    let ma_policy_ids =
+        migrate_medassistance(&client, &mut reader, &person_ids, &agent_ids, &mut insurers).await?;
     migrate_medassistance_members(&client, &mut reader, &ma_policy_ids, &person_ids).await?;
-    migrate_osago(&client, &mut reader, &person_ids, &car_ids, &agent_ids).await?;
+    migrate_osago(
+        &client,
+        &mut reader,
+        &person_ids,
+        &car_ids,
+        &agent_ids,
+        &mut insurers,
+    )
+    .await?;
 
     print_summary(&client).await?;
     Ok(())
@@ -466,6 +550,7 @@ async fn migrate_green_card(
     person_ids: &IdMap,
     car_ids: &IdMap,
     agent_ids: &IdMap,
+    insurers: &mut InsurerMap,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("migrating green card policies...");
     let t = Table::open(reader, "Зеленая карта")?;
@@ -482,6 +567,7 @@ async fn migrate_green_card(
     let i_premium = t.idx("Страхова премія");
     let i_agent = t.idx("Агент");
     let i_status = t.idx("Статус дії");
+    let i_insurer = t.idx_any(INSURER_COLUMNS);
 
     let mut ok = 0u64;
     let mut skip = 0u64;
@@ -521,11 +607,12 @@ async fn migrate_green_card(
         let (period_units, period_unit) = parse_period(&period_str);
         let premium = money_to_int(&row[i_premium]);
         let status = map_policy_status(&row[i_status]);
+        let insurer_id = resolve_insurer(client, insurers, row, i_insurer).await?;
 
         let q = format!(
             "WITH ins AS (
-                INSERT INTO policy (type, holder_id, series, number, start_date, end_date, status)
-                VALUES ('green_card'::policy_type, {holder_new}, {}, {}, {}, {}, '{status}'::policy_status)
+                INSERT INTO policy (type, holder_id, insurer_id, series, number, start_date, end_date, status)
+                VALUES ('green_card'::policy_type, {holder_new}, {insurer_id}, {}, {}, {}, {}, '{status}'::policy_status)
                 RETURNING id
             )
             INSERT INTO green_card_policy (id, territory, period_in_units, period_unit, premium, car_id)
@@ -556,6 +643,7 @@ async fn migrate_medassistance(
     reader: &mut PageReader,
     person_ids: &IdMap,
     agent_ids: &IdMap,
+    insurers: &mut InsurerMap,
 ) -> Result<IdMap, Box<dyn std::error::Error>> {
     println!("migrating medassistance policies...");
     let members = Table::open(reader, "Многие МА")?;
@@ -573,6 +661,7 @@ async fn migrate_medassistance(
     let i_premium = t.idx("Страхова премія");
     let i_status = t.idx("Статус");
     let i_agent = t.idx("Агент");
+    let i_insurer = t.idx_any(INSURER_COLUMNS);
 
     let mi_policy = members.idx("Код полиса");
     let mi_person = members.idx("КодОС");
@@ -623,11 +712,12 @@ async fn migrate_medassistance(
         let program = text_or(&row[i_program], "STANDART");
         let premium = money_to_int(&row[i_premium]);
         let status = map_policy_status(&row[i_status]);
+        let insurer_id = resolve_insurer(client, insurers, row, i_insurer).await?;
 
         let q = format!(
             "WITH ins AS (
-                INSERT INTO policy (type, holder_id, series, number, start_date, end_date, status)
-                VALUES ('medassistance'::policy_type, {holder_new}, {}, {}, {}, {}, '{status}'::policy_status)
+                INSERT INTO policy (type, holder_id, insurer_id, series, number, start_date, end_date, status)
+                VALUES ('medassistance'::policy_type, {holder_new}, {insurer_id}, {}, {}, {}, {}, '{status}'::policy_status)
                 RETURNING id
             )
             INSERT INTO medassistance_policy (id, territory, period_days, premium, payout, program)
@@ -701,6 +791,7 @@ async fn migrate_osago(
     person_ids: &IdMap,
     car_ids: &IdMap,
     agent_ids: &IdMap,
+    insurers: &mut InsurerMap,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("migrating osago policies...");
     let t = Table::open(reader, "ОСАГО")?;
@@ -717,6 +808,7 @@ async fn migrate_osago(
     let i_premium = t.idx("Стоимость");
     let i_status = t.idx("СтатусПолісу");
     let i_agent = t.idx("Агент");
+    let i_insurer = t.idx_any(INSURER_COLUMNS);
 
     let mut ok = 0u64;
     let mut skip = 0u64;
@@ -752,11 +844,12 @@ async fn migrate_osago(
         let zone = map_osago_zone(&row[i_zone]);
         let premium = money_to_int(&row[i_premium]);
         let status = map_policy_status(&row[i_status]);
+        let insurer_id = resolve_insurer(client, insurers, row, i_insurer).await?;
 
         let q = format!(
             "WITH ins AS (
-                INSERT INTO policy (type, holder_id, series, number, start_date, end_date, status)
-                VALUES ('osago'::policy_type, {holder_new}, {}, {}, {}, {}, '{status}'::policy_status)
+                INSERT INTO policy (type, holder_id, insurer_id, series, number, start_date, end_date, status)
+                VALUES ('osago'::policy_type, {holder_new}, {insurer_id}, {}, {}, {}, {}, '{status}'::policy_status)
                 RETURNING id
             )
             INSERT INTO osago_policy (id, period_in_units, period_unit, car_id, zone, exempt, premium)
@@ -786,6 +879,7 @@ async fn print_summary(client: &tokio_postgres::Client) -> Result<(), Box<dyn st
     for table in [
         "person",
         "agent",
+        "insurer",
         "car",
         "policy",
         "green_card_policy",